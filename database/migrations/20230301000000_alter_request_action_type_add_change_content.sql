@@ -0,0 +1,2 @@
+-- Add migration script here
+alter type request_action_type add value 'change_content';
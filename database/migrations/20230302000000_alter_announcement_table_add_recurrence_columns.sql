@@ -0,0 +1,4 @@
+-- Add migration script here
+alter table "announcement"
+add column "recurrence" jsonb,
+add column "occurrence_active" boolean not null default false;
@@ -0,0 +1,15 @@
+-- Add migration script here
+create type job_run_trigger as enum ('scheduled', 'manual');
+create type job_run_outcome as enum ('running', 'succeeded', 'failed');
+
+create table "job_run" (
+  id serial primary key,
+  job_name text not null,
+  trigger job_run_trigger not null,
+  outcome job_run_outcome not null default 'running',
+  error text,
+  started_at timestamptz not null default now(),
+  finished_at timestamptz
+);
+
+create index "job_run_job_name_started_at_idx" on "job_run" ("job_name", "started_at" desc);
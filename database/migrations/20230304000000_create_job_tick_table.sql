@@ -0,0 +1,7 @@
+-- Add migration script here
+alter type job_run_trigger add value 'catch_up';
+
+create table "job_tick" (
+  job_name text primary key,
+  last_tick_at timestamptz not null
+);
@@ -0,0 +1,12 @@
+-- Add migration script here
+create table "outbox" (
+  id serial primary key,
+  queue_name text not null,
+  payload text not null,
+  attempts integer not null default 0,
+  last_error text,
+  created_at timestamptz not null default now(),
+  delivered_at timestamptz
+);
+
+create index "outbox_undelivered_idx" on "outbox" ("id") where "delivered_at" is null;
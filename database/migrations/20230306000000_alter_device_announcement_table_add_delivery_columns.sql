@@ -0,0 +1,7 @@
+-- Add migration script here
+create type announcement_delivery_status as enum ('pending', 'received', 'media_downloaded', 'playing', 'failed');
+
+alter table "device_announcement"
+  add column "delivery_status" announcement_delivery_status not null default 'pending',
+  add column "delivery_error" text,
+  add column "delivery_updated_at" timestamptz;
@@ -0,0 +1,3 @@
+-- Add migration script here
+alter table "announcement"
+add column "is_emergency" boolean not null default false;
@@ -0,0 +1,14 @@
+-- Add migration script here
+create table "announcement_target" (
+  id serial primary key,
+
+  announcement_id integer not null references announcement(id),
+  building_id integer references building(id),
+  floor_id integer references floor(id),
+
+  created_at timestamptz not null default now(),
+
+  check (("building_id" is null) <> ("floor_id" is null))
+);
+
+create index "announcement_target_announcement_id_idx" on "announcement_target" ("announcement_id");
@@ -0,0 +1,3 @@
+-- Add migration script here
+alter table "device_announcement"
+add column "targeted" boolean not null default false;
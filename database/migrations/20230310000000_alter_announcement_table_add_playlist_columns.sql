@@ -0,0 +1,5 @@
+-- Add migration script here
+alter table "announcement"
+add column "priority" integer not null default 0,
+add column "weight" integer not null default 1 check ("weight" > 0),
+add column "display_duration" double precision check ("display_duration" > 0);
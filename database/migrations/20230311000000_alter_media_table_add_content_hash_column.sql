@@ -0,0 +1,3 @@
+-- Add migration script here
+alter table "media"
+add column "content_hash" text;
@@ -0,0 +1,2 @@
+-- Add migration script here
+alter type announcement_status add value 'draft';
@@ -0,0 +1,2 @@
+-- Add migration script here
+alter table "request" add column "withdrawn_at" timestamptz;
@@ -0,0 +1,72 @@
+-- Add migration script here
+create table "approval_workflow" (
+  id serial primary key,
+
+  building_id integer references building(id) on delete cascade,
+  action request_action_type,
+
+  created_at timestamptz not null default now(),
+  updated_at timestamptz not null default now()
+);
+
+create trigger update_approval_workflow_updated_at_column before update on "approval_workflow" for each row execute procedure update_modified_column();
+
+-- A null building or action matches every building or action, at most one workflow per scope
+create unique index "approval_workflow_building_action_key" on "approval_workflow" ("building_id", "action")
+  where "building_id" is not null and "action" is not null;
+create unique index "approval_workflow_building_key" on "approval_workflow" ("building_id")
+  where "building_id" is not null and "action" is null;
+create unique index "approval_workflow_action_key" on "approval_workflow" ("action")
+  where "building_id" is null and "action" is not null;
+create unique index "approval_workflow_default_key" on "approval_workflow" ((true))
+  where "building_id" is null and "action" is null;
+
+create table "approval_workflow_stage" (
+  id serial primary key,
+
+  workflow_id integer not null references approval_workflow(id) on delete cascade,
+  position integer not null,
+  roles varchar(255)[] not null check (cardinality(roles) > 0)
+);
+
+-- Stages sharing a position can be approved in any order
+create index "approval_workflow_stage_workflow_id_position_idx" on "approval_workflow_stage" ("workflow_id", "position");
+
+create table "request_approval_step" (
+  id serial primary key,
+
+  request_id integer not null references request(id) on delete cascade,
+  position integer not null,
+  roles varchar(255)[] not null,
+
+  approved boolean,
+  approver_id integer references "user"(id),
+  approved_at timestamptz,
+
+  created_at timestamptz not null default now()
+);
+
+create index "request_approval_step_request_id_position_idx" on "request_approval_step" ("request_id", "position");
+
+-- The previous LSC and BM approvals become the default workflow. Both stages share a position so they
+-- can still be approved in any order, and an admin still completes both at once.
+with "default_workflow" as (
+  insert into "approval_workflow" default values returning "id"
+)
+insert into "approval_workflow_stage" ("workflow_id", "position", "roles")
+select "id", 1, array['lsc', 'admin'] from "default_workflow"
+union all
+select "id", 1, array['bm', 'admin'] from "default_workflow";
+
+insert into "request_approval_step" ("request_id", "position", "roles", "approved", "approver_id", "approved_at")
+select "id", 1, array['lsc', 'admin'], "approved_by_lsc", "lsc_approver", case when "approved_by_lsc" is not null then "updated_at" end
+from "request"
+union all
+select "id", 1, array['bm', 'admin'], "approved_by_bm", "bm_approver", case when "approved_by_bm" is not null then "updated_at" end
+from "request";
+
+alter table "request"
+  drop column "approved_by_lsc",
+  drop column "approved_by_bm",
+  drop column "lsc_approver",
+  drop column "bm_approver";
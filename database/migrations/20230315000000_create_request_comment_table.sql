@@ -0,0 +1,15 @@
+-- Add migration script here
+create table "request_comment" (
+  id serial primary key,
+
+  request_id integer not null references request(id) on delete cascade,
+  user_id integer not null references "user"(id),
+  approval_step_id integer references request_approval_step(id) on delete set null,
+
+  body text not null check (length(trim(body)) > 0),
+
+  created_at timestamptz not null default now(),
+  updated_at timestamptz not null default now()
+);
+
+create index "request_comment_request_id_idx" on "request_comment" ("request_id");
@@ -0,0 +1,21 @@
+-- Add migration script here
+create table "approval_delegation" (
+  id serial primary key,
+
+  delegator_id integer not null references "user"(id),
+  delegate_id integer not null references "user"(id),
+
+  start_date timestamptz not null,
+  end_date timestamptz not null,
+
+  created_at timestamptz not null default now(),
+  updated_at timestamptz not null default now(),
+
+  check (delegator_id <> delegate_id),
+  check (start_date <= end_date)
+);
+
+create index "approval_delegation_delegate_id_idx" on "approval_delegation" ("delegate_id");
+
+-- A step decided by a delegate keeps the delegate as approver and the delegating user here
+alter table "request_approval_step" add column "on_behalf_of" integer references "user"(id);
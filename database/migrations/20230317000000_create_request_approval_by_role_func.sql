@@ -0,0 +1,25 @@
+-- Add migration script here
+-- Derives the former "approved_by_lsc" and "approved_by_bm" columns from the recorded approval steps: the
+-- decision of the first stage naming the role, or the outcome of the whole request when no stage names it
+create or replace function request_approval_by_role(integer, varchar)
+returns boolean as $$
+    select case
+        when exists (
+            select 1 from "request_approval_step"
+            where "request_approval_step"."request_id" = $1 and $2 = any("request_approval_step"."roles")
+        ) then (
+            select "request_approval_step"."approved" from "request_approval_step"
+            where "request_approval_step"."request_id" = $1 and $2 = any("request_approval_step"."roles")
+            order by "request_approval_step"."position", "request_approval_step"."id"
+            limit 1
+        )
+        when exists (
+            select 1 from "request_approval_step"
+            where "request_approval_step"."request_id" = $1 and "request_approval_step"."approved" is false
+        ) then false
+        when not exists (
+            select 1 from "request_approval_step"
+            where "request_approval_step"."request_id" = $1 and "request_approval_step"."approved" is not true
+        ) then true
+    end;
+$$ language 'sql' stable;
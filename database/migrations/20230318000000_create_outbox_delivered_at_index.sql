@@ -0,0 +1,2 @@
+-- Add migration script here
+create index "outbox_delivered_at_idx" on "outbox" ("delivered_at") where "delivered_at" is not null;
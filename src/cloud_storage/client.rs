@@ -1,17 +1,19 @@
 use async_process::{Command, Stdio};
 use std::{
-    fs::{create_dir_all, remove_file, File},
+    fs::{self, create_dir_all, remove_file, File},
     io::Write,
 };
 
 use actix_web::web::Bytes;
 use async_trait::async_trait;
+use sha2::{Digest, Sha256};
 
 use crate::features::media::domain::CropArgs;
 
 #[derive(Debug)]
 pub enum TmpFileError {
     WriteError(String),
+    ReadError(String),
     RemoveError(String),
     CropError(String),
 }
@@ -20,6 +22,7 @@ impl std::fmt::Display for TmpFileError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             TmpFileError::WriteError(message) => write!(f, "{}", message),
+            TmpFileError::ReadError(message) => write!(f, "{}", message),
             TmpFileError::RemoveError(message) => write!(f, "{}", message),
             TmpFileError::CropError(message) => write!(f, "{}", message),
         }
@@ -69,6 +72,16 @@ impl TmpFile {
         Ok(())
     }
 
+    /// The hex encoded SHA-256 digest of the file, devices use it to verify their downloads
+    pub fn content_hash(&self) -> Result<String, TmpFileError> {
+        let bytes = match fs::read(self.path.clone()) {
+            Ok(bytes) => bytes,
+            Err(e) => return Err(TmpFileError::ReadError(e.to_string())),
+        };
+
+        Ok(format!("{:x}", Sha256::digest(&bytes)))
+    }
+
     pub fn name(&self) -> String {
         format!("{}.{}", self.filename, self.filetype)
     }
@@ -134,6 +147,9 @@ impl std::fmt::Display for CloudStorageError {
     }
 }
 
+/// How long the URL returned by `get_object` stays valid
+pub const PRESIGNED_URL_EXPIRY_SECS: u64 = 900;
+
 #[async_trait]
 pub trait CloudStorageClient {
     async fn get_object(&self, key: String) -> Result<String, CloudStorageError>;
@@ -4,7 +4,7 @@ use std::time::Duration;
 use async_trait::async_trait;
 use aws_sdk_s3::{presigning::config::PresigningConfig, types::ByteStream, Client};
 
-use super::{CloudStorageClient, CloudStorageError, TmpFile};
+use super::{CloudStorageClient, CloudStorageError, TmpFile, PRESIGNED_URL_EXPIRY_SECS};
 
 pub struct S3Adapter {
     client: Client,
@@ -20,7 +20,7 @@ impl S3Adapter {
 #[async_trait]
 impl CloudStorageClient for S3Adapter {
     async fn get_object(&self, key: String) -> Result<String, CloudStorageError> {
-        let config = match PresigningConfig::expires_in(Duration::from_secs(900)) {
+        let config = match PresigningConfig::expires_in(Duration::from_secs(PRESIGNED_URL_EXPIRY_SECS)) {
             Ok(conf) => conf,
             Err(e) => return Err(CloudStorageError::PresignedRequestError(e.to_string())),
         };
@@ -2,9 +2,13 @@ use std::env;
 use std::error;
 use std::fmt;
 use std::num;
+use std::time::Duration;
 
+use rand::Rng;
 use secrecy::Secret;
 
+use crate::queue::{TrimPolicy, TrimPolicyParseError};
+
 #[derive(Debug)]
 pub enum ConfigError {
     DevelopmentConfigError,
@@ -22,7 +26,7 @@ impl fmt::Display for ConfigError {
                 write!(f, "An error occured in the system environment variable")
             }
             ConfigError::ParsingError => {
-                write!(f, "Something went wrong when parsing an integer")
+                write!(f, "Something went wrong when parsing a value")
             }
         }
     }
@@ -40,6 +44,12 @@ impl From<env::VarError> for ConfigError {
     }
 }
 
+impl From<TrimPolicyParseError> for ConfigError {
+    fn from(_: TrimPolicyParseError) -> Self {
+        ConfigError::ParsingError
+    }
+}
+
 impl From<num::ParseIntError> for ConfigError {
     fn from(_: num::ParseIntError) -> Self {
         ConfigError::ParsingError
@@ -81,6 +91,29 @@ pub struct Configuration {
     pub default_user_role: String,
 
     pub static_base_url: String,
+
+    pub instance_id: String,
+
+    pub queue_max_deliveries: usize,
+    pub queue_retry_backoff_ms: u64,
+    pub queue_claim_min_idle_ms: u64,
+    pub queue_device_trim_policy: TrimPolicy,
+    pub queue_livestream_trim_policy: TrimPolicy,
+}
+
+const DEFAULT_QUEUE_MAX_DELIVERIES: usize = 5;
+const DEFAULT_QUEUE_RETRY_BACKOFF_MS: u64 = 1000;
+const DEFAULT_QUEUE_CLAIM_MIN_IDLE_MS: u64 = 60000;
+const DEFAULT_QUEUE_DEVICE_TRIM_POLICY: TrimPolicy = TrimPolicy::MaxLen(1000);
+const DEFAULT_QUEUE_LIVESTREAM_TRIM_POLICY: TrimPolicy =
+    TrimPolicy::MinAge(Duration::from_secs(86400));
+
+/// Identifies the running process among the other replicas, used as the redis consumer name and lock owner
+fn generate_instance_id() -> String {
+    let hostname = env::var("HOSTNAME").unwrap_or_else(|_| "enchiridion".into());
+    let suffix: u32 = rand::thread_rng().gen();
+
+    format!("{}-{:08x}", hostname, suffix)
 }
 
 impl Configuration {
@@ -128,6 +161,32 @@ impl Configuration {
             default_user_role: dotenvy::var("DEFAULT_USER_ROLE")?,
 
             static_base_url: dotenvy::var("STATIC_BASE_URL")?,
+
+            instance_id: dotenvy::var("INSTANCE_ID")
+                .ok()
+                .filter(|id| !id.is_empty())
+                .unwrap_or_else(generate_instance_id),
+
+            queue_max_deliveries: match dotenvy::var("QUEUE_MAX_DELIVERIES") {
+                Ok(value) if !value.is_empty() => value.parse()?,
+                _ => DEFAULT_QUEUE_MAX_DELIVERIES,
+            },
+            queue_retry_backoff_ms: match dotenvy::var("QUEUE_RETRY_BACKOFF_MS") {
+                Ok(value) if !value.is_empty() => value.parse()?,
+                _ => DEFAULT_QUEUE_RETRY_BACKOFF_MS,
+            },
+            queue_claim_min_idle_ms: match dotenvy::var("QUEUE_CLAIM_MIN_IDLE_MS") {
+                Ok(value) if !value.is_empty() => value.parse()?,
+                _ => DEFAULT_QUEUE_CLAIM_MIN_IDLE_MS,
+            },
+            queue_device_trim_policy: match dotenvy::var("QUEUE_DEVICE_TRIM_POLICY") {
+                Ok(value) if !value.is_empty() => value.parse()?,
+                _ => DEFAULT_QUEUE_DEVICE_TRIM_POLICY,
+            },
+            queue_livestream_trim_policy: match dotenvy::var("QUEUE_LIVESTREAM_TRIM_POLICY") {
+                Ok(value) if !value.is_empty() => value.parse()?,
+                _ => DEFAULT_QUEUE_LIVESTREAM_TRIM_POLICY,
+            },
         })
     }
 
@@ -174,6 +233,32 @@ impl Configuration {
             default_user_role: env::var("DEFAULT_USER_ROLE")?,
 
             static_base_url: env::var("STATIC_BASE_URL")?,
+
+            instance_id: env::var("INSTANCE_ID")
+                .ok()
+                .filter(|id| !id.is_empty())
+                .unwrap_or_else(generate_instance_id),
+
+            queue_max_deliveries: match env::var("QUEUE_MAX_DELIVERIES") {
+                Ok(value) if !value.is_empty() => value.parse()?,
+                _ => DEFAULT_QUEUE_MAX_DELIVERIES,
+            },
+            queue_retry_backoff_ms: match env::var("QUEUE_RETRY_BACKOFF_MS") {
+                Ok(value) if !value.is_empty() => value.parse()?,
+                _ => DEFAULT_QUEUE_RETRY_BACKOFF_MS,
+            },
+            queue_claim_min_idle_ms: match env::var("QUEUE_CLAIM_MIN_IDLE_MS") {
+                Ok(value) if !value.is_empty() => value.parse()?,
+                _ => DEFAULT_QUEUE_CLAIM_MIN_IDLE_MS,
+            },
+            queue_device_trim_policy: match env::var("QUEUE_DEVICE_TRIM_POLICY") {
+                Ok(value) if !value.is_empty() => value.parse()?,
+                _ => DEFAULT_QUEUE_DEVICE_TRIM_POLICY,
+            },
+            queue_livestream_trim_policy: match env::var("QUEUE_LIVESTREAM_TRIM_POLICY") {
+                Ok(value) if !value.is_empty() => value.parse()?,
+                _ => DEFAULT_QUEUE_LIVESTREAM_TRIM_POLICY,
+            },
         })
     }
 }
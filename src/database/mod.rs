@@ -1,5 +1,7 @@
 pub mod database_error;
 pub mod pagination;
+pub mod unit_of_work;
 
 pub use database_error::*;
 pub use pagination::*;
+pub use unit_of_work::*;
@@ -0,0 +1,42 @@
+use async_trait::async_trait;
+use sqlx::{PgConnection, Pool, Postgres, Transaction};
+
+/// A database transaction shared by every repository taking part in a multi step operation.
+/// Nothing is persisted until the unit of work is committed, dropping it rolls the writes back.
+pub struct UnitOfWork {
+    tx: Transaction<'static, Postgres>,
+}
+
+impl UnitOfWork {
+    pub fn connection(&mut self) -> &mut PgConnection {
+        &mut self.tx
+    }
+
+    pub async fn commit(self) -> Result<(), sqlx::Error> {
+        self.tx.commit().await
+    }
+}
+
+#[async_trait]
+pub trait UnitOfWorkFactoryInterface {
+    async fn begin(&self) -> Result<UnitOfWork, sqlx::Error>;
+}
+
+pub struct UnitOfWorkFactory {
+    _db: Pool<Postgres>,
+}
+
+impl UnitOfWorkFactory {
+    pub fn new(_db: Pool<Postgres>) -> Self {
+        UnitOfWorkFactory { _db }
+    }
+}
+
+#[async_trait]
+impl UnitOfWorkFactoryInterface for UnitOfWorkFactory {
+    async fn begin(&self) -> Result<UnitOfWork, sqlx::Error> {
+        Ok(UnitOfWork {
+            tx: self._db.begin().await?,
+        })
+    }
+}
@@ -1,5 +1,9 @@
+use chrono::{Datelike, NaiveTime, Weekday};
+use chrono_tz::Asia::Jakarta;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
+use crate::cloud_storage::PRESIGNED_URL_EXPIRY_SECS;
 use crate::features::media::domain::MediaType;
 
 pub struct Announcement {
@@ -13,6 +17,8 @@ pub struct Announcement {
     pub media: String,
     pub media_type: MediaType,
     pub media_duration: Option<f64>,
+    pub recurrence: Option<AnnouncementRecurrence>,
+    pub is_emergency: bool,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
 }
@@ -27,6 +33,13 @@ pub struct AnnouncementDetail {
     pub status: AnnouncementStatus,
     pub start_date: chrono::DateTime<chrono::Utc>,
     pub end_date: chrono::DateTime<chrono::Utc>,
+    pub recurrence: Option<AnnouncementRecurrence>,
+    pub occurrence_active: bool,
+    pub is_emergency: bool,
+    pub targets: AnnouncementTargets,
+    pub priority: i32,
+    pub weight: i32,
+    pub display_duration: Option<f64>,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
     pub user_id: i32,
@@ -34,11 +47,232 @@ pub struct AnnouncementDetail {
     pub devices: Vec<AnnouncementDetailDevices>,
 }
 
+impl AnnouncementDetail {
+    /// Recurring announcements are only synced to the devices while one of their occurrences is running,
+    /// the scheduler takes care of pushing them once the occurrence starts.
+    pub fn is_displayable(&self) -> bool {
+        self.recurrence.is_none() || self.occurrence_active
+    }
+}
+
+/// Buildings and floors an announcement is shown in, devices that are added to them later on
+/// receive the announcement as well
+#[derive(Debug, Clone, Default)]
+pub struct AnnouncementTargets {
+    pub building_ids: Vec<i32>,
+    pub floor_ids: Vec<i32>,
+}
+
+impl AnnouncementTargets {
+    pub fn is_empty(&self) -> bool {
+        self.building_ids.is_empty() && self.floor_ids.is_empty()
+    }
+}
+
 pub struct AnnouncementDetailDevices {
     pub id: i32,
     pub name: String,
     pub description: String,
     pub floor_id: i32,
+    pub delivery_status: AnnouncementDeliveryStatus,
+    pub delivery_error: Option<String>,
+    pub delivery_updated_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct AnnouncementRecurrence {
+    pub days_of_week: Vec<Weekday>,
+    pub time_windows: Vec<AnnouncementTimeWindow>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct AnnouncementTimeWindow {
+    pub start_time: NaiveTime,
+    pub end_time: NaiveTime,
+}
+
+impl AnnouncementRecurrence {
+    /// The days and time windows are expressed in the local time of the campus (Asia/Jakarta),
+    /// the same timezone the scheduler runs on.
+    pub fn is_occurring_at(&self, datetime: chrono::DateTime<chrono::Utc>) -> bool {
+        let local = datetime.with_timezone(&Jakarta);
+        if !self.days_of_week.contains(&local.weekday()) {
+            return false;
+        }
+
+        let time = local.time();
+        self.time_windows
+            .iter()
+            .any(|window| window.start_time <= time && time < window.end_time)
+    }
+}
+
+pub struct RecurringAnnouncement {
+    pub id: i32,
+    pub media_type: MediaType,
+    pub media_duration: Option<f64>,
+    pub recurrence: AnnouncementRecurrence,
+    pub occurrence_active: bool,
+}
+
+/// How long an image is shown when the announcement does not specify a display duration
+pub const DEFAULT_IMAGE_DISPLAY_DURATION_SECS: f64 = 10.0;
+
+pub struct PlaylistAnnouncement {
+    pub id: i32,
+    pub title: String,
+    pub media_type: MediaType,
+    pub media_duration: Option<f64>,
+    pub display_duration: Option<f64>,
+    pub priority: i32,
+    pub weight: i32,
+    pub is_emergency: bool,
+}
+
+/// Images are shown for their display duration, videos play for the length of the media
+pub fn playback_duration(
+    media_type: &MediaType,
+    media_duration: Option<f64>,
+    display_duration: Option<f64>,
+) -> Option<f64> {
+    match media_type {
+        MediaType::Image => Some(display_duration.unwrap_or(DEFAULT_IMAGE_DISPLAY_DURATION_SECS)),
+        MediaType::Video => media_duration,
+    }
+}
+
+impl PlaylistAnnouncement {
+    pub fn duration(&self) -> Option<f64> {
+        playback_duration(&self.media_type, self.media_duration, self.display_duration)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PlaylistItem {
+    pub announcement_id: i32,
+    pub title: String,
+    pub media_type: MediaType,
+    pub duration: Option<f64>,
+    pub priority: i32,
+    pub weight: i32,
+}
+
+impl From<&PlaylistAnnouncement> for PlaylistItem {
+    fn from(announcement: &PlaylistAnnouncement) -> Self {
+        PlaylistItem {
+            announcement_id: announcement.id,
+            title: announcement.title.clone(),
+            media_type: announcement.media_type.clone(),
+            duration: announcement.duration(),
+            priority: announcement.priority,
+            weight: announcement.weight,
+        }
+    }
+}
+
+/// Builds one rotation of the playlist of a device.
+///
+/// Announcements with a higher priority are played first, within the same priority every
+/// announcement appears `weight` times and the appearances are spread out with a smooth weighted
+/// round robin so the heaviest announcement does not play back to back. An emergency preempts
+/// the rotation, only the latest one is played.
+pub fn build_playlist(mut announcements: Vec<PlaylistAnnouncement>) -> Vec<PlaylistItem> {
+    if let Some(emergency) = announcements
+        .iter()
+        .filter(|announcement| announcement.is_emergency)
+        .max_by_key(|announcement| announcement.id)
+    {
+        return vec![PlaylistItem::from(emergency)];
+    }
+
+    announcements.sort_by(|a, b| b.priority.cmp(&a.priority).then(a.id.cmp(&b.id)));
+
+    let mut tiers: Vec<Vec<PlaylistAnnouncement>> = vec![];
+    for announcement in announcements {
+        match tiers.last_mut() {
+            Some(tier) if tier[0].priority == announcement.priority => tier.push(announcement),
+            _ => tiers.push(vec![announcement]),
+        }
+    }
+
+    let mut playlist = vec![];
+    for tier in tiers {
+        let weights: Vec<i32> = tier
+            .iter()
+            .map(|announcement| announcement.weight.max(1))
+            .collect();
+        let total: i32 = weights.iter().sum();
+        let mut current = vec![0; tier.len()];
+
+        for _ in 0..total {
+            let mut picked = 0;
+            for index in 0..tier.len() {
+                current[index] += weights[index];
+                if current[index] > current[picked] {
+                    picked = index;
+                }
+            }
+
+            current[picked] -= total;
+            playlist.push(PlaylistItem::from(&tier[picked]));
+        }
+    }
+
+    playlist
+}
+
+/// An announcement a device is currently displaying, as listed in the manifest of the device
+#[derive(Debug, Clone, Serialize)]
+pub struct ManifestAnnouncement {
+    pub id: i32,
+    pub title: String,
+    pub media: String,
+    pub media_type: MediaType,
+    pub media_duration: Option<f64>,
+    pub display_duration: Option<f64>,
+    pub content_hash: Option<String>,
+    pub start_date: chrono::DateTime<chrono::Utc>,
+    pub end_date: chrono::DateTime<chrono::Utc>,
+    pub recurrence: Option<AnnouncementRecurrence>,
+    pub priority: i32,
+    pub weight: i32,
+    pub is_emergency: bool,
+}
+
+impl ManifestAnnouncement {
+    pub fn duration(&self) -> Option<f64> {
+        playback_duration(&self.media_type, self.media_duration, self.display_duration)
+    }
+}
+
+pub struct DeviceManifestItem {
+    pub announcement: ManifestAnnouncement,
+    pub media_url: String,
+}
+
+pub struct DeviceManifest {
+    pub etag: String,
+    pub items: Vec<DeviceManifestItem>,
+    pub media_urls_expire_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// The presigned media URLs of a manifest are renewed once half of their lifetime has passed
+pub const MANIFEST_MEDIA_URL_WINDOW_SECS: i64 = PRESIGNED_URL_EXPIRY_SECS as i64 / 2;
+
+/// The ETag of a manifest depends on what the device displays and on the window its media URLs
+/// were presigned in, so a device polling at least once per window never holds expired URLs.
+/// The announcements are expected to be sorted.
+pub fn manifest_etag(
+    announcements: &Vec<ManifestAnnouncement>,
+    now: chrono::DateTime<chrono::Utc>,
+) -> String {
+    let mut payload = serde_json::to_vec(announcements).unwrap_or_default();
+    let media_url_window = now.timestamp().div_euclid(MANIFEST_MEDIA_URL_WINDOW_SECS);
+    payload.extend_from_slice(&media_url_window.to_be_bytes());
+
+    format!("\"{:x}\"", Sha256::digest(&payload))
 }
 
 pub struct AnnouncementMediaObject {
@@ -50,6 +284,7 @@ pub struct AnnouncementMediaObject {
 #[serde(rename_all = "snake_case")]
 #[sqlx(type_name = "announcement_status", rename_all = "snake_case")]
 pub enum AnnouncementStatus {
+    Draft,
     WaitingForApproval,
     WaitingForSync,
     Active,
@@ -68,6 +303,7 @@ pub struct AnnouncementStatusObject {
 impl AnnouncementStatus {
     pub fn label(self) -> &'static str {
         match self {
+            AnnouncementStatus::Draft => "Draft",
             AnnouncementStatus::WaitingForApproval => "Waiting For Approval",
             AnnouncementStatus::WaitingForSync => "Waiting For Sync",
             AnnouncementStatus::Active => "Active",
@@ -79,6 +315,7 @@ impl AnnouncementStatus {
 
     pub fn value(self) -> &'static str {
         match self {
+            AnnouncementStatus::Draft => "draft",
             AnnouncementStatus::WaitingForApproval => "waiting_for_approval",
             AnnouncementStatus::WaitingForSync => "waiting_for_sync",
             AnnouncementStatus::Active => "active",
@@ -96,9 +333,69 @@ impl AnnouncementStatus {
     }
 }
 
+/// Progress of an announcement on a single device, as reported by the device itself.
+#[derive(Debug, sqlx::Type, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "announcement_delivery_status", rename_all = "snake_case")]
+pub enum AnnouncementDeliveryStatus {
+    Pending,
+    Received,
+    MediaDownloaded,
+    Playing,
+    Failed,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnnouncementDeliveryStatusObject {
+    value: String,
+    label: String,
+}
+
+impl AnnouncementDeliveryStatus {
+    pub fn label(self) -> &'static str {
+        match self {
+            AnnouncementDeliveryStatus::Pending => "Pending",
+            AnnouncementDeliveryStatus::Received => "Received",
+            AnnouncementDeliveryStatus::MediaDownloaded => "Media Downloaded",
+            AnnouncementDeliveryStatus::Playing => "Playing",
+            AnnouncementDeliveryStatus::Failed => "Failed",
+        }
+    }
+
+    pub fn value(self) -> &'static str {
+        match self {
+            AnnouncementDeliveryStatus::Pending => "pending",
+            AnnouncementDeliveryStatus::Received => "received",
+            AnnouncementDeliveryStatus::MediaDownloaded => "media_downloaded",
+            AnnouncementDeliveryStatus::Playing => "playing",
+            AnnouncementDeliveryStatus::Failed => "failed",
+        }
+    }
+
+    pub fn object(self) -> AnnouncementDeliveryStatusObject {
+        AnnouncementDeliveryStatusObject {
+            value: self.clone().value().to_string(),
+            label: self.clone().label().to_string(),
+        }
+    }
+}
+
 pub enum AnnouncementErrorCode {
     AnnouncementNotFound,
     UserNotFound,
+    MediaNotFound,
+    TargetNotFound,
+    InvalidDeliveryStatus,
+    NoTargetDevices,
+    NotAnEmergency,
+    EmergencyAlreadyCleared,
+    DisplayDurationNotSupported,
+    DeviceNotFound,
+    NotADraft,
+    NotAnnouncementOwner,
+    NotCancelable,
+    ApprovalWorkflowNotConfigured,
     InternalServerError,
 }
 
@@ -107,6 +404,24 @@ impl std::fmt::Display for AnnouncementErrorCode {
         match self {
             AnnouncementErrorCode::AnnouncementNotFound => write!(f, "ANNOUNCEMENT_NOT_FOUND"),
             AnnouncementErrorCode::UserNotFound => write!(f, "USER_NOT_FOUND"),
+            AnnouncementErrorCode::MediaNotFound => write!(f, "MEDIA_NOT_FOUND"),
+            AnnouncementErrorCode::TargetNotFound => write!(f, "TARGET_NOT_FOUND"),
+            AnnouncementErrorCode::InvalidDeliveryStatus => write!(f, "INVALID_DELIVERY_STATUS"),
+            AnnouncementErrorCode::NoTargetDevices => write!(f, "NO_TARGET_DEVICES"),
+            AnnouncementErrorCode::NotAnEmergency => write!(f, "NOT_AN_EMERGENCY"),
+            AnnouncementErrorCode::EmergencyAlreadyCleared => {
+                write!(f, "EMERGENCY_ALREADY_CLEARED")
+            }
+            AnnouncementErrorCode::DisplayDurationNotSupported => {
+                write!(f, "DISPLAY_DURATION_NOT_SUPPORTED")
+            }
+            AnnouncementErrorCode::DeviceNotFound => write!(f, "DEVICE_NOT_FOUND"),
+            AnnouncementErrorCode::NotADraft => write!(f, "NOT_A_DRAFT"),
+            AnnouncementErrorCode::NotAnnouncementOwner => write!(f, "NOT_ANNOUNCEMENT_OWNER"),
+            AnnouncementErrorCode::NotCancelable => write!(f, "ANNOUNCEMENT_NOT_CANCELABLE"),
+            AnnouncementErrorCode::ApprovalWorkflowNotConfigured => {
+                write!(f, "APPROVAL_WORKFLOW_NOT_CONFIGURED")
+            }
             AnnouncementErrorCode::InternalServerError => write!(f, "INTERNAL_SERVER_ERROR"),
         }
     }
@@ -114,6 +429,10 @@ impl std::fmt::Display for AnnouncementErrorCode {
 
 pub enum CreateAnnouncementError {
     UserNotFound(String),
+    TargetNotFound(String),
+    NoTargetDevices(String),
+    DisplayDurationNotSupported(String),
+    ApprovalWorkflowNotConfigured(String),
     InternalServerError,
 }
 
@@ -121,6 +440,14 @@ impl std::fmt::Display for CreateAnnouncementError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             CreateAnnouncementError::UserNotFound(message) => write!(f, "{}", message),
+            CreateAnnouncementError::TargetNotFound(message) => write!(f, "{}", message),
+            CreateAnnouncementError::NoTargetDevices(message) => write!(f, "{}", message),
+            CreateAnnouncementError::DisplayDurationNotSupported(message) => {
+                write!(f, "{}", message)
+            }
+            CreateAnnouncementError::ApprovalWorkflowNotConfigured(message) => {
+                write!(f, "{}", message)
+            }
             CreateAnnouncementError::InternalServerError => write!(f, "Internal Server Error"),
         }
     }
@@ -170,6 +497,78 @@ impl std::fmt::Display for GetAnnouncementMediaPresignedURLError {
     }
 }
 
+pub enum ReportAnnouncementDeliveryError {
+    AnnouncementNotFound(String),
+    InvalidDeliveryStatus(String),
+    InternalServerError,
+}
+
+impl std::fmt::Display for ReportAnnouncementDeliveryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReportAnnouncementDeliveryError::AnnouncementNotFound(message) => {
+                write!(f, "{}", message)
+            }
+            ReportAnnouncementDeliveryError::InvalidDeliveryStatus(message) => {
+                write!(f, "{}", message)
+            }
+            ReportAnnouncementDeliveryError::InternalServerError => {
+                write!(f, "Internal Server Error")
+            }
+        }
+    }
+}
+
+pub enum BroadcastEmergencyAnnouncementError {
+    MediaNotFound(String),
+    TargetNotFound(String),
+    NoTargetDevices(String),
+    InternalServerError,
+}
+
+impl std::fmt::Display for BroadcastEmergencyAnnouncementError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BroadcastEmergencyAnnouncementError::MediaNotFound(message) => {
+                write!(f, "{}", message)
+            }
+            BroadcastEmergencyAnnouncementError::TargetNotFound(message) => {
+                write!(f, "{}", message)
+            }
+            BroadcastEmergencyAnnouncementError::NoTargetDevices(message) => {
+                write!(f, "{}", message)
+            }
+            BroadcastEmergencyAnnouncementError::InternalServerError => {
+                write!(f, "Internal Server Error")
+            }
+        }
+    }
+}
+
+pub enum ClearEmergencyAnnouncementError {
+    AnnouncementNotFound(String),
+    NotAnEmergency(String),
+    EmergencyAlreadyCleared(String),
+    InternalServerError,
+}
+
+impl std::fmt::Display for ClearEmergencyAnnouncementError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClearEmergencyAnnouncementError::AnnouncementNotFound(message) => {
+                write!(f, "{}", message)
+            }
+            ClearEmergencyAnnouncementError::NotAnEmergency(message) => write!(f, "{}", message),
+            ClearEmergencyAnnouncementError::EmergencyAlreadyCleared(message) => {
+                write!(f, "{}", message)
+            }
+            ClearEmergencyAnnouncementError::InternalServerError => {
+                write!(f, "Internal Server Error")
+            }
+        }
+    }
+}
+
 pub enum HandleScheduledAnnouncementsError {
     BrokenThread,
     InternalServerError,
@@ -188,3 +587,173 @@ impl std::fmt::Display for HandleScheduledAnnouncementsError {
     }
 }
 
+pub enum GetDevicePlaylistError {
+    InternalServerError,
+}
+
+impl std::fmt::Display for GetDevicePlaylistError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GetDevicePlaylistError::InternalServerError => write!(f, "Internal Server Error"),
+        }
+    }
+}
+
+pub enum GetDeviceManifestError {
+    InternalServerError,
+}
+
+impl std::fmt::Display for GetDeviceManifestError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GetDeviceManifestError::InternalServerError => write!(f, "Internal Server Error"),
+        }
+    }
+}
+
+pub enum UpdateDraftAnnouncementError {
+    AnnouncementNotFound(String),
+    NotAnnouncementOwner(String),
+    NotADraft(String),
+    MediaNotFound(String),
+    DeviceNotFound(String),
+    NoTargetDevices(String),
+    InternalServerError,
+}
+
+impl std::fmt::Display for UpdateDraftAnnouncementError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UpdateDraftAnnouncementError::AnnouncementNotFound(message) => write!(f, "{}", message),
+            UpdateDraftAnnouncementError::NotAnnouncementOwner(message) => write!(f, "{}", message),
+            UpdateDraftAnnouncementError::NotADraft(message) => write!(f, "{}", message),
+            UpdateDraftAnnouncementError::MediaNotFound(message) => write!(f, "{}", message),
+            UpdateDraftAnnouncementError::DeviceNotFound(message) => write!(f, "{}", message),
+            UpdateDraftAnnouncementError::NoTargetDevices(message) => write!(f, "{}", message),
+            UpdateDraftAnnouncementError::InternalServerError => {
+                write!(f, "Internal Server Error")
+            }
+        }
+    }
+}
+
+pub enum DeleteDraftAnnouncementError {
+    AnnouncementNotFound(String),
+    NotAnnouncementOwner(String),
+    NotADraft(String),
+    InternalServerError,
+}
+
+impl std::fmt::Display for DeleteDraftAnnouncementError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DeleteDraftAnnouncementError::AnnouncementNotFound(message) => write!(f, "{}", message),
+            DeleteDraftAnnouncementError::NotAnnouncementOwner(message) => write!(f, "{}", message),
+            DeleteDraftAnnouncementError::NotADraft(message) => write!(f, "{}", message),
+            DeleteDraftAnnouncementError::InternalServerError => {
+                write!(f, "Internal Server Error")
+            }
+        }
+    }
+}
+
+pub enum SubmitDraftAnnouncementError {
+    AnnouncementNotFound(String),
+    NotAnnouncementOwner(String),
+    NotADraft(String),
+    ApprovalWorkflowNotConfigured(String),
+    InternalServerError,
+}
+
+impl std::fmt::Display for SubmitDraftAnnouncementError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SubmitDraftAnnouncementError::AnnouncementNotFound(message) => write!(f, "{}", message),
+            SubmitDraftAnnouncementError::NotAnnouncementOwner(message) => write!(f, "{}", message),
+            SubmitDraftAnnouncementError::NotADraft(message) => write!(f, "{}", message),
+            SubmitDraftAnnouncementError::ApprovalWorkflowNotConfigured(message) => {
+                write!(f, "{}", message)
+            }
+            SubmitDraftAnnouncementError::InternalServerError => {
+                write!(f, "Internal Server Error")
+            }
+        }
+    }
+}
+
+pub enum CancelAnnouncementError {
+    AnnouncementNotFound(String),
+    NotAnnouncementOwner(String),
+    NotCancelable(String),
+    InternalServerError,
+}
+
+impl std::fmt::Display for CancelAnnouncementError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CancelAnnouncementError::AnnouncementNotFound(message) => write!(f, "{}", message),
+            CancelAnnouncementError::NotAnnouncementOwner(message) => write!(f, "{}", message),
+            CancelAnnouncementError::NotCancelable(message) => write!(f, "{}", message),
+            CancelAnnouncementError::InternalServerError => write!(f, "Internal Server Error"),
+        }
+    }
+}
+
+/// Reasons an announcement can not be handled as a draft of the user, shared by every draft operation
+pub enum DraftAccessError {
+    AnnouncementNotFound(String),
+    NotAnnouncementOwner(String),
+    NotADraft(String),
+    InternalServerError,
+}
+
+impl From<DraftAccessError> for UpdateDraftAnnouncementError {
+    fn from(e: DraftAccessError) -> Self {
+        match e {
+            DraftAccessError::AnnouncementNotFound(message) => {
+                UpdateDraftAnnouncementError::AnnouncementNotFound(message)
+            }
+            DraftAccessError::NotAnnouncementOwner(message) => {
+                UpdateDraftAnnouncementError::NotAnnouncementOwner(message)
+            }
+            DraftAccessError::NotADraft(message) => UpdateDraftAnnouncementError::NotADraft(message),
+            DraftAccessError::InternalServerError => {
+                UpdateDraftAnnouncementError::InternalServerError
+            }
+        }
+    }
+}
+
+impl From<DraftAccessError> for DeleteDraftAnnouncementError {
+    fn from(e: DraftAccessError) -> Self {
+        match e {
+            DraftAccessError::AnnouncementNotFound(message) => {
+                DeleteDraftAnnouncementError::AnnouncementNotFound(message)
+            }
+            DraftAccessError::NotAnnouncementOwner(message) => {
+                DeleteDraftAnnouncementError::NotAnnouncementOwner(message)
+            }
+            DraftAccessError::NotADraft(message) => DeleteDraftAnnouncementError::NotADraft(message),
+            DraftAccessError::InternalServerError => {
+                DeleteDraftAnnouncementError::InternalServerError
+            }
+        }
+    }
+}
+
+impl From<DraftAccessError> for SubmitDraftAnnouncementError {
+    fn from(e: DraftAccessError) -> Self {
+        match e {
+            DraftAccessError::AnnouncementNotFound(message) => {
+                SubmitDraftAnnouncementError::AnnouncementNotFound(message)
+            }
+            DraftAccessError::NotAnnouncementOwner(message) => {
+                SubmitDraftAnnouncementError::NotAnnouncementOwner(message)
+            }
+            DraftAccessError::NotADraft(message) => SubmitDraftAnnouncementError::NotADraft(message),
+            DraftAccessError::InternalServerError => {
+                SubmitDraftAnnouncementError::InternalServerError
+            }
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use std::sync::Arc;
 
-use actix_web::{web, HttpResponse};
+use actix_web::{http::header, web, HttpRequest, HttpResponse};
 use chrono::{NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
 use serde::{Deserialize, Serialize};
 
@@ -13,9 +13,15 @@ use crate::{
 };
 
 use super::{
-    AnnouncementErrorCode, AnnouncementServiceInterface, AnnouncementStatus,
-    AnnouncementStatusObject, CreateAnnouncementParams, GetAnnouncementDetailError,
-    GetAnnouncementMediaPresignedURLError, ListAnnouncementError, ListAnnouncementParams,
+    AnnouncementDeliveryStatus, AnnouncementDeliveryStatusObject, AnnouncementErrorCode,
+    AnnouncementRecurrence, AnnouncementServiceInterface, AnnouncementStatus,
+    AnnouncementStatusObject, AnnouncementTargets, AnnouncementTimeWindow,
+    BroadcastEmergencyAnnouncementError,
+    BroadcastEmergencyAnnouncementParams, ClearEmergencyAnnouncementError,
+    CancelAnnouncementError, DeleteDraftAnnouncementError, SubmitDraftAnnouncementError, UpdateDraftAnnouncementError,
+    UpdateDraftAnnouncementParams,
+    CreateAnnouncementParams, GetAnnouncementDetailError, GetAnnouncementMediaPresignedURLError,
+    GetDeviceManifestError, GetDevicePlaylistError, ListAnnouncementError, ListAnnouncementParams, ReportAnnouncementDeliveryError, ReportAnnouncementDeliveryParams,
 };
 
 #[derive(Debug, Deserialize)]
@@ -26,10 +32,94 @@ pub struct CreateAnnouncementBody {
     pub start_date: String,
     pub end_date: String,
     pub notes: String,
+    #[serde(default)]
     pub device_ids: Vec<i32>,
+    #[serde(default)]
+    pub building_ids: Vec<i32>,
+    #[serde(default)]
+    pub floor_ids: Vec<i32>,
+    pub recurrence: Option<AnnouncementRecurrenceObject>,
+    #[serde(default)]
+    pub priority: i32,
+    pub weight: Option<i32>,
+    pub display_duration: Option<f64>,
+    #[serde(default)]
+    pub is_draft: bool,
 }
 
-fn parse_announcement_date_input(raw: String) -> Option<chrono::DateTime<chrono::Utc>> {
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnnouncementRecurrenceObject {
+    pub days_of_week: Vec<chrono::Weekday>,
+    pub time_windows: Vec<AnnouncementTimeWindowObject>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnnouncementTimeWindowObject {
+    pub start_time: String,
+    pub end_time: String,
+}
+
+impl From<AnnouncementRecurrence> for AnnouncementRecurrenceObject {
+    fn from(recurrence: AnnouncementRecurrence) -> Self {
+        AnnouncementRecurrenceObject {
+            days_of_week: recurrence.days_of_week,
+            time_windows: recurrence
+                .time_windows
+                .into_iter()
+                .map(|window| AnnouncementTimeWindowObject {
+                    start_time: window.start_time.format("%H:%M").to_string(),
+                    end_time: window.end_time.format("%H:%M").to_string(),
+                })
+                .collect(),
+        }
+    }
+}
+
+fn parse_announcement_recurrence_input(
+    raw: &AnnouncementRecurrenceObject,
+) -> Result<AnnouncementRecurrence, String> {
+    if raw.days_of_week.len() == 0 {
+        return Err("Recurrence must have at least one day of the week".into());
+    }
+    if raw.time_windows.len() == 0 {
+        return Err("Recurrence must have at least one time window".into());
+    }
+
+    let mut time_windows: Vec<AnnouncementTimeWindow> = vec![];
+    for window in &raw.time_windows {
+        let start_time = match NaiveTime::parse_from_str(window.start_time.as_str(), "%H:%M") {
+            Ok(time) => time,
+            Err(_) => return Err("Recurrence start time must follow hh:mm format".into()),
+        };
+        let end_time = match NaiveTime::parse_from_str(window.end_time.as_str(), "%H:%M") {
+            Ok(time) => time,
+            Err(_) => return Err("Recurrence end time must follow hh:mm format".into()),
+        };
+
+        if start_time >= end_time {
+            return Err("Recurrence start time must be before the end time".into());
+        }
+
+        time_windows.push(AnnouncementTimeWindow {
+            start_time,
+            end_time,
+        });
+    }
+
+    Ok(AnnouncementRecurrence {
+        days_of_week: raw.days_of_week.clone(),
+        time_windows,
+    })
+}
+
+/// Accepts a full RFC 3339 timestamp, or a yyyy-mm-dd date meaning midnight UTC
+pub fn parse_announcement_date_input(raw: String) -> Option<chrono::DateTime<chrono::Utc>> {
+    if let Ok(date_time) = chrono::DateTime::parse_from_rfc3339(raw.as_str()) {
+        return Some(date_time.with_timezone(&chrono::Utc));
+    }
+
     let naive_date = match NaiveDate::parse_from_str(raw.as_str(), "%Y-%m-%d") {
         Ok(date) => date,
         Err(_) => return None,
@@ -70,15 +160,54 @@ pub async fn create_announcement(
         }
     };
 
+    let recurrence = match &body.recurrence {
+        Some(recurrence) => match parse_announcement_recurrence_input(recurrence) {
+            Ok(recurrence) => Some(recurrence),
+            Err(message) => {
+                return HttpResponse::BadRequest().json(HttpErrorResponse::new(
+                    API_VALIDATION_ERROR_CODE.to_string(),
+                    vec![message],
+                ))
+            }
+        },
+        None => None,
+    };
+
+    let weight = body.weight.unwrap_or(1);
+    if weight < 1 {
+        return HttpResponse::BadRequest().json(HttpErrorResponse::new(
+            API_VALIDATION_ERROR_CODE.to_string(),
+            vec!["Weight must be at least 1".to_string()],
+        ));
+    }
+
+    if let Some(display_duration) = body.display_duration {
+        if display_duration <= 0.0 {
+            return HttpResponse::BadRequest().json(HttpErrorResponse::new(
+                API_VALIDATION_ERROR_CODE.to_string(),
+                vec!["Display duration must be greater than 0".to_string()],
+            ));
+        }
+    }
+
     if let Err(e) = announcement_service
         .create_announcement(CreateAnnouncementParams {
             title: body.title.clone(),
             media_id: body.media_id,
             notes: body.notes.clone(),
             device_ids: body.device_ids.clone(),
+            targets: AnnouncementTargets {
+                building_ids: body.building_ids.clone(),
+                floor_ids: body.floor_ids.clone(),
+            },
             user_id,
             start_date,
             end_date,
+            recurrence,
+            priority: body.priority,
+            weight,
+            display_duration: body.display_duration,
+            is_draft: body.is_draft,
         })
         .await
     {
@@ -89,6 +218,30 @@ pub async fn create_announcement(
                     vec![message],
                 ))
             }
+            CreateAnnouncementError::TargetNotFound(message) => {
+                return HttpResponse::NotFound().json(HttpErrorResponse::new(
+                    AnnouncementErrorCode::TargetNotFound.to_string(),
+                    vec![message],
+                ))
+            }
+            CreateAnnouncementError::NoTargetDevices(message) => {
+                return HttpResponse::BadRequest().json(HttpErrorResponse::new(
+                    AnnouncementErrorCode::NoTargetDevices.to_string(),
+                    vec![message],
+                ))
+            }
+            CreateAnnouncementError::DisplayDurationNotSupported(message) => {
+                return HttpResponse::BadRequest().json(HttpErrorResponse::new(
+                    AnnouncementErrorCode::DisplayDurationNotSupported.to_string(),
+                    vec![message],
+                ))
+            }
+            CreateAnnouncementError::ApprovalWorkflowNotConfigured(message) => {
+                return HttpResponse::Conflict().json(HttpErrorResponse::new(
+                    AnnouncementErrorCode::ApprovalWorkflowNotConfigured.to_string(),
+                    vec![message],
+                ))
+            }
             CreateAnnouncementError::InternalServerError => {
                 return HttpResponse::InternalServerError().json(HttpErrorResponse::new(
                     AnnouncementErrorCode::InternalServerError.to_string(),
@@ -101,6 +254,376 @@ pub async fn create_announcement(
     HttpResponse::NoContent().finish()
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BroadcastEmergencyAnnouncementBody {
+    pub title: String,
+    pub media_id: i32,
+    pub notes: String,
+    pub building_ids: Option<Vec<i32>>,
+    pub floor_ids: Option<Vec<i32>>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BroadcastEmergencyAnnouncementResponse {
+    id: i32,
+}
+
+pub async fn broadcast_emergency_announcement(
+    announcement_service: web::Data<Arc<dyn AnnouncementServiceInterface + Send + Sync + 'static>>,
+    auth: AuthenticationContext,
+    body: web::Json<BroadcastEmergencyAnnouncementBody>,
+) -> HttpResponse {
+    let user_id = match derive_user_id(auth) {
+        Ok(id) => id,
+        Err(e) => return derive_authentication_middleware_error(e),
+    };
+
+    let announcement_id = match announcement_service
+        .broadcast_emergency_announcement(BroadcastEmergencyAnnouncementParams {
+            title: body.title.clone(),
+            media_id: body.media_id,
+            notes: body.notes.clone(),
+            targets: AnnouncementTargets {
+                building_ids: body.building_ids.clone().unwrap_or_default(),
+                floor_ids: body.floor_ids.clone().unwrap_or_default(),
+            },
+            user_id,
+        })
+        .await
+    {
+        Ok(id) => id,
+        Err(e) => match e {
+            BroadcastEmergencyAnnouncementError::MediaNotFound(message) => {
+                return HttpResponse::NotFound().json(HttpErrorResponse::new(
+                    AnnouncementErrorCode::MediaNotFound.to_string(),
+                    vec![message],
+                ))
+            }
+            BroadcastEmergencyAnnouncementError::TargetNotFound(message) => {
+                return HttpResponse::NotFound().json(HttpErrorResponse::new(
+                    AnnouncementErrorCode::TargetNotFound.to_string(),
+                    vec![message],
+                ))
+            }
+            BroadcastEmergencyAnnouncementError::NoTargetDevices(message) => {
+                return HttpResponse::BadRequest().json(HttpErrorResponse::new(
+                    AnnouncementErrorCode::NoTargetDevices.to_string(),
+                    vec![message],
+                ))
+            }
+            BroadcastEmergencyAnnouncementError::InternalServerError => {
+                return HttpResponse::InternalServerError().json(HttpErrorResponse::new(
+                    AnnouncementErrorCode::InternalServerError.to_string(),
+                    vec![BroadcastEmergencyAnnouncementError::InternalServerError.to_string()],
+                ))
+            }
+        },
+    };
+
+    HttpResponse::Created().json(BroadcastEmergencyAnnouncementResponse {
+        id: announcement_id,
+    })
+}
+
+pub async fn clear_emergency_announcement(
+    announcement_service: web::Data<Arc<dyn AnnouncementServiceInterface + Send + Sync + 'static>>,
+    auth: AuthenticationContext,
+    announcement_id: web::Path<i32>,
+) -> HttpResponse {
+    if let Err(e) = derive_user_id(auth) {
+        return derive_authentication_middleware_error(e);
+    }
+
+    if let Err(e) = announcement_service
+        .clear_emergency_announcement(announcement_id.into_inner())
+        .await
+    {
+        match e {
+            ClearEmergencyAnnouncementError::AnnouncementNotFound(message) => {
+                return HttpResponse::NotFound().json(HttpErrorResponse::new(
+                    AnnouncementErrorCode::AnnouncementNotFound.to_string(),
+                    vec![message],
+                ))
+            }
+            ClearEmergencyAnnouncementError::NotAnEmergency(message) => {
+                return HttpResponse::BadRequest().json(HttpErrorResponse::new(
+                    AnnouncementErrorCode::NotAnEmergency.to_string(),
+                    vec![message],
+                ))
+            }
+            ClearEmergencyAnnouncementError::EmergencyAlreadyCleared(message) => {
+                return HttpResponse::Conflict().json(HttpErrorResponse::new(
+                    AnnouncementErrorCode::EmergencyAlreadyCleared.to_string(),
+                    vec![message],
+                ))
+            }
+            ClearEmergencyAnnouncementError::InternalServerError => {
+                return HttpResponse::InternalServerError().json(HttpErrorResponse::new(
+                    AnnouncementErrorCode::InternalServerError.to_string(),
+                    vec![ClearEmergencyAnnouncementError::InternalServerError.to_string()],
+                ))
+            }
+        }
+    };
+
+    HttpResponse::NoContent().finish()
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateDraftAnnouncementBody {
+    pub title: Option<String>,
+    pub media_id: Option<i32>,
+    pub notes: Option<String>,
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+    pub device_ids: Option<Vec<i32>>,
+}
+
+pub async fn update_draft_announcement(
+    announcement_service: web::Data<Arc<dyn AnnouncementServiceInterface + Send + Sync + 'static>>,
+    auth: AuthenticationContext,
+    announcement_id: web::Path<i32>,
+    body: web::Json<UpdateDraftAnnouncementBody>,
+) -> HttpResponse {
+    let user_id = match derive_user_id(auth) {
+        Ok(id) => id,
+        Err(e) => return derive_authentication_middleware_error(e),
+    };
+
+    let mut start_date = None;
+    if let Some(raw) = body.start_date.clone() {
+        start_date = match parse_announcement_date_input(raw) {
+            Some(date) => Some(date),
+            None => {
+                return HttpResponse::BadRequest().json(HttpErrorResponse::new(
+                    API_VALIDATION_ERROR_CODE.to_string(),
+                    vec!["Start date is invalid".to_string()],
+                ))
+            }
+        };
+    }
+
+    let mut end_date = None;
+    if let Some(raw) = body.end_date.clone() {
+        end_date = match parse_announcement_date_input(raw) {
+            Some(date) => Some(date),
+            None => {
+                return HttpResponse::BadRequest().json(HttpErrorResponse::new(
+                    API_VALIDATION_ERROR_CODE.to_string(),
+                    vec!["End date is invalid".to_string()],
+                ))
+            }
+        };
+    }
+
+    if let Err(e) = announcement_service
+        .update_draft_announcement(
+            announcement_id.into_inner(),
+            user_id,
+            UpdateDraftAnnouncementParams {
+                title: body.title.clone(),
+                media_id: body.media_id,
+                notes: body.notes.clone(),
+                start_date,
+                end_date,
+                device_ids: body.device_ids.clone(),
+            },
+        )
+        .await
+    {
+        match e {
+            UpdateDraftAnnouncementError::AnnouncementNotFound(message) => {
+                return HttpResponse::NotFound().json(HttpErrorResponse::new(
+                    AnnouncementErrorCode::AnnouncementNotFound.to_string(),
+                    vec![message],
+                ))
+            }
+            UpdateDraftAnnouncementError::NotAnnouncementOwner(message) => {
+                return HttpResponse::Forbidden().json(HttpErrorResponse::new(
+                    AnnouncementErrorCode::NotAnnouncementOwner.to_string(),
+                    vec![message],
+                ))
+            }
+            UpdateDraftAnnouncementError::NotADraft(message) => {
+                return HttpResponse::Conflict().json(HttpErrorResponse::new(
+                    AnnouncementErrorCode::NotADraft.to_string(),
+                    vec![message],
+                ))
+            }
+            UpdateDraftAnnouncementError::MediaNotFound(message) => {
+                return HttpResponse::NotFound().json(HttpErrorResponse::new(
+                    AnnouncementErrorCode::MediaNotFound.to_string(),
+                    vec![message],
+                ))
+            }
+            UpdateDraftAnnouncementError::DeviceNotFound(message) => {
+                return HttpResponse::NotFound().json(HttpErrorResponse::new(
+                    AnnouncementErrorCode::DeviceNotFound.to_string(),
+                    vec![message],
+                ))
+            }
+            UpdateDraftAnnouncementError::NoTargetDevices(message) => {
+                return HttpResponse::BadRequest().json(HttpErrorResponse::new(
+                    AnnouncementErrorCode::NoTargetDevices.to_string(),
+                    vec![message],
+                ))
+            }
+            UpdateDraftAnnouncementError::InternalServerError => {
+                return HttpResponse::InternalServerError().json(HttpErrorResponse::new(
+                    AnnouncementErrorCode::InternalServerError.to_string(),
+                    vec![UpdateDraftAnnouncementError::InternalServerError.to_string()],
+                ))
+            }
+        }
+    };
+
+    HttpResponse::NoContent().finish()
+}
+
+pub async fn delete_draft_announcement(
+    announcement_service: web::Data<Arc<dyn AnnouncementServiceInterface + Send + Sync + 'static>>,
+    auth: AuthenticationContext,
+    announcement_id: web::Path<i32>,
+) -> HttpResponse {
+    let user_id = match derive_user_id(auth) {
+        Ok(id) => id,
+        Err(e) => return derive_authentication_middleware_error(e),
+    };
+
+    if let Err(e) = announcement_service
+        .delete_draft_announcement(announcement_id.into_inner(), user_id)
+        .await
+    {
+        match e {
+            DeleteDraftAnnouncementError::AnnouncementNotFound(message) => {
+                return HttpResponse::NotFound().json(HttpErrorResponse::new(
+                    AnnouncementErrorCode::AnnouncementNotFound.to_string(),
+                    vec![message],
+                ))
+            }
+            DeleteDraftAnnouncementError::NotAnnouncementOwner(message) => {
+                return HttpResponse::Forbidden().json(HttpErrorResponse::new(
+                    AnnouncementErrorCode::NotAnnouncementOwner.to_string(),
+                    vec![message],
+                ))
+            }
+            DeleteDraftAnnouncementError::NotADraft(message) => {
+                return HttpResponse::Conflict().json(HttpErrorResponse::new(
+                    AnnouncementErrorCode::NotADraft.to_string(),
+                    vec![message],
+                ))
+            }
+            DeleteDraftAnnouncementError::InternalServerError => {
+                return HttpResponse::InternalServerError().json(HttpErrorResponse::new(
+                    AnnouncementErrorCode::InternalServerError.to_string(),
+                    vec![DeleteDraftAnnouncementError::InternalServerError.to_string()],
+                ))
+            }
+        }
+    };
+
+    HttpResponse::NoContent().finish()
+}
+
+pub async fn submit_draft_announcement(
+    announcement_service: web::Data<Arc<dyn AnnouncementServiceInterface + Send + Sync + 'static>>,
+    auth: AuthenticationContext,
+    announcement_id: web::Path<i32>,
+) -> HttpResponse {
+    let user_id = match derive_user_id(auth) {
+        Ok(id) => id,
+        Err(e) => return derive_authentication_middleware_error(e),
+    };
+
+    if let Err(e) = announcement_service
+        .submit_draft_announcement(announcement_id.into_inner(), user_id)
+        .await
+    {
+        match e {
+            SubmitDraftAnnouncementError::AnnouncementNotFound(message) => {
+                return HttpResponse::NotFound().json(HttpErrorResponse::new(
+                    AnnouncementErrorCode::AnnouncementNotFound.to_string(),
+                    vec![message],
+                ))
+            }
+            SubmitDraftAnnouncementError::NotAnnouncementOwner(message) => {
+                return HttpResponse::Forbidden().json(HttpErrorResponse::new(
+                    AnnouncementErrorCode::NotAnnouncementOwner.to_string(),
+                    vec![message],
+                ))
+            }
+            SubmitDraftAnnouncementError::NotADraft(message) => {
+                return HttpResponse::Conflict().json(HttpErrorResponse::new(
+                    AnnouncementErrorCode::NotADraft.to_string(),
+                    vec![message],
+                ))
+            }
+            SubmitDraftAnnouncementError::ApprovalWorkflowNotConfigured(message) => {
+                return HttpResponse::Conflict().json(HttpErrorResponse::new(
+                    AnnouncementErrorCode::ApprovalWorkflowNotConfigured.to_string(),
+                    vec![message],
+                ))
+            }
+            SubmitDraftAnnouncementError::InternalServerError => {
+                return HttpResponse::InternalServerError().json(HttpErrorResponse::new(
+                    AnnouncementErrorCode::InternalServerError.to_string(),
+                    vec![SubmitDraftAnnouncementError::InternalServerError.to_string()],
+                ))
+            }
+        }
+    };
+
+    HttpResponse::NoContent().finish()
+}
+
+pub async fn cancel_announcement(
+    announcement_service: web::Data<Arc<dyn AnnouncementServiceInterface + Send + Sync + 'static>>,
+    auth: AuthenticationContext,
+    announcement_id: web::Path<i32>,
+) -> HttpResponse {
+    let user_id = match derive_user_id(auth) {
+        Ok(id) => id,
+        Err(e) => return derive_authentication_middleware_error(e),
+    };
+
+    if let Err(e) = announcement_service
+        .cancel_announcement(announcement_id.into_inner(), user_id)
+        .await
+    {
+        match e {
+            CancelAnnouncementError::AnnouncementNotFound(message) => {
+                return HttpResponse::NotFound().json(HttpErrorResponse::new(
+                    AnnouncementErrorCode::AnnouncementNotFound.to_string(),
+                    vec![message],
+                ))
+            }
+            CancelAnnouncementError::NotAnnouncementOwner(message) => {
+                return HttpResponse::Forbidden().json(HttpErrorResponse::new(
+                    AnnouncementErrorCode::NotAnnouncementOwner.to_string(),
+                    vec![message],
+                ))
+            }
+            CancelAnnouncementError::NotCancelable(message) => {
+                return HttpResponse::Conflict().json(HttpErrorResponse::new(
+                    AnnouncementErrorCode::NotCancelable.to_string(),
+                    vec![message],
+                ))
+            }
+            CancelAnnouncementError::InternalServerError => {
+                return HttpResponse::InternalServerError().json(HttpErrorResponse::new(
+                    AnnouncementErrorCode::InternalServerError.to_string(),
+                    vec![CancelAnnouncementError::InternalServerError.to_string()],
+                ))
+            }
+        }
+    };
+
+    HttpResponse::NoContent().finish()
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ListAnnouncementQueryParams {
@@ -110,6 +633,7 @@ pub struct ListAnnouncementQueryParams {
     pub status: Option<AnnouncementStatus>,
     pub user_id: Option<i32>,
     pub device_id: Option<i32>,
+    pub is_emergency: Option<bool>,
     pub start_date: Option<String>,
     pub end_date: Option<String>,
     pub populate_media: Option<bool>,
@@ -136,6 +660,7 @@ pub struct ListAnnouncementContent {
     media: String,
     media_type: MediaType,
     media_duration: Option<f64>,
+    is_emergency: bool,
     created_at: String,
 }
 
@@ -201,6 +726,7 @@ pub async fn list_announcement(
             status: query_params.status.clone(),
             user_id: query_params.user_id.clone(),
             device_id: query_params.device_id.clone(),
+            is_emergency: query_params.is_emergency,
             populate_media: query_params.populate_media.clone(),
             start_date,
             end_date,
@@ -234,6 +760,7 @@ pub async fn list_announcement(
             media: row.media,
             media_type: row.media_type,
             media_duration: row.media_duration,
+            is_emergency: row.is_emergency,
             created_at: row.created_at.to_rfc3339(),
         })
         .collect();
@@ -257,6 +784,12 @@ pub struct GetAnnouncementDetailResponse {
     author: AnnouncementAuthorObject,
     start_date: String,
     end_date: String,
+    recurrence: Option<AnnouncementRecurrenceObject>,
+    is_emergency: bool,
+    targets: AnnouncementTargetsObject,
+    priority: i32,
+    weight: i32,
+    display_duration: Option<f64>,
     devices: Vec<GetAnnouncementDetailDevice>,
     created_at: String,
     updated_at: String,
@@ -264,6 +797,13 @@ pub struct GetAnnouncementDetailResponse {
     media_duration: Option<f64>,
 }
 
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnnouncementTargetsObject {
+    building_ids: Vec<i32>,
+    floor_ids: Vec<i32>,
+}
+
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct GetAnnouncementDetailDevice {
@@ -271,6 +811,15 @@ pub struct GetAnnouncementDetailDevice {
     name: String,
     description: String,
     floor_id: i32,
+    delivery: GetAnnouncementDetailDeviceDelivery,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetAnnouncementDetailDeviceDelivery {
+    status: AnnouncementDeliveryStatusObject,
+    error: Option<String>,
+    updated_at: Option<String>,
 }
 
 pub async fn get_announcement_detail(
@@ -319,6 +868,15 @@ pub async fn get_announcement_detail(
         },
         start_date: result.start_date.to_rfc3339(),
         end_date: result.end_date.to_rfc3339(),
+        recurrence: result.recurrence.map(AnnouncementRecurrenceObject::from),
+        is_emergency: result.is_emergency,
+        targets: AnnouncementTargetsObject {
+            building_ids: result.targets.building_ids,
+            floor_ids: result.targets.floor_ids,
+        },
+        priority: result.priority,
+        weight: result.weight,
+        display_duration: result.display_duration,
         devices: result
             .devices
             .into_iter()
@@ -327,6 +885,11 @@ pub async fn get_announcement_detail(
                 name: row.name,
                 description: row.description,
                 floor_id: row.floor_id,
+                delivery: GetAnnouncementDetailDeviceDelivery {
+                    status: row.delivery_status.object(),
+                    error: row.delivery_error,
+                    updated_at: row.delivery_updated_at.map(|date| date.to_rfc3339()),
+                },
             })
             .collect(),
         created_at: result.created_at.to_rfc3339(),
@@ -412,3 +975,218 @@ pub async fn get_announcement_media_presigned_url_device(
         media: obj.media,
     })
 }
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportAnnouncementDeliveryBody {
+    pub status: AnnouncementDeliveryStatus,
+    pub error: Option<String>,
+}
+
+pub async fn report_announcement_delivery_status_device(
+    announcement_service: web::Data<Arc<dyn AnnouncementServiceInterface + Send + Sync + 'static>>,
+    auth: device_middleware::DeviceAuthenticationContext,
+    announcement_id: web::Path<i32>,
+    body: web::Json<ReportAnnouncementDeliveryBody>,
+) -> HttpResponse {
+    let device_id = match device_middleware::get_device_id(auth) {
+        Ok(id) => id,
+        Err(e) => return device_middleware::parse_device_authentication_middleware_error(e),
+    };
+
+    let body = body.into_inner();
+    if let Err(e) = announcement_service
+        .report_delivery_status(ReportAnnouncementDeliveryParams {
+            announcement_id: announcement_id.into_inner(),
+            device_id,
+            status: body.status,
+            error: body.error,
+        })
+        .await
+    {
+        match e {
+            ReportAnnouncementDeliveryError::AnnouncementNotFound(message) => {
+                return HttpResponse::NotFound().json(HttpErrorResponse::new(
+                    AnnouncementErrorCode::AnnouncementNotFound.to_string(),
+                    vec![message],
+                ))
+            }
+            ReportAnnouncementDeliveryError::InvalidDeliveryStatus(message) => {
+                return HttpResponse::BadRequest().json(HttpErrorResponse::new(
+                    AnnouncementErrorCode::InvalidDeliveryStatus.to_string(),
+                    vec![message],
+                ))
+            }
+            ReportAnnouncementDeliveryError::InternalServerError => {
+                return HttpResponse::InternalServerError().json(HttpErrorResponse::new(
+                    AnnouncementErrorCode::InternalServerError.to_string(),
+                    vec![ReportAnnouncementDeliveryError::InternalServerError.to_string()],
+                ))
+            }
+        }
+    }
+
+    HttpResponse::NoContent().finish()
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DevicePlaylistItemObject {
+    announcement_id: i32,
+    title: String,
+    media_type: MediaType,
+    duration: Option<f64>,
+    priority: i32,
+    weight: i32,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetDevicePlaylistResponse {
+    items: Vec<DevicePlaylistItemObject>,
+}
+
+/// One rotation of the playlist, the device plays the items in order and starts over once it is done
+pub async fn get_device_playlist_device(
+    announcement_service: web::Data<Arc<dyn AnnouncementServiceInterface + Send + Sync + 'static>>,
+    auth: device_middleware::DeviceAuthenticationContext,
+) -> HttpResponse {
+    let device_id = match device_middleware::get_device_id(auth) {
+        Ok(id) => id,
+        Err(e) => return device_middleware::parse_device_authentication_middleware_error(e),
+    };
+
+    let playlist = match announcement_service.get_device_playlist(device_id).await {
+        Ok(playlist) => playlist,
+        Err(e) => match e {
+            GetDevicePlaylistError::InternalServerError => {
+                return HttpResponse::InternalServerError().json(HttpErrorResponse::new(
+                    AnnouncementErrorCode::InternalServerError.to_string(),
+                    vec![GetDevicePlaylistError::InternalServerError.to_string()],
+                ))
+            }
+        },
+    };
+
+    HttpResponse::Ok().json(GetDevicePlaylistResponse {
+        items: playlist
+            .into_iter()
+            .map(|item| DevicePlaylistItemObject {
+                announcement_id: item.announcement_id,
+                title: item.title,
+                media_type: item.media_type,
+                duration: item.duration,
+                priority: item.priority,
+                weight: item.weight,
+            })
+            .collect(),
+    })
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceManifestAnnouncementObject {
+    id: i32,
+    title: String,
+    media_url: String,
+    media_type: MediaType,
+    duration: Option<f64>,
+    content_hash: Option<String>,
+    valid_from: String,
+    valid_until: String,
+    recurrence: Option<AnnouncementRecurrenceObject>,
+    priority: i32,
+    weight: i32,
+    is_emergency: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetDeviceManifestResponse {
+    etag: String,
+    media_urls_expire_at: String,
+    announcements: Vec<DeviceManifestAnnouncementObject>,
+}
+
+/// Whether the `If-None-Match` header of the request lists the given ETag
+fn is_etag_matching(req: &HttpRequest, etag: &str) -> bool {
+    let value = match req.headers().get(header::IF_NONE_MATCH) {
+        Some(value) => match value.to_str() {
+            Ok(value) => value,
+            Err(_) => return false,
+        },
+        None => return false,
+    };
+
+    value.split(',').map(|tag| tag.trim()).any(|tag| {
+        tag == "*" || tag.strip_prefix("W/").unwrap_or(tag) == etag
+    })
+}
+
+/// Everything the device should currently display, devices poll it with `If-None-Match` and
+/// use it to recover their state without replaying the device queue
+pub async fn get_device_manifest_device(
+    announcement_service: web::Data<Arc<dyn AnnouncementServiceInterface + Send + Sync + 'static>>,
+    auth: device_middleware::DeviceAuthenticationContext,
+    req: HttpRequest,
+) -> HttpResponse {
+    let device_id = match device_middleware::get_device_id(auth) {
+        Ok(id) => id,
+        Err(e) => return device_middleware::parse_device_authentication_middleware_error(e),
+    };
+
+    let manifest = match announcement_service.get_device_manifest(device_id).await {
+        Ok(manifest) => manifest,
+        Err(e) => match e {
+            GetDeviceManifestError::InternalServerError => {
+                return HttpResponse::InternalServerError().json(HttpErrorResponse::new(
+                    AnnouncementErrorCode::InternalServerError.to_string(),
+                    vec![GetDeviceManifestError::InternalServerError.to_string()],
+                ))
+            }
+        },
+    };
+
+    if is_etag_matching(&req, &manifest.etag) {
+        return HttpResponse::NotModified()
+            .insert_header((header::ETAG, manifest.etag))
+            .insert_header((header::CACHE_CONTROL, "no-cache"))
+            .finish();
+    }
+
+    HttpResponse::Ok()
+        .insert_header((header::ETAG, manifest.etag.clone()))
+        .insert_header((header::CACHE_CONTROL, "no-cache"))
+        .json(GetDeviceManifestResponse {
+            etag: manifest.etag,
+            media_urls_expire_at: manifest.media_urls_expire_at.to_rfc3339(),
+            announcements: manifest
+                .items
+                .into_iter()
+                .map(|item| DeviceManifestAnnouncementObject {
+                    id: item.announcement.id,
+                    duration: item.announcement.duration(),
+                    title: item.announcement.title,
+                    media_url: item.media_url,
+                    media_type: item.announcement.media_type,
+                    content_hash: item.announcement.content_hash,
+                    valid_from: item.announcement.start_date.to_rfc3339(),
+                    valid_until: item.announcement.end_date.to_rfc3339(),
+                    recurrence: item
+                        .announcement
+                        .recurrence
+                        .map(AnnouncementRecurrenceObject::from),
+                    priority: item.announcement.priority,
+                    weight: item.announcement.weight,
+                    is_emergency: item.announcement.is_emergency,
+                })
+                .collect(),
+        })
+}
+
+/// The schema of the messages published to the device queues, served so the device firmware can validate against it
+pub async fn get_sync_envelope_schema_device() -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("application/schema+json")
+        .body(include_str!("../../../schemas/device_sync_envelope.v1.json"))
+}
@@ -1,16 +1,27 @@
-use std::collections::BTreeMap;
+use std::sync::Arc;
 
 use async_trait::async_trait;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-use crate::queue::Producer;
+use crate::{
+    database::UnitOfWork,
+    outbox::{InsertOutboxMessageParams, OutboxRepositoryInterface},
+    queue::{Envelope, QueueBackendInterface},
+};
 
-#[derive(Debug, Serialize, PartialEq)]
+use super::AnnouncementRecurrence;
+
+const QUEUE_CLEANUP_CONSUMER_NAME: &str = "device-cleanup";
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub enum AnnouncementSyncAction {
     Create,
     Delete,
     Resync,
+    /// The device plays the announcement over its whole playlist until the emergency is cleared
+    Emergency,
+    ClearEmergency,
 }
 
 pub enum AnnouncementQueueError {
@@ -27,14 +38,16 @@ impl std::fmt::Display for AnnouncementQueueError {
     }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub struct DeviceSynchronizationParams {
     action: AnnouncementSyncAction,
     announcement_id: Option<i32>,
     announcement_ids: Option<Vec<i32>>,
+    emergency_announcement_id: Option<i32>,
     media_type: Option<String>,
     media_duration: Option<f64>,
+    recurrence: Option<AnnouncementRecurrence>,
 }
 
 impl DeviceSynchronizationParams {
@@ -43,8 +56,10 @@ impl DeviceSynchronizationParams {
             action,
             announcement_id: None,
             announcement_ids: None,
+            emergency_announcement_id: None,
             media_type: None,
             media_duration: None,
+            recurrence: None,
         }
     }
 
@@ -58,6 +73,11 @@ impl DeviceSynchronizationParams {
         self
     }
 
+    pub fn emergency_announcement_id(mut self, id: i32) -> Self {
+        self.emergency_announcement_id = Some(id);
+        self
+    }
+
     pub fn media_type (mut self, media_type: String) -> Self {
         self.media_type = Some(media_type);
         self
@@ -67,59 +87,142 @@ impl DeviceSynchronizationParams {
         self.media_duration = Some(media_duration);
         self
     }
+
+    pub fn recurrence(mut self, recurrence: AnnouncementRecurrence) -> Self {
+        self.recurrence = Some(recurrence);
+        self
+    }
+}
+
+/// The envelope type of the messages published to the device queues
+pub const DEVICE_SYNCHRONIZATION_MESSAGE_TYPE: &str = "device_synchronization";
+
+pub fn device_queue_name(device_id: i32) -> String {
+    format!("device-queue-{}", device_id)
 }
 
+/// Synchronization messages are written to the outbox within the caller's unit of work,
+/// they only reach the device queues once the unit of work has been committed.
 #[async_trait]
 pub trait AnnouncementQueueInterface {
     async fn create(
         &self,
+        unit_of_work: &mut UnitOfWork,
         device_ids: Vec<i32>,
         announcement_id: i32,
         media_type: String,
         media_duration: Option<f64>,
+        recurrence: Option<AnnouncementRecurrence>,
     ) -> Result<(), AnnouncementQueueError>;
     async fn delete(
         &self,
+        unit_of_work: &mut UnitOfWork,
         device_ids: Vec<i32>,
         announcement_id: i32,
     ) -> Result<(), AnnouncementQueueError>;
     async fn resync(
         &self,
+        unit_of_work: &mut UnitOfWork,
         device_id: i32,
         announcement_ids: Vec<i32>,
+        emergency_announcement_id: Option<i32>,
+    ) -> Result<(), AnnouncementQueueError>;
+    async fn emergency(
+        &self,
+        unit_of_work: &mut UnitOfWork,
+        device_ids: Vec<i32>,
+        announcement_id: i32,
+        media_type: String,
+        media_duration: Option<f64>,
+    ) -> Result<(), AnnouncementQueueError>;
+    async fn clear_emergency(
+        &self,
+        unit_of_work: &mut UnitOfWork,
+        device_ids: Vec<i32>,
+        announcement_id: i32,
     ) -> Result<(), AnnouncementQueueError>;
+    async fn remove_device_queue(&self, device_id: i32) -> Result<(), AnnouncementQueueError>;
 }
 
 pub struct AnnouncementQueue {
-    _redis: deadpool_redis::Pool,
+    _outbox_repository: Arc<dyn OutboxRepositoryInterface>,
+    _queue_backend: Arc<dyn QueueBackendInterface>,
 }
 
 impl AnnouncementQueue {
-    pub fn new(_redis: deadpool_redis::Pool) -> Self {
-        AnnouncementQueue { _redis }
+    pub fn new(
+        _outbox_repository: Arc<dyn OutboxRepositoryInterface>,
+        _queue_backend: Arc<dyn QueueBackendInterface>,
+    ) -> Self {
+        AnnouncementQueue {
+            _outbox_repository,
+            _queue_backend,
+        }
     }
 
-    pub fn create_producer(&self, device_id: i32) -> Producer {
-        Producer::new(self._redis.clone(), format!("device-queue-{}", device_id))
+    pub fn queue_name(&self, device_id: i32) -> String {
+        device_queue_name(device_id)
     }
 
-    pub fn create_payload_map(&self, data: &String) -> BTreeMap<String, String> {
-        let mut map: BTreeMap<String, String> = BTreeMap::new();
-        map.insert("data".into(), data.clone());
+    /// Every device gets its own envelope, hence its own message id
+    pub fn synchronization_messages(
+        &self,
+        device_ids: Vec<i32>,
+        params: &DeviceSynchronizationParams,
+    ) -> Result<Vec<InsertOutboxMessageParams>, AnnouncementQueueError> {
+        let mut messages: Vec<InsertOutboxMessageParams> = vec![];
+
+        for device_id in device_ids {
+            let envelope = Envelope::new(DEVICE_SYNCHRONIZATION_MESSAGE_TYPE, params);
+
+            let payload = match serde_json::to_string(&envelope) {
+                Ok(payload) => payload,
+                Err(e) => {
+                    return Err(AnnouncementQueueError::PayloadSerializationError(
+                        e.to_string(),
+                    ))
+                }
+            };
+
+            messages.push(InsertOutboxMessageParams {
+                queue_name: self.queue_name(device_id),
+                payload,
+            });
+        }
 
-        map
+        Ok(messages)
     }
 
+    async fn push(
+        &self,
+        unit_of_work: &mut UnitOfWork,
+        device_ids: Vec<i32>,
+        params: DeviceSynchronizationParams,
+    ) -> Result<(), AnnouncementQueueError> {
+        let messages = self.synchronization_messages(device_ids, &params)?;
+
+        if let Err(_) = self
+            ._outbox_repository
+            .insert(unit_of_work, messages)
+            .await
+        {
+            return Err(AnnouncementQueueError::InternalServerError);
+        }
+
+        Ok(())
+    }
 }
 
 #[async_trait]
 impl AnnouncementQueueInterface for AnnouncementQueue {
     async fn create(
         &self,
+        unit_of_work: &mut UnitOfWork,
         device_ids: Vec<i32>,
         announcement_id: i32,
         media_type: String,
         media_duration: Option<f64>,
+        recurrence: Option<AnnouncementRecurrence>,
     ) -> Result<(), AnnouncementQueueError> {
         let mut params = DeviceSynchronizationParams::new(AnnouncementSyncAction::Create)
             .announcement_id(announcement_id)
@@ -127,74 +230,90 @@ impl AnnouncementQueueInterface for AnnouncementQueue {
         if let Some(duration) = media_duration {
             params = params.media_duration(duration);
         }
-
-        let payload = match serde_json::to_string(&params) {
-            Ok(payload) => payload,
-            Err(e) => {
-                return Err(AnnouncementQueueError::PayloadSerializationError(
-                    e.to_string(),
-                ))
-            }
-        };
-
-        for device_id in device_ids {
-            let producer = self.create_producer(device_id);
-            if let Err(_) = producer.push(self.create_payload_map(&payload)).await {
-                return Err(AnnouncementQueueError::InternalServerError);
-            };
+        if let Some(recurrence) = recurrence {
+            params = params.recurrence(recurrence);
         }
 
-        Ok(())
+        self.push(unit_of_work, device_ids, params).await
     }
 
     async fn delete(
         &self,
+        unit_of_work: &mut UnitOfWork,
         device_ids: Vec<i32>,
         announcement_id: i32,
     ) -> Result<(), AnnouncementQueueError> {
         let params = DeviceSynchronizationParams::new(AnnouncementSyncAction::Delete)
             .announcement_id(announcement_id);
 
-        let payload = match serde_json::to_string(&params) {
-            Ok(payload) => payload,
-            Err(e) => {
-                return Err(AnnouncementQueueError::PayloadSerializationError(
-                    e.to_string(),
-                ))
-            }
-        };
-
-        for device_id in device_ids {
-            let producer = self.create_producer(device_id);
-            if let Err(_) = producer.push(self.create_payload_map(&payload)).await {
-                return Err(AnnouncementQueueError::InternalServerError);
-            };
-        }
-
-        Ok(())
+        self.push(unit_of_work, device_ids, params).await
     }
 
     async fn resync(
         &self,
+        unit_of_work: &mut UnitOfWork,
         device_id: i32,
         announcement_ids: Vec<i32>,
+        emergency_announcement_id: Option<i32>,
     ) -> Result<(), AnnouncementQueueError> {
-        let params = DeviceSynchronizationParams::new(AnnouncementSyncAction::Resync)
+        let mut params = DeviceSynchronizationParams::new(AnnouncementSyncAction::Resync)
             .announcement_ids(announcement_ids);
+        if let Some(id) = emergency_announcement_id {
+            params = params.emergency_announcement_id(id);
+        }
+
+        self.push(unit_of_work, vec![device_id], params).await
+    }
+
+    async fn emergency(
+        &self,
+        unit_of_work: &mut UnitOfWork,
+        device_ids: Vec<i32>,
+        announcement_id: i32,
+        media_type: String,
+        media_duration: Option<f64>,
+    ) -> Result<(), AnnouncementQueueError> {
+        let mut params = DeviceSynchronizationParams::new(AnnouncementSyncAction::Emergency)
+            .announcement_id(announcement_id)
+            .media_type(media_type);
+        if let Some(duration) = media_duration {
+            params = params.media_duration(duration);
+        }
+
+        self.push(unit_of_work, device_ids, params).await
+    }
+
+    async fn clear_emergency(
+        &self,
+        unit_of_work: &mut UnitOfWork,
+        device_ids: Vec<i32>,
+        announcement_id: i32,
+    ) -> Result<(), AnnouncementQueueError> {
+        let params = DeviceSynchronizationParams::new(AnnouncementSyncAction::ClearEmergency)
+            .announcement_id(announcement_id);
+
+        self.push(unit_of_work, device_ids, params).await
+    }
 
-        let payload = match serde_json::to_string(&params) {
-            Ok(payload) => payload,
-            Err(e) => {
-                return Err(AnnouncementQueueError::PayloadSerializationError(
-                    e.to_string(),
-                ))
-            }
-        };
-
-        let producer = self.create_producer(device_id);
-        if let Err(_) = producer.push(self.create_payload_map(&payload)).await {
+    /// Drops the messages that were not relayed yet, then the stream of the device together
+    /// with its consumer group and dead letters
+    async fn remove_device_queue(&self, device_id: i32) -> Result<(), AnnouncementQueueError> {
+        let queue_name = self.queue_name(device_id);
+
+        if let Err(_) = self
+            ._outbox_repository
+            .delete_undelivered(queue_name.clone())
+            .await
+        {
             return Err(AnnouncementQueueError::InternalServerError);
-        };
+        }
+
+        let mut consumer = self
+            ._queue_backend
+            .consumer(queue_name, QUEUE_CLEANUP_CONSUMER_NAME.to_string());
+        if let Err(_) = consumer.delete().await {
+            return Err(AnnouncementQueueError::InternalServerError);
+        }
 
         Ok(())
     }
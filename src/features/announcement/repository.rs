@@ -1,17 +1,26 @@
 use std::collections::BTreeMap;
 
 use async_trait::async_trait;
-use sqlx::{postgres::PgRow, Pool, Postgres, Row};
+use sqlx::{postgres::PgRow, PgConnection, Pool, Postgres, Row};
 
-use crate::{database::PaginationResult, features::media::domain::MediaType};
+use crate::{
+    database::{PaginationResult, UnitOfWork},
+    features::media::domain::MediaType,
+};
 
-use super::{Announcement, AnnouncementDetail, AnnouncementDetailDevices, AnnouncementStatus};
+use super::{
+    Announcement, AnnouncementDeliveryStatus, AnnouncementDetail, AnnouncementDetailDevices,
+    AnnouncementRecurrence, AnnouncementStatus, AnnouncementTargets, ManifestAnnouncement,
+    PlaylistAnnouncement,
+    RecurringAnnouncement,
+};
 
 pub struct CountAnnouncementParams {
     pub query: Option<String>,
     pub status: Option<AnnouncementStatus>,
     pub user_id: Option<i32>,
     pub device_id: Option<i32>,
+    pub is_emergency: Option<bool>,
 
     pub start_date_gt: Option<chrono::DateTime<chrono::Utc>>,
     pub start_date_gte: Option<chrono::DateTime<chrono::Utc>>,
@@ -31,6 +40,7 @@ impl CountAnnouncementParams {
             status: None,
             user_id: None,
             device_id: None,
+            is_emergency: None,
 
             start_date_gt: None,
             start_date_gte: None,
@@ -64,6 +74,11 @@ impl CountAnnouncementParams {
         self
     }
 
+    pub fn is_emergency(mut self, is_emergency: bool) -> Self {
+        self.is_emergency = Some(is_emergency);
+        self
+    }
+
     pub fn start_date_gt(mut self, start_date_gt: chrono::DateTime<chrono::Utc>) -> Self {
         self.start_date_gt = Some(start_date_gt);
         self
@@ -112,6 +127,7 @@ pub struct FindListAnnouncementParams {
     pub status: Option<AnnouncementStatus>,
     pub user_id: Option<i32>,
     pub device_id: Option<i32>,
+    pub is_emergency: Option<bool>,
 
     pub start_date_gt: Option<chrono::DateTime<chrono::Utc>>,
     pub start_date_gte: Option<chrono::DateTime<chrono::Utc>>,
@@ -134,6 +150,7 @@ impl FindListAnnouncementParams {
             status: None,
             user_id: None,
             device_id: None,
+            is_emergency: None,
 
             start_date_gt: None,
             start_date_gte: None,
@@ -177,6 +194,11 @@ impl FindListAnnouncementParams {
         self
     }
 
+    pub fn is_emergency(mut self, is_emergency: bool) -> Self {
+        self.is_emergency = Some(is_emergency);
+        self
+    }
+
     pub fn start_date_gt(mut self, start_date_gt: chrono::DateTime<chrono::Utc>) -> Self {
         self.start_date_gt = Some(start_date_gt);
         self
@@ -226,6 +248,26 @@ pub struct InsertAnnouncementParams {
     pub notes: String,
     pub device_ids: Vec<i32>,
     pub user_id: i32,
+    pub recurrence: Option<AnnouncementRecurrence>,
+    pub is_emergency: bool,
+    pub targets: AnnouncementTargets,
+    /// The devices among `device_ids` that were only picked because of the targets
+    pub targeted_device_ids: Vec<i32>,
+    pub priority: i32,
+    pub weight: i32,
+    pub display_duration: Option<f64>,
+}
+
+/// Foreign keys of the `announcement_target` table are named after it, a violation means
+/// one of the buildings or floors does not exist
+pub fn is_announcement_target_constraint(constraint: Option<&str>) -> bool {
+    constraint.map_or(false, |name| name.starts_with("announcement_target_"))
+}
+
+pub struct UpdateAnnouncementContentParams {
+    pub title: Option<String>,
+    pub notes: Option<String>,
+    pub media_id: Option<i32>,
 }
 
 pub struct ListAnnouncementRow {
@@ -238,6 +280,8 @@ pub struct ListAnnouncementRow {
     announcement_media: String,
     announcement_media_type: MediaType,
     announcement_media_duration: Option<f64>,
+    announcement_recurrence: Option<sqlx::types::Json<AnnouncementRecurrence>>,
+    announcement_is_emergency: bool,
     announcement_created_at: chrono::DateTime<chrono::Utc>,
     announcement_updated_at: chrono::DateTime<chrono::Utc>,
     user_id: i32,
@@ -254,6 +298,12 @@ pub struct AnnouncementDetailRow {
     announcement_media_type: MediaType,
     announcement_media_duration: Option<f64>,
     announcement_notes: String,
+    announcement_recurrence: Option<sqlx::types::Json<AnnouncementRecurrence>>,
+    announcement_occurrence_active: bool,
+    announcement_is_emergency: bool,
+    announcement_priority: i32,
+    announcement_weight: i32,
+    announcement_display_duration: Option<f64>,
     announcement_created_at: chrono::DateTime<chrono::Utc>,
     announcement_updated_at: chrono::DateTime<chrono::Utc>,
     user_id: i32,
@@ -262,6 +312,9 @@ pub struct AnnouncementDetailRow {
     device_name: String,
     device_description: String,
     device_floor_id: i32,
+    device_delivery_status: AnnouncementDeliveryStatus,
+    device_delivery_error: Option<String>,
+    device_delivery_updated_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 #[async_trait]
@@ -272,14 +325,25 @@ pub trait AnnouncementRepositoryInterface {
         params: FindListAnnouncementParams,
     ) -> Result<PaginationResult<Announcement>, sqlx::Error>;
     async fn find_one(&self, announcement_id: i32) -> Result<AnnouncementDetail, sqlx::Error>;
-    async fn insert(&self, params: InsertAnnouncementParams) -> Result<i32, sqlx::Error>;
+    async fn find_one_in_unit_of_work(
+        &self,
+        unit_of_work: &mut UnitOfWork,
+        announcement_id: i32,
+    ) -> Result<AnnouncementDetail, sqlx::Error>;
+    async fn insert(
+        &self,
+        unit_of_work: &mut UnitOfWork,
+        params: InsertAnnouncementParams,
+    ) -> Result<i32, sqlx::Error>;
     async fn update_status(
         &self,
+        unit_of_work: &mut UnitOfWork,
         announcement_id: i32,
         status: AnnouncementStatus,
     ) -> Result<(), sqlx::Error>;
     async fn batch_update_status(
         &self,
+        unit_of_work: &mut UnitOfWork,
         announcement_ids: Vec<i32>,
         status: AnnouncementStatus,
     ) -> Result<(), sqlx::Error>;
@@ -293,15 +357,64 @@ pub trait AnnouncementRepositoryInterface {
     ) -> Result<BTreeMap<i32, Vec<i32>>, sqlx::Error>;
     async fn extend_end_date(
         &self,
+        unit_of_work: &mut UnitOfWork,
         announcement_id: i32,
         end_date: chrono::DateTime<chrono::Utc>,
     ) -> Result<(), sqlx::Error>;
     async fn update_announcement_target_devices(
         &self,
+        unit_of_work: &mut UnitOfWork,
         announcement_id: i32,
         to_be_removed_device_ids: Vec<i32>,
         to_be_added_device_ids: Vec<i32>,
     ) -> Result<(), sqlx::Error>;
+    async fn update_dates(
+        &self,
+        unit_of_work: &mut UnitOfWork,
+        announcement_id: i32,
+        start_date: chrono::DateTime<chrono::Utc>,
+        end_date: chrono::DateTime<chrono::Utc>,
+    ) -> Result<(), sqlx::Error>;
+    async fn delete(
+        &self,
+        unit_of_work: &mut UnitOfWork,
+        announcement_id: i32,
+    ) -> Result<(), sqlx::Error>;
+    async fn update_content(
+        &self,
+        unit_of_work: &mut UnitOfWork,
+        announcement_id: i32,
+        params: UpdateAnnouncementContentParams,
+    ) -> Result<(), sqlx::Error>;
+    async fn find_active_recurring_announcements(
+        &self,
+    ) -> Result<Vec<RecurringAnnouncement>, sqlx::Error>;
+    async fn update_delivery_status(
+        &self,
+        announcement_id: i32,
+        device_id: i32,
+        status: AnnouncementDeliveryStatus,
+        error: Option<String>,
+    ) -> Result<(), sqlx::Error>;
+    async fn batch_update_occurrence_active(
+        &self,
+        unit_of_work: &mut UnitOfWork,
+        announcement_ids: Vec<i32>,
+        occurrence_active: bool,
+    ) -> Result<(), sqlx::Error>;
+    async fn find_device_ids_by_location(
+        &self,
+        building_ids: Vec<i32>,
+        floor_ids: Vec<i32>,
+    ) -> Result<Vec<i32>, sqlx::Error>;
+    async fn find_playlist_announcements(
+        &self,
+        device_id: i32,
+    ) -> Result<Vec<PlaylistAnnouncement>, sqlx::Error>;
+    async fn find_manifest_announcements(
+        &self,
+        device_id: i32,
+    ) -> Result<Vec<ManifestAnnouncement>, sqlx::Error>;
 }
 pub struct AnnouncementRepository {
     _db: Pool<Postgres>,
@@ -313,6 +426,150 @@ impl AnnouncementRepository {
     }
 }
 
+async fn find_announcement_detail(
+    conn: &mut PgConnection,
+    announcement_id: i32,
+) -> Result<AnnouncementDetail, sqlx::Error> {
+    let result = sqlx::query(
+            r#"
+            select
+                "announcement"."id" as "announcement_id",
+                "announcement"."title" as "announcement_title",
+                "media"."path" as "announcement_media",
+                "media"."media_type" as "announcement_media_type",
+                "media"."media_duration" as "announcement_media_duration",
+                "announcement"."notes" as "announcement_notes",
+                "announcement"."recurrence" as "announcement_recurrence",
+                "announcement"."occurrence_active" as "announcement_occurrence_active",
+                "announcement"."is_emergency" as "announcement_is_emergency",
+                "announcement"."priority" as "announcement_priority",
+                "announcement"."weight" as "announcement_weight",
+                "announcement"."display_duration" as "announcement_display_duration",
+                "announcement"."status" as "announcement_status",
+                "announcement"."start_date" as "announcement_start_date",
+                "announcement"."end_date" as "announcement_end_date",
+                "announcement"."created_at" as "announcement_created_at",
+                "announcement"."updated_at" as "announcement_updated_at",
+                "user"."id" as "user_id",
+                "user"."name" as "user_name",
+                "device"."id" as "device_id",
+                "device"."name" as "device_name",
+                "device"."description" as "device_description",
+                "device"."floor_id" as "device_floor_id",
+                "device_announcement"."delivery_status" as "device_delivery_status",
+                "device_announcement"."delivery_error" as "device_delivery_error",
+                "device_announcement"."delivery_updated_at" as "device_delivery_updated_at"
+            from "announcement"
+            join "user" on "user"."id" = "announcement"."user_id"
+            join "media" on "media"."id" = "announcement"."media_id"
+            join "device_announcement" on "device_announcement"."announcement_id" = "announcement"."id"
+            join "device" on "device"."id" = "device_announcement"."device_id"
+            where "announcement"."id" = $1
+            "#,
+    )
+    .bind(announcement_id)
+    .map(|row: PgRow| AnnouncementDetailRow {
+        announcement_id: row.get("announcement_id"),
+        announcement_title: row.get("announcement_title"),
+        announcement_start_date: row.get("announcement_start_date"),
+        announcement_end_date: row.get("announcement_end_date"),
+        announcement_status: row.get("announcement_status"),
+        announcement_media: row.get("announcement_media"),
+        announcement_media_type: row.get("announcement_media_type"),
+        announcement_media_duration: row.get("announcement_media_duration"),
+        announcement_notes: row.get("announcement_notes"),
+        announcement_recurrence: row.get("announcement_recurrence"),
+        announcement_occurrence_active: row.get("announcement_occurrence_active"),
+        announcement_is_emergency: row.get("announcement_is_emergency"),
+        announcement_priority: row.get("announcement_priority"),
+        announcement_weight: row.get("announcement_weight"),
+        announcement_display_duration: row.get("announcement_display_duration"),
+        announcement_created_at: row.get("announcement_created_at"),
+        announcement_updated_at: row.get("announcement_updated_at"),
+        user_id: row.get("user_id"),
+        user_name: row.get("user_name"),
+        device_id: row.get("device_id"),
+        device_name: row.get("device_name"),
+        device_description: row.get("device_description"),
+        device_floor_id: row.get("device_floor_id"),
+        device_delivery_status: row.get("device_delivery_status"),
+        device_delivery_error: row.get("device_delivery_error"),
+        device_delivery_updated_at: row.get("device_delivery_updated_at"),
+    })
+    .fetch_all(&mut *conn)
+    .await?;
+
+    if result.len() == 0 {
+        return Err(sqlx::Error::RowNotFound);
+    }
+
+    let target_rows = sqlx::query(
+        r#"
+        select "building_id", "floor_id"
+        from "announcement_target"
+        where "announcement_id" = $1
+        order by "id"
+        "#,
+    )
+    .bind(announcement_id)
+    .map(|row: PgRow| {
+        let building_id: Option<i32> = row.get("building_id");
+        let floor_id: Option<i32> = row.get("floor_id");
+
+        (building_id, floor_id)
+    })
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let mut targets = AnnouncementTargets::default();
+    for (building_id, floor_id) in target_rows {
+        if let Some(building_id) = building_id {
+            targets.building_ids.push(building_id);
+        }
+        if let Some(floor_id) = floor_id {
+            targets.floor_ids.push(floor_id);
+        }
+    }
+
+    Ok(AnnouncementDetail {
+        id: result[0].announcement_id,
+        title: result[0].announcement_title.clone(),
+        media: result[0].announcement_media.clone(),
+        media_type: result[0].announcement_media_type.clone(),
+        media_duration: result[0].announcement_media_duration,
+        notes: result[0].announcement_notes.clone(),
+        status: result[0].announcement_status.clone(),
+        start_date: result[0].announcement_start_date,
+        end_date: result[0].announcement_end_date,
+        recurrence: result[0]
+            .announcement_recurrence
+            .clone()
+            .map(|recurrence| recurrence.0),
+        occurrence_active: result[0].announcement_occurrence_active,
+        is_emergency: result[0].announcement_is_emergency,
+        targets,
+        priority: result[0].announcement_priority,
+        weight: result[0].announcement_weight,
+        display_duration: result[0].announcement_display_duration,
+        created_at: result[0].announcement_created_at,
+        updated_at: result[0].announcement_updated_at,
+        user_id: result[0].user_id,
+        user_name: result[0].user_name.clone(),
+        devices: result
+            .into_iter()
+            .map(|row| AnnouncementDetailDevices {
+                id: row.device_id,
+                name: row.device_name,
+                description: row.device_description,
+                floor_id: row.device_floor_id,
+                delivery_status: row.device_delivery_status,
+                delivery_error: row.device_delivery_error,
+                delivery_updated_at: row.device_delivery_updated_at,
+            })
+            .collect(),
+    })
+}
+
 #[async_trait]
 impl AnnouncementRepositoryInterface for AnnouncementRepository {
     async fn count(&self, params: CountAnnouncementParams) -> Result<i32, sqlx::Error> {
@@ -341,7 +598,8 @@ impl AnnouncementRepositoryInterface for AnnouncementRepository {
                 ($9::timestamp is null or "announcement"."end_date" > $9) and
                 ($10::timestamp is null or "announcement"."end_date" >= $10) and
                 ($11::timestamp is null or "announcement"."end_date" < $11) and
-                ($12::timestamp is null or "announcement"."end_date" <= $12)
+                ($12::timestamp is null or "announcement"."end_date" <= $12) and
+                ($13::boolean is null or "announcement"."is_emergency" = $13)
 
             "#,
         )
@@ -357,6 +615,7 @@ impl AnnouncementRepositoryInterface for AnnouncementRepository {
         .bind(params.end_date_gte.clone())
         .bind(params.end_date_lt.clone())
         .bind(params.end_date_lte.clone())
+        .bind(params.is_emergency)
         .map(|row: PgRow| row.get("count"))
         .fetch_one(&self._db)
         .await?;
@@ -382,6 +641,8 @@ impl AnnouncementRepositoryInterface for AnnouncementRepository {
                 "media"."path" as "announcement_media",
                 "media"."media_type" as "announcement_media_type",
                 "media"."media_duration" as "announcement_media_duration",
+                "announcement"."recurrence" as "announcement_recurrence",
+                "announcement"."is_emergency" as "announcement_is_emergency",
                 "announcement"."created_at" as "announcement_created_at",
                 "announcement"."updated_at" as "announcement_updated_at",
                 "user"."id" as "user_id",
@@ -412,7 +673,8 @@ impl AnnouncementRepositoryInterface for AnnouncementRepository {
                     ($11::timestamp is null or "announcement"."end_date" > $11) and
                     ($12::timestamp is null or "announcement"."end_date" >= $12) and
                     ($13::timestamp is null or "announcement"."end_date" < $13) and
-                    ($14::timestamp is null or "announcement"."end_date" <= $14)
+                    ($14::timestamp is null or "announcement"."end_date" <= $14) and
+                    ($15::boolean is null or "announcement"."is_emergency" = $15)
             ) "result" on true
             where
                 (
@@ -432,7 +694,8 @@ impl AnnouncementRepositoryInterface for AnnouncementRepository {
                 ($11::timestamp is null or "announcement"."end_date" > $11) and
                 ($12::timestamp is null or "announcement"."end_date" >= $12) and
                 ($13::timestamp is null or "announcement"."end_date" < $13) and
-                ($14::timestamp is null or "announcement"."end_date" <= $14)
+                ($14::timestamp is null or "announcement"."end_date" <= $14) and
+                ($15::boolean is null or "announcement"."is_emergency" = $15)
             group by "announcement"."id", "media"."id", "user"."id", "result"."count"
             order by "announcement"."id" desc
             offset $1 limit $2
@@ -452,6 +715,7 @@ impl AnnouncementRepositoryInterface for AnnouncementRepository {
         .bind(params.end_date_gte)
         .bind(params.end_date_lt)
         .bind(params.end_date_lte)
+        .bind(params.is_emergency)
         .map(|row: PgRow| ListAnnouncementRow {
             count: row.get("count"),
             announcement_id: row.get("announcement_id"),
@@ -462,6 +726,8 @@ impl AnnouncementRepositoryInterface for AnnouncementRepository {
             announcement_media: row.get("announcement_media"),
             announcement_media_type: row.get("announcement_media_type"),
             announcement_media_duration: row.get("announcement_media_duration"),
+            announcement_recurrence: row.get("announcement_recurrence"),
+            announcement_is_emergency: row.get("announcement_is_emergency"),
             announcement_created_at: row.get("announcement_created_at"),
             announcement_updated_at: row.get("announcement_updated_at"),
             user_id: row.get("user_id"),
@@ -491,6 +757,8 @@ impl AnnouncementRepositoryInterface for AnnouncementRepository {
                 media: row.announcement_media,
                 media_type: row.announcement_media_type,
                 media_duration: row.announcement_media_duration,
+                recurrence: row.announcement_recurrence.map(|recurrence| recurrence.0),
+                is_emergency: row.announcement_is_emergency,
                 created_at: row.announcement_created_at,
                 updated_at: row.announcement_updated_at,
             })
@@ -505,88 +773,24 @@ impl AnnouncementRepositoryInterface for AnnouncementRepository {
     }
 
     async fn find_one(&self, announcement_id: i32) -> Result<AnnouncementDetail, sqlx::Error> {
-        let result = sqlx::query(
-                r#"
-                select
-                    "announcement"."id" as "announcement_id",
-                    "announcement"."title" as "announcement_title",
-                    "media"."path" as "announcement_media",
-                    "media"."media_type" as "announcement_media_type",
-                    "media"."media_duration" as "announcement_media_duration",
-                    "announcement"."notes" as "announcement_notes",
-                    "announcement"."status" as "announcement_status",
-                    "announcement"."start_date" as "announcement_start_date",
-                    "announcement"."end_date" as "announcement_end_date",
-                    "announcement"."created_at" as "announcement_created_at",
-                    "announcement"."updated_at" as "announcement_updated_at",
-                    "user"."id" as "user_id",
-                    "user"."name" as "user_name",
-                    "device"."id" as "device_id",
-                    "device"."name" as "device_name",
-                    "device"."description" as "device_description",
-                    "device"."floor_id" as "device_floor_id"
-                from "announcement"
-                join "user" on "user"."id" = "announcement"."user_id"
-                join "media" on "media"."id" = "announcement"."media_id"
-                join "device_announcement" on "device_announcement"."announcement_id" = "announcement"."id"
-                join "device" on "device"."id" = "device_announcement"."device_id"
-                where "announcement"."id" = $1
-                "#,
-        )
-        .bind(announcement_id)
-        .map(|row: PgRow| AnnouncementDetailRow {
-            announcement_id: row.get("announcement_id"),
-            announcement_title: row.get("announcement_title"),
-            announcement_start_date: row.get("announcement_start_date"),
-            announcement_end_date: row.get("announcement_end_date"),
-            announcement_status: row.get("announcement_status"),
-            announcement_media: row.get("announcement_media"),
-            announcement_media_type: row.get("announcement_media_type"),
-            announcement_media_duration: row.get("announcement_media_duration"),
-            announcement_notes: row.get("announcement_notes"),
-            announcement_created_at: row.get("announcement_created_at"),
-            announcement_updated_at: row.get("announcement_updated_at"),
-            user_id: row.get("user_id"),
-            user_name: row.get("user_name"),
-            device_id: row.get("device_id"),
-            device_name: row.get("device_name"),
-            device_description: row.get("device_description"),
-            device_floor_id: row.get("device_floor_id"),
-        })
-        .fetch_all(&self._db)
-        .await?;
+        let mut conn = self._db.acquire().await?;
 
-        if result.len() == 0 {
-            return Err(sqlx::Error::RowNotFound);
-        }
+        find_announcement_detail(&mut conn, announcement_id).await
+    }
 
-        Ok(AnnouncementDetail {
-            id: result[0].announcement_id,
-            title: result[0].announcement_title.clone(),
-            media: result[0].announcement_media.clone(),
-            media_type: result[0].announcement_media_type.clone(),
-            media_duration: result[0].announcement_media_duration,
-            notes: result[0].announcement_notes.clone(),
-            status: result[0].announcement_status.clone(),
-            start_date: result[0].announcement_start_date,
-            end_date: result[0].announcement_end_date,
-            created_at: result[0].announcement_created_at,
-            updated_at: result[0].announcement_updated_at,
-            user_id: result[0].user_id,
-            user_name: result[0].user_name.clone(),
-            devices: result
-                .into_iter()
-                .map(|row| AnnouncementDetailDevices {
-                    id: row.device_id,
-                    name: row.device_name,
-                    description: row.device_description,
-                    floor_id: row.device_floor_id,
-                })
-                .collect(),
-        })
+    async fn find_one_in_unit_of_work(
+        &self,
+        unit_of_work: &mut UnitOfWork,
+        announcement_id: i32,
+    ) -> Result<AnnouncementDetail, sqlx::Error> {
+        find_announcement_detail(unit_of_work.connection(), announcement_id).await
     }
 
-    async fn insert(&self, params: InsertAnnouncementParams) -> Result<i32, sqlx::Error> {
+    async fn insert(
+        &self,
+        unit_of_work: &mut UnitOfWork,
+        params: InsertAnnouncementParams,
+    ) -> Result<i32, sqlx::Error> {
         let result = sqlx::query!(
             r#"
                 with cte_announcement as (
@@ -605,16 +809,93 @@ impl AnnouncementRepositoryInterface for AnnouncementRepository {
             params.notes,
             params.user_id,
             &params.device_ids,
-        ).fetch_one(&self._db).await?;
+        ).fetch_one(unit_of_work.connection()).await?;
+
+        let id = match result.id {
+            Some(id) => id,
+            None => return Err(sqlx::Error::RowNotFound),
+        };
+
+        if let Some(recurrence) = params.recurrence {
+            sqlx::query(
+                r#"
+                update "announcement"
+                set "recurrence" = $2
+                where "id" = $1
+                "#,
+            )
+            .bind(id)
+            .bind(sqlx::types::Json(recurrence))
+            .execute(unit_of_work.connection())
+            .await?;
+        }
+
+        sqlx::query(
+            r#"
+            update "announcement"
+            set
+                "priority" = $2,
+                "weight" = $3,
+                "display_duration" = $4
+            where "id" = $1
+            "#,
+        )
+        .bind(id)
+        .bind(params.priority)
+        .bind(params.weight)
+        .bind(params.display_duration)
+        .execute(unit_of_work.connection())
+        .await?;
+
+        if params.is_emergency {
+            sqlx::query(
+                r#"
+                update "announcement"
+                set "is_emergency" = true
+                where "id" = $1
+                "#,
+            )
+            .bind(id)
+            .execute(unit_of_work.connection())
+            .await?;
+        }
+
+        if !params.targets.is_empty() {
+            sqlx::query(
+                r#"
+                insert into "announcement_target" ("announcement_id", "building_id", "floor_id")
+                select $1, unnest($2::int4[]), null
+                union all
+                select $1, null, unnest($3::int4[])
+                "#,
+            )
+            .bind(id)
+            .bind(&params.targets.building_ids)
+            .bind(&params.targets.floor_ids)
+            .execute(unit_of_work.connection())
+            .await?;
+        }
 
-        match result.id {
-            Some(id) => Ok(id),
-            None => Err(sqlx::Error::RowNotFound),
+        if params.targeted_device_ids.len() > 0 {
+            sqlx::query(
+                r#"
+                update "device_announcement"
+                set "targeted" = true
+                where "announcement_id" = $1 and "device_id" = any($2)
+                "#,
+            )
+            .bind(id)
+            .bind(&params.targeted_device_ids)
+            .execute(unit_of_work.connection())
+            .await?;
         }
+
+        Ok(id)
     }
 
     async fn update_status(
         &self,
+        unit_of_work: &mut UnitOfWork,
         announcement_id: i32,
         status: AnnouncementStatus,
     ) -> Result<(), sqlx::Error> {
@@ -627,7 +908,7 @@ impl AnnouncementRepositoryInterface for AnnouncementRepository {
             announcement_id,
             status as _,
         )
-        .execute(&self._db)
+        .execute(unit_of_work.connection())
         .await?
         .rows_affected();
 
@@ -640,6 +921,7 @@ impl AnnouncementRepositoryInterface for AnnouncementRepository {
 
     async fn batch_update_status(
         &self,
+        unit_of_work: &mut UnitOfWork,
         announcement_ids: Vec<i32>,
         status: AnnouncementStatus,
     ) -> Result<(), sqlx::Error> {
@@ -652,7 +934,7 @@ impl AnnouncementRepositoryInterface for AnnouncementRepository {
         )
         .bind(&announcement_ids)
         .bind(status)
-        .execute(&self._db)
+        .execute(unit_of_work.connection())
         .await?
         .rows_affected();
 
@@ -713,6 +995,7 @@ impl AnnouncementRepositoryInterface for AnnouncementRepository {
 
     async fn extend_end_date(
         &self,
+        unit_of_work: &mut UnitOfWork,
         announcement_id: i32,
         end_date: chrono::DateTime<chrono::Utc>,
     ) -> Result<(), sqlx::Error> {
@@ -725,7 +1008,7 @@ impl AnnouncementRepositoryInterface for AnnouncementRepository {
             announcement_id,
             end_date
         )
-        .execute(&self._db)
+        .execute(unit_of_work.connection())
         .await?
         .rows_affected();
 
@@ -738,6 +1021,7 @@ impl AnnouncementRepositoryInterface for AnnouncementRepository {
 
     async fn update_announcement_target_devices(
         &self,
+        unit_of_work: &mut UnitOfWork,
         announcement_id: i32,
         to_be_removed_device_ids: Vec<i32>,
         to_be_added_device_ids: Vec<i32>,
@@ -750,7 +1034,7 @@ impl AnnouncementRepositoryInterface for AnnouncementRepository {
             announcement_id,
             &to_be_removed_device_ids,
         )
-        .execute(&self._db)
+        .execute(unit_of_work.connection())
         .await?;
 
         sqlx::query!(
@@ -761,9 +1045,351 @@ impl AnnouncementRepositoryInterface for AnnouncementRepository {
             announcement_id,
             &to_be_added_device_ids,
         )
+        .execute(unit_of_work.connection())
+        .await?;
+
+        Ok(())
+    }
+
+    async fn update_dates(
+        &self,
+        unit_of_work: &mut UnitOfWork,
+        announcement_id: i32,
+        start_date: chrono::DateTime<chrono::Utc>,
+        end_date: chrono::DateTime<chrono::Utc>,
+    ) -> Result<(), sqlx::Error> {
+        let rows_affected = sqlx::query(
+            r#"
+            update "announcement"
+            set
+                "start_date" = $2,
+                "end_date" = $3
+            where "id" = $1
+            "#,
+        )
+        .bind(announcement_id)
+        .bind(start_date)
+        .bind(end_date)
+        .execute(unit_of_work.connection())
+        .await?
+        .rows_affected();
+
+        if rows_affected == 0 {
+            return Err(sqlx::Error::RowNotFound);
+        }
+
+        Ok(())
+    }
+
+    async fn delete(
+        &self,
+        unit_of_work: &mut UnitOfWork,
+        announcement_id: i32,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            delete from "announcement_target"
+            where "announcement_id" = $1
+            "#,
+        )
+        .bind(announcement_id)
+        .execute(unit_of_work.connection())
+        .await?;
+
+        sqlx::query(
+            r#"
+            delete from "device_announcement"
+            where "announcement_id" = $1
+            "#,
+        )
+        .bind(announcement_id)
+        .execute(unit_of_work.connection())
+        .await?;
+
+        let rows_affected = sqlx::query(
+            r#"
+            delete from "announcement"
+            where "id" = $1
+            "#,
+        )
+        .bind(announcement_id)
+        .execute(unit_of_work.connection())
+        .await?
+        .rows_affected();
+
+        if rows_affected == 0 {
+            return Err(sqlx::Error::RowNotFound);
+        }
+
+        Ok(())
+    }
+
+    async fn update_content(
+        &self,
+        unit_of_work: &mut UnitOfWork,
+        announcement_id: i32,
+        params: UpdateAnnouncementContentParams,
+    ) -> Result<(), sqlx::Error> {
+        let rows_affected = sqlx::query(
+            r#"
+            update "announcement"
+            set
+                "title" = coalesce($2, "title"),
+                "notes" = coalesce($3, "notes"),
+                "media_id" = coalesce($4, "media_id")
+            where "id" = $1
+            "#,
+        )
+        .bind(announcement_id)
+        .bind(params.title)
+        .bind(params.notes)
+        .bind(params.media_id)
+        .execute(unit_of_work.connection())
+        .await?
+        .rows_affected();
+
+        if rows_affected == 0 {
+            return Err(sqlx::Error::RowNotFound);
+        }
+
+        // The devices have to download the new content again
+        sqlx::query(
+            r#"
+            update "device_announcement"
+            set
+                "delivery_status" = 'pending',
+                "delivery_error" = null,
+                "delivery_updated_at" = null
+            where "announcement_id" = $1
+            "#,
+        )
+        .bind(announcement_id)
+        .execute(unit_of_work.connection())
+        .await?;
+
+        Ok(())
+    }
+
+    async fn update_delivery_status(
+        &self,
+        announcement_id: i32,
+        device_id: i32,
+        status: AnnouncementDeliveryStatus,
+        error: Option<String>,
+    ) -> Result<(), sqlx::Error> {
+        let rows_affected = sqlx::query(
+            r#"
+            update "device_announcement"
+            set
+                "delivery_status" = $3,
+                "delivery_error" = $4,
+                "delivery_updated_at" = now()
+            where "announcement_id" = $1 and "device_id" = $2
+            "#,
+        )
+        .bind(announcement_id)
+        .bind(device_id)
+        .bind(status)
+        .bind(error)
         .execute(&self._db)
+        .await?
+        .rows_affected();
+
+        if rows_affected == 0 {
+            return Err(sqlx::Error::RowNotFound);
+        }
+
+        Ok(())
+    }
+
+    async fn find_active_recurring_announcements(
+        &self,
+    ) -> Result<Vec<RecurringAnnouncement>, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            select
+                "announcement"."id" as "announcement_id",
+                "media"."media_type" as "announcement_media_type",
+                "media"."media_duration" as "announcement_media_duration",
+                "announcement"."recurrence" as "announcement_recurrence",
+                "announcement"."occurrence_active" as "announcement_occurrence_active"
+            from "announcement"
+            join "media" on "media"."id" = "announcement"."media_id"
+            where
+                "announcement"."status" = 'active' and
+                "announcement"."recurrence" is not null
+            "#,
+        )
+        .map(|row: PgRow| {
+            let recurrence: sqlx::types::Json<AnnouncementRecurrence> =
+                row.get("announcement_recurrence");
+
+            RecurringAnnouncement {
+                id: row.get("announcement_id"),
+                media_type: row.get("announcement_media_type"),
+                media_duration: row.get("announcement_media_duration"),
+                recurrence: recurrence.0,
+                occurrence_active: row.get("announcement_occurrence_active"),
+            }
+        })
+        .fetch_all(&self._db)
         .await?;
 
+        Ok(result)
+    }
+
+    async fn batch_update_occurrence_active(
+        &self,
+        unit_of_work: &mut UnitOfWork,
+        announcement_ids: Vec<i32>,
+        occurrence_active: bool,
+    ) -> Result<(), sqlx::Error> {
+        let rows_affected = sqlx::query(
+            r#"
+            update "announcement"
+            set "occurrence_active" = $2
+            where "id" = any($1)
+            "#,
+        )
+        .bind(&announcement_ids)
+        .bind(occurrence_active)
+        .execute(unit_of_work.connection())
+        .await?
+        .rows_affected();
+
+        if rows_affected == 0 {
+            return Err(sqlx::Error::RowNotFound);
+        }
+
         Ok(())
     }
+
+    /// Without any building or floor every device is returned
+    async fn find_device_ids_by_location(
+        &self,
+        building_ids: Vec<i32>,
+        floor_ids: Vec<i32>,
+    ) -> Result<Vec<i32>, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            select "device"."id" as "device_id"
+            from "device"
+            join "floor" on "floor"."id" = "device"."floor_id"
+            where
+                "device"."deleted_at" is null and
+                (
+                    (cardinality($1::int4[]) = 0 and cardinality($2::int4[]) = 0) or
+                    "floor"."building_id" = any($1) or
+                    "device"."floor_id" = any($2)
+                )
+            order by "device"."id"
+            "#,
+        )
+        .bind(&building_ids)
+        .bind(&floor_ids)
+        .map(|row: PgRow| row.get("device_id"))
+        .fetch_all(&self._db)
+        .await?;
+
+        Ok(result)
+    }
+
+    async fn find_playlist_announcements(
+        &self,
+        device_id: i32,
+    ) -> Result<Vec<PlaylistAnnouncement>, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            select
+                "announcement"."id" as "announcement_id",
+                "announcement"."title" as "announcement_title",
+                "media"."media_type" as "announcement_media_type",
+                "media"."media_duration" as "announcement_media_duration",
+                "announcement"."display_duration" as "announcement_display_duration",
+                "announcement"."priority" as "announcement_priority",
+                "announcement"."weight" as "announcement_weight",
+                "announcement"."is_emergency" as "announcement_is_emergency"
+            from "device_announcement"
+            join "announcement" on "announcement"."id" = "device_announcement"."announcement_id"
+            join "media" on "media"."id" = "announcement"."media_id"
+            where
+                "device_announcement"."device_id" = $1 and
+                "announcement"."status" = 'active' and
+                ("announcement"."recurrence" is null or "announcement"."occurrence_active")
+            order by "announcement"."id"
+            "#,
+        )
+        .bind(device_id)
+        .map(|row: PgRow| PlaylistAnnouncement {
+            id: row.get("announcement_id"),
+            title: row.get("announcement_title"),
+            media_type: row.get("announcement_media_type"),
+            media_duration: row.get("announcement_media_duration"),
+            display_duration: row.get("announcement_display_duration"),
+            priority: row.get("announcement_priority"),
+            weight: row.get("announcement_weight"),
+            is_emergency: row.get("announcement_is_emergency"),
+        })
+        .fetch_all(&self._db)
+        .await?;
+
+        Ok(result)
+    }
+
+    async fn find_manifest_announcements(
+        &self,
+        device_id: i32,
+    ) -> Result<Vec<ManifestAnnouncement>, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            select
+                "announcement"."id" as "announcement_id",
+                "announcement"."title" as "announcement_title",
+                "media"."path" as "announcement_media",
+                "media"."media_type" as "announcement_media_type",
+                "media"."media_duration" as "announcement_media_duration",
+                "media"."content_hash" as "announcement_content_hash",
+                "announcement"."display_duration" as "announcement_display_duration",
+                "announcement"."start_date" as "announcement_start_date",
+                "announcement"."end_date" as "announcement_end_date",
+                "announcement"."recurrence" as "announcement_recurrence",
+                "announcement"."priority" as "announcement_priority",
+                "announcement"."weight" as "announcement_weight",
+                "announcement"."is_emergency" as "announcement_is_emergency"
+            from "device_announcement"
+            join "announcement" on "announcement"."id" = "device_announcement"."announcement_id"
+            join "media" on "media"."id" = "announcement"."media_id"
+            where
+                "device_announcement"."device_id" = $1 and
+                "announcement"."status" = 'active' and
+                ("announcement"."recurrence" is null or "announcement"."occurrence_active")
+            order by "announcement"."id"
+            "#,
+        )
+        .bind(device_id)
+        .map(|row: PgRow| {
+            let recurrence: Option<sqlx::types::Json<AnnouncementRecurrence>> =
+                row.get("announcement_recurrence");
+
+            ManifestAnnouncement {
+                id: row.get("announcement_id"),
+                title: row.get("announcement_title"),
+                media: row.get("announcement_media"),
+                media_type: row.get("announcement_media_type"),
+                media_duration: row.get("announcement_media_duration"),
+                display_duration: row.get("announcement_display_duration"),
+                content_hash: row.get("announcement_content_hash"),
+                start_date: row.get("announcement_start_date"),
+                end_date: row.get("announcement_end_date"),
+                recurrence: recurrence.map(|recurrence| recurrence.0),
+                priority: row.get("announcement_priority"),
+                weight: row.get("announcement_weight"),
+                is_emergency: row.get("announcement_is_emergency"),
+            }
+        })
+        .fetch_all(&self._db)
+        .await?;
+
+        Ok(result)
+    }
 }
@@ -3,18 +3,26 @@ use std::sync::Arc;
 
 use crate::{
     cloud_storage,
-    database::{DatabaseError, PaginationResult},
+    database::{DatabaseError, PaginationResult, UnitOfWork, UnitOfWorkFactoryInterface},
     features::{
-        request::{CreateRequestParams, RequestActionType, RequestServiceInterface},
+        media::domain::MediaType,
+        request::{InsertRequestParams, RequestActionType, RequestRepositoryInterface},
         AnnouncementQueueInterface,
     },
 };
 
 use super::{
-    Announcement, AnnouncementDetail, AnnouncementMediaObject, AnnouncementRepositoryInterface,
-    AnnouncementStatus, CountAnnouncementParams, CreateAnnouncementError,
+    build_playlist, is_announcement_target_constraint, manifest_etag, Announcement, AnnouncementDeliveryStatus, AnnouncementDetail,
+    AnnouncementMediaObject, AnnouncementRecurrence, AnnouncementRepositoryInterface,
+    AnnouncementStatus, AnnouncementTargets, BroadcastEmergencyAnnouncementError,
+    CancelAnnouncementError,
+    ClearEmergencyAnnouncementError, CountAnnouncementParams, CreateAnnouncementError,
+    DeleteDraftAnnouncementError, DraftAccessError, SubmitDraftAnnouncementError,
+    UpdateAnnouncementContentParams, UpdateDraftAnnouncementError,
     FindListAnnouncementParams, GetAnnouncementDetailError, GetAnnouncementMediaPresignedURLError,
-    HandleScheduledAnnouncementsError, InsertAnnouncementParams, ListAnnouncementError,
+    DeviceManifest, DeviceManifestItem, GetDeviceManifestError, GetDevicePlaylistError,
+    HandleScheduledAnnouncementsError, InsertAnnouncementParams,
+    ListAnnouncementError, PlaylistItem, ReportAnnouncementDeliveryError,
 };
 
 pub struct ListAnnouncementParams {
@@ -24,6 +32,7 @@ pub struct ListAnnouncementParams {
     pub status: Option<AnnouncementStatus>,
     pub user_id: Option<i32>,
     pub device_id: Option<i32>,
+    pub is_emergency: Option<bool>,
     pub start_date: Option<chrono::DateTime<chrono::Utc>>,
     pub end_date: Option<chrono::DateTime<chrono::Utc>>,
     pub populate_media: Option<bool>,
@@ -36,7 +45,41 @@ pub struct CreateAnnouncementParams {
     pub end_date: chrono::DateTime<chrono::Utc>,
     pub notes: String,
     pub device_ids: Vec<i32>,
+    pub targets: AnnouncementTargets,
     pub user_id: i32,
+    pub recurrence: Option<AnnouncementRecurrence>,
+    pub priority: i32,
+    pub weight: i32,
+    /// Only applies to images, videos are played for the length of the media
+    pub display_duration: Option<f64>,
+    /// Drafts are saved without an approval request until they are submitted
+    pub is_draft: bool,
+}
+
+/// Fields left empty keep their current value, `device_ids` replaces every device of the draft
+pub struct UpdateDraftAnnouncementParams {
+    pub title: Option<String>,
+    pub media_id: Option<i32>,
+    pub notes: Option<String>,
+    pub start_date: Option<chrono::DateTime<chrono::Utc>>,
+    pub end_date: Option<chrono::DateTime<chrono::Utc>>,
+    pub device_ids: Option<Vec<i32>>,
+}
+
+/// Without any building or floor the emergency is broadcast to every device
+pub struct BroadcastEmergencyAnnouncementParams {
+    pub title: String,
+    pub media_id: i32,
+    pub notes: String,
+    pub targets: AnnouncementTargets,
+    pub user_id: i32,
+}
+
+pub struct ReportAnnouncementDeliveryParams {
+    pub announcement_id: i32,
+    pub device_id: i32,
+    pub status: AnnouncementDeliveryStatus,
+    pub error: Option<String>,
 }
 
 #[async_trait]
@@ -53,10 +96,51 @@ pub trait AnnouncementServiceInterface {
         &self,
         params: CreateAnnouncementParams,
     ) -> Result<(), CreateAnnouncementError>;
+    async fn update_draft_announcement(
+        &self,
+        announcement_id: i32,
+        user_id: i32,
+        params: UpdateDraftAnnouncementParams,
+    ) -> Result<(), UpdateDraftAnnouncementError>;
+    async fn delete_draft_announcement(
+        &self,
+        announcement_id: i32,
+        user_id: i32,
+    ) -> Result<(), DeleteDraftAnnouncementError>;
+    async fn submit_draft_announcement(
+        &self,
+        announcement_id: i32,
+        user_id: i32,
+    ) -> Result<(), SubmitDraftAnnouncementError>;
+    async fn cancel_announcement(
+        &self,
+        announcement_id: i32,
+        user_id: i32,
+    ) -> Result<(), CancelAnnouncementError>;
+    async fn broadcast_emergency_announcement(
+        &self,
+        params: BroadcastEmergencyAnnouncementParams,
+    ) -> Result<i32, BroadcastEmergencyAnnouncementError>;
+    async fn clear_emergency_announcement(
+        &self,
+        announcement_id: i32,
+    ) -> Result<(), ClearEmergencyAnnouncementError>;
     async fn get_announcement_media_presigned_url(
         &self,
         announcement_id: i32,
     ) -> Result<AnnouncementMediaObject, GetAnnouncementMediaPresignedURLError>;
+    async fn report_delivery_status(
+        &self,
+        params: ReportAnnouncementDeliveryParams,
+    ) -> Result<(), ReportAnnouncementDeliveryError>;
+    async fn get_device_playlist(
+        &self,
+        device_id: i32,
+    ) -> Result<Vec<PlaylistItem>, GetDevicePlaylistError>;
+    async fn get_device_manifest(
+        &self,
+        device_id: i32,
+    ) -> Result<DeviceManifest, GetDeviceManifestError>;
     async fn handle_waiting_for_approval_announcements(
         &self,
         now: chrono::DateTime<chrono::Utc>,
@@ -69,12 +153,17 @@ pub trait AnnouncementServiceInterface {
         &self,
         now: chrono::DateTime<chrono::Utc>,
     ) -> Result<(), HandleScheduledAnnouncementsError>;
+    async fn handle_recurring_announcements(
+        &self,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> Result<(), HandleScheduledAnnouncementsError>;
 }
 
 pub struct AnnouncementService {
     _announcement_repository: Arc<dyn AnnouncementRepositoryInterface + Send + Sync + 'static>,
     _announcement_queue: Arc<dyn AnnouncementQueueInterface + Send + Sync + 'static>,
-    _request_service: Arc<dyn RequestServiceInterface + Send + Sync + 'static>,
+    _request_repository: Arc<dyn RequestRepositoryInterface + Send + Sync + 'static>,
+    _unit_of_work: Arc<dyn UnitOfWorkFactoryInterface + Send + Sync + 'static>,
     _cloud_storage: cloud_storage::Client,
 }
 
@@ -82,16 +171,55 @@ impl AnnouncementService {
     pub fn new(
         _announcement_repository: Arc<dyn AnnouncementRepositoryInterface + Send + Sync + 'static>,
         _announcement_queue: Arc<dyn AnnouncementQueueInterface + Send + Sync + 'static>,
-        _request_service: Arc<dyn RequestServiceInterface + Send + Sync + 'static>,
+        _request_repository: Arc<dyn RequestRepositoryInterface + Send + Sync + 'static>,
+        _unit_of_work: Arc<dyn UnitOfWorkFactoryInterface + Send + Sync + 'static>,
         _cloud_storage: cloud_storage::Client,
     ) -> Self {
         AnnouncementService {
             _announcement_repository,
             _announcement_queue,
-            _request_service,
+            _request_repository,
+            _unit_of_work,
             _cloud_storage,
         }
     }
+
+    async fn find_owned_draft(
+        &self,
+        unit_of_work: &mut UnitOfWork,
+        announcement_id: i32,
+        user_id: i32,
+    ) -> Result<AnnouncementDetail, DraftAccessError> {
+        let announcement = match self
+            ._announcement_repository
+            .find_one_in_unit_of_work(unit_of_work, announcement_id)
+            .await
+        {
+            Ok(announcement) => announcement,
+            Err(e) => match e {
+                sqlx::Error::RowNotFound => {
+                    return Err(DraftAccessError::AnnouncementNotFound(
+                        "Announcement not found".into(),
+                    ))
+                }
+                _ => return Err(DraftAccessError::InternalServerError),
+            },
+        };
+
+        if announcement.user_id != user_id {
+            return Err(DraftAccessError::NotAnnouncementOwner(
+                "Only the author of the draft is allowed to change it".into(),
+            ));
+        }
+
+        if announcement.status != AnnouncementStatus::Draft {
+            return Err(DraftAccessError::NotADraft(
+                "The announcement has already been submitted".into(),
+            ));
+        }
+
+        Ok(announcement)
+    }
 }
 
 #[async_trait]
@@ -116,6 +244,9 @@ impl AnnouncementServiceInterface for AnnouncementService {
         if let Some(device_id) = params.device_id {
             repo_params = repo_params.device_id(device_id);
         }
+        if let Some(is_emergency) = params.is_emergency {
+            repo_params = repo_params.is_emergency(is_emergency);
+        }
         if let Some(start_date) = params.start_date {
             repo_params = repo_params.start_date_gte(start_date);
         }
@@ -186,18 +317,61 @@ impl AnnouncementServiceInterface for AnnouncementService {
         &self,
         params: CreateAnnouncementParams,
     ) -> Result<(), CreateAnnouncementError> {
-        // TODO: use db transaction if fail
+        let mut targeted_device_ids: Vec<i32> = vec![];
+        if !params.targets.is_empty() {
+            let ids = match self
+                ._announcement_repository
+                .find_device_ids_by_location(
+                    params.targets.building_ids.clone(),
+                    params.targets.floor_ids.clone(),
+                )
+                .await
+            {
+                Ok(ids) => ids,
+                Err(_) => return Err(CreateAnnouncementError::InternalServerError),
+            };
+
+            targeted_device_ids = ids
+                .into_iter()
+                .filter(|id| !params.device_ids.contains(id))
+                .collect();
+        }
+
+        let mut device_ids = params.device_ids.clone();
+        device_ids.extend(targeted_device_ids.iter());
+
+        if device_ids.len() == 0 {
+            return Err(CreateAnnouncementError::NoTargetDevices(
+                "There are no devices in the selected devices, buildings and floors".into(),
+            ));
+        }
+
+        let mut unit_of_work = match self._unit_of_work.begin().await {
+            Ok(unit_of_work) => unit_of_work,
+            Err(_) => return Err(CreateAnnouncementError::InternalServerError),
+        };
+
         let announcement_id = match self
             ._announcement_repository
-            .insert(InsertAnnouncementParams {
-                title: params.title.clone(),
-                notes: params.notes.clone(),
-                start_date: params.start_date,
-                end_date: params.end_date,
-                device_ids: params.device_ids,
-                user_id: params.user_id,
-                media_id: params.media_id,
-            })
+            .insert(
+                &mut unit_of_work,
+                InsertAnnouncementParams {
+                    title: params.title.clone(),
+                    notes: params.notes.clone(),
+                    start_date: params.start_date,
+                    end_date: params.end_date,
+                    device_ids,
+                    user_id: params.user_id,
+                    media_id: params.media_id,
+                    recurrence: params.recurrence,
+                    is_emergency: false,
+                    targets: params.targets,
+                    targeted_device_ids,
+                    priority: params.priority,
+                    weight: params.weight,
+                    display_duration: params.display_duration,
+                },
+            )
             .await
         {
             Ok(id) => id,
@@ -205,6 +379,13 @@ impl AnnouncementServiceInterface for AnnouncementService {
                 sqlx::Error::Database(db_error) => {
                     if let Some(code) = db_error.code() {
                         let code = code.to_string();
+                        if code == DatabaseError::ForeignKeyError.to_string()
+                            && is_announcement_target_constraint(db_error.constraint())
+                        {
+                            return Err(CreateAnnouncementError::TargetNotFound(
+                                "Building or floor not found".into(),
+                            ));
+                        }
                         if code == DatabaseError::ForeignKeyError.to_string() {
                             return Err(CreateAnnouncementError::UserNotFound(
                                 "User not found".into(),
@@ -217,17 +398,528 @@ impl AnnouncementServiceInterface for AnnouncementService {
             },
         };
 
+        if params.display_duration.is_some() {
+            let announcement = match self
+                ._announcement_repository
+                .find_one_in_unit_of_work(&mut unit_of_work, announcement_id)
+                .await
+            {
+                Ok(announcement) => announcement,
+                Err(_) => return Err(CreateAnnouncementError::InternalServerError),
+            };
+
+            if let MediaType::Video = announcement.media_type {
+                return Err(CreateAnnouncementError::DisplayDurationNotSupported(
+                    "Display duration can only be set for images".into(),
+                ));
+            }
+        }
+
+        if params.is_draft {
+            if let Err(_) = self
+                ._announcement_repository
+                .update_status(&mut unit_of_work, announcement_id, AnnouncementStatus::Draft)
+                .await
+            {
+                return Err(CreateAnnouncementError::InternalServerError);
+            }
+
+            if let Err(_) = unit_of_work.commit().await {
+                return Err(CreateAnnouncementError::InternalServerError);
+            }
+
+            return Ok(());
+        }
+
+        // The request is inserted within the same unit of work, an announcement is never left
+        // without the request that approves it
+        if let Err(e) = self
+            ._request_repository
+            .insert(
+                &mut unit_of_work,
+                InsertRequestParams::new(
+                    RequestActionType::Create,
+                    params.notes.clone(),
+                    announcement_id,
+                    params.user_id,
+                ),
+            )
+            .await
+        {
+            match e {
+                // No approval workflow applies to the announcement
+                sqlx::Error::RowNotFound => {
+                    return Err(CreateAnnouncementError::ApprovalWorkflowNotConfigured(
+                        "No approval workflow is configured for this announcement".into(),
+                    ))
+                }
+                _ => return Err(CreateAnnouncementError::InternalServerError),
+            }
+        }
+
+        if let Err(_) = unit_of_work.commit().await {
+            return Err(CreateAnnouncementError::InternalServerError);
+        }
+
+        Ok(())
+    }
+
+    async fn update_draft_announcement(
+        &self,
+        announcement_id: i32,
+        user_id: i32,
+        params: UpdateDraftAnnouncementParams,
+    ) -> Result<(), UpdateDraftAnnouncementError> {
+        if let Some(device_ids) = &params.device_ids {
+            if device_ids.len() == 0 {
+                return Err(UpdateDraftAnnouncementError::NoTargetDevices(
+                    "A draft needs at least one device".into(),
+                ));
+            }
+        }
+
+        let mut unit_of_work = match self._unit_of_work.begin().await {
+            Ok(unit_of_work) => unit_of_work,
+            Err(_) => return Err(UpdateDraftAnnouncementError::InternalServerError),
+        };
+
+        let announcement = self
+            .find_owned_draft(&mut unit_of_work, announcement_id, user_id)
+            .await?;
+
+        if let Err(e) = self
+            ._announcement_repository
+            .update_content(
+                &mut unit_of_work,
+                announcement_id,
+                UpdateAnnouncementContentParams {
+                    title: params.title,
+                    notes: params.notes,
+                    media_id: params.media_id,
+                },
+            )
+            .await
+        {
+            match e {
+                sqlx::Error::Database(db_error) => {
+                    if let Some(code) = db_error.code() {
+                        if code.to_string() == DatabaseError::ForeignKeyError.to_string() {
+                            return Err(UpdateDraftAnnouncementError::MediaNotFound(
+                                "Media not found".into(),
+                            ));
+                        }
+                    }
+                    return Err(UpdateDraftAnnouncementError::InternalServerError);
+                }
+                _ => return Err(UpdateDraftAnnouncementError::InternalServerError),
+            }
+        }
+
+        if params.start_date.is_some() || params.end_date.is_some() {
+            if let Err(_) = self
+                ._announcement_repository
+                .update_dates(
+                    &mut unit_of_work,
+                    announcement_id,
+                    params.start_date.unwrap_or(announcement.start_date),
+                    params.end_date.unwrap_or(announcement.end_date),
+                )
+                .await
+            {
+                return Err(UpdateDraftAnnouncementError::InternalServerError);
+            }
+        }
+
+        if let Some(device_ids) = params.device_ids {
+            let current_device_ids: Vec<i32> =
+                announcement.devices.iter().map(|device| device.id).collect();
+
+            let to_be_removed_device_ids = current_device_ids
+                .iter()
+                .filter(|id| !device_ids.contains(id))
+                .cloned()
+                .collect();
+            let to_be_added_device_ids = device_ids
+                .iter()
+                .filter(|id| !current_device_ids.contains(id))
+                .cloned()
+                .collect();
+
+            if let Err(e) = self
+                ._announcement_repository
+                .update_announcement_target_devices(
+                    &mut unit_of_work,
+                    announcement_id,
+                    to_be_removed_device_ids,
+                    to_be_added_device_ids,
+                )
+                .await
+            {
+                match e {
+                    sqlx::Error::Database(db_error) => {
+                        if let Some(code) = db_error.code() {
+                            if code.to_string() == DatabaseError::ForeignKeyError.to_string() {
+                                return Err(UpdateDraftAnnouncementError::DeviceNotFound(
+                                    "Device not found".into(),
+                                ));
+                            }
+                        }
+                        return Err(UpdateDraftAnnouncementError::InternalServerError);
+                    }
+                    _ => return Err(UpdateDraftAnnouncementError::InternalServerError),
+                }
+            }
+        }
+
+        if let Err(_) = unit_of_work.commit().await {
+            return Err(UpdateDraftAnnouncementError::InternalServerError);
+        }
+
+        Ok(())
+    }
+
+    async fn delete_draft_announcement(
+        &self,
+        announcement_id: i32,
+        user_id: i32,
+    ) -> Result<(), DeleteDraftAnnouncementError> {
+        let mut unit_of_work = match self._unit_of_work.begin().await {
+            Ok(unit_of_work) => unit_of_work,
+            Err(_) => return Err(DeleteDraftAnnouncementError::InternalServerError),
+        };
+
+        self.find_owned_draft(&mut unit_of_work, announcement_id, user_id)
+            .await?;
+
+        if let Err(_) = self
+            ._announcement_repository
+            .delete(&mut unit_of_work, announcement_id)
+            .await
+        {
+            return Err(DeleteDraftAnnouncementError::InternalServerError);
+        }
+
+        if let Err(_) = unit_of_work.commit().await {
+            return Err(DeleteDraftAnnouncementError::InternalServerError);
+        }
+
+        Ok(())
+    }
+
+    async fn submit_draft_announcement(
+        &self,
+        announcement_id: i32,
+        user_id: i32,
+    ) -> Result<(), SubmitDraftAnnouncementError> {
+        let mut unit_of_work = match self._unit_of_work.begin().await {
+            Ok(unit_of_work) => unit_of_work,
+            Err(_) => return Err(SubmitDraftAnnouncementError::InternalServerError),
+        };
+
+        let announcement = self
+            .find_owned_draft(&mut unit_of_work, announcement_id, user_id)
+            .await?;
+
+        if let Err(_) = self
+            ._announcement_repository
+            .update_status(
+                &mut unit_of_work,
+                announcement_id,
+                AnnouncementStatus::WaitingForApproval,
+            )
+            .await
+        {
+            return Err(SubmitDraftAnnouncementError::InternalServerError);
+        }
+
+        if let Err(e) = self
+            ._request_repository
+            .insert(
+                &mut unit_of_work,
+                InsertRequestParams::new(
+                    RequestActionType::Create,
+                    announcement.notes,
+                    announcement_id,
+                    user_id,
+                ),
+            )
+            .await
+        {
+            match e {
+                // No approval workflow applies to the announcement
+                sqlx::Error::RowNotFound => {
+                    return Err(SubmitDraftAnnouncementError::ApprovalWorkflowNotConfigured(
+                        "No approval workflow is configured for this announcement".into(),
+                    ))
+                }
+                _ => return Err(SubmitDraftAnnouncementError::InternalServerError),
+            }
+        }
+
+        if let Err(_) = unit_of_work.commit().await {
+            return Err(SubmitDraftAnnouncementError::InternalServerError);
+        }
+
+        Ok(())
+    }
+
+    async fn cancel_announcement(
+        &self,
+        announcement_id: i32,
+        user_id: i32,
+    ) -> Result<(), CancelAnnouncementError> {
+        let mut unit_of_work = match self._unit_of_work.begin().await {
+            Ok(unit_of_work) => unit_of_work,
+            Err(_) => return Err(CancelAnnouncementError::InternalServerError),
+        };
+
+        let announcement = match self
+            ._announcement_repository
+            .find_one_in_unit_of_work(&mut unit_of_work, announcement_id)
+            .await
+        {
+            Ok(announcement) => announcement,
+            Err(e) => match e {
+                sqlx::Error::RowNotFound => {
+                    return Err(CancelAnnouncementError::AnnouncementNotFound(
+                        "Announcement not found".into(),
+                    ))
+                }
+                _ => return Err(CancelAnnouncementError::InternalServerError),
+            },
+        };
+
+        if announcement.user_id != user_id {
+            return Err(CancelAnnouncementError::NotAnnouncementOwner(
+                "Only the author of the announcement is allowed to cancel it".into(),
+            ));
+        }
+
+        if announcement.status != AnnouncementStatus::WaitingForApproval
+            && announcement.status != AnnouncementStatus::WaitingForSync
+        {
+            return Err(CancelAnnouncementError::NotCancelable(
+                "Only announcements that are not active yet can be canceled".into(),
+            ));
+        }
+
         if let Err(_) = self
-            ._request_service
-            .create_request(CreateRequestParams::new(
-                RequestActionType::Create,
-                params.notes.clone(),
+            ._announcement_repository
+            .update_status(
+                &mut unit_of_work,
                 announcement_id,
-                params.user_id,
-            ))
+                AnnouncementStatus::Canceled,
+            )
             .await
         {
-            return Err(CreateAnnouncementError::InternalServerError);
+            return Err(CancelAnnouncementError::InternalServerError);
+        }
+
+        if let Err(_) = self
+            ._request_repository
+            .withdraw_pending_requests_from_announcement_id(&mut unit_of_work, announcement_id)
+            .await
+        {
+            return Err(CancelAnnouncementError::InternalServerError);
+        }
+
+        if let Err(_) = unit_of_work.commit().await {
+            return Err(CancelAnnouncementError::InternalServerError);
+        }
+
+        Ok(())
+    }
+
+    async fn broadcast_emergency_announcement(
+        &self,
+        params: BroadcastEmergencyAnnouncementParams,
+    ) -> Result<i32, BroadcastEmergencyAnnouncementError> {
+        let device_ids = match self
+            ._announcement_repository
+            .find_device_ids_by_location(
+                params.targets.building_ids.clone(),
+                params.targets.floor_ids.clone(),
+            )
+            .await
+        {
+            Ok(ids) => ids,
+            Err(_) => return Err(BroadcastEmergencyAnnouncementError::InternalServerError),
+        };
+
+        if device_ids.len() == 0 {
+            return Err(BroadcastEmergencyAnnouncementError::NoTargetDevices(
+                "There are no devices in the selected buildings and floors".into(),
+            ));
+        }
+
+        let mut unit_of_work = match self._unit_of_work.begin().await {
+            Ok(unit_of_work) => unit_of_work,
+            Err(_) => return Err(BroadcastEmergencyAnnouncementError::InternalServerError),
+        };
+
+        // An emergency lasts until it is cleared, the end date is moved to that moment
+        let now = chrono::Utc::now();
+
+        let announcement_id = match self
+            ._announcement_repository
+            .insert(
+                &mut unit_of_work,
+                InsertAnnouncementParams {
+                    title: params.title,
+                    notes: params.notes,
+                    start_date: now,
+                    end_date: now,
+                    device_ids: device_ids.clone(),
+                    user_id: params.user_id,
+                    media_id: params.media_id,
+                    recurrence: None,
+                    is_emergency: true,
+                    // Devices installed in the targeted buildings and floors during the emergency
+                    // receive it as well, an emergency for every device has no targets
+                    targeted_device_ids: match params.targets.is_empty() {
+                        true => vec![],
+                        false => device_ids.clone(),
+                    },
+                    targets: params.targets,
+                    priority: 0,
+                    weight: 1,
+                    display_duration: None,
+                },
+            )
+            .await
+        {
+            Ok(id) => id,
+            Err(e) => match e {
+                sqlx::Error::Database(db_error) => {
+                    if let Some(code) = db_error.code() {
+                        let code = code.to_string();
+                        if code == DatabaseError::ForeignKeyError.to_string()
+                            && is_announcement_target_constraint(db_error.constraint())
+                        {
+                            return Err(BroadcastEmergencyAnnouncementError::TargetNotFound(
+                                "Building or floor not found".into(),
+                            ));
+                        }
+                        if code == DatabaseError::ForeignKeyError.to_string() {
+                            return Err(BroadcastEmergencyAnnouncementError::MediaNotFound(
+                                "Media not found".into(),
+                            ));
+                        }
+                    }
+                    return Err(BroadcastEmergencyAnnouncementError::InternalServerError);
+                }
+                _ => return Err(BroadcastEmergencyAnnouncementError::InternalServerError),
+            },
+        };
+
+        let announcement = match self
+            ._announcement_repository
+            .find_one_in_unit_of_work(&mut unit_of_work, announcement_id)
+            .await
+        {
+            Ok(announcement) => announcement,
+            Err(_) => return Err(BroadcastEmergencyAnnouncementError::InternalServerError),
+        };
+
+        // No request is created, the emergency skips the approval flow and goes out right away
+        if let Err(_) = self
+            ._announcement_repository
+            .update_status(&mut unit_of_work, announcement_id, AnnouncementStatus::Active)
+            .await
+        {
+            return Err(BroadcastEmergencyAnnouncementError::InternalServerError);
+        }
+
+        if let Err(_) = self
+            ._announcement_queue
+            .emergency(
+                &mut unit_of_work,
+                device_ids,
+                announcement_id,
+                announcement.media_type.to_string(),
+                announcement.media_duration,
+            )
+            .await
+        {
+            return Err(BroadcastEmergencyAnnouncementError::InternalServerError);
+        }
+
+        if let Err(_) = unit_of_work.commit().await {
+            return Err(BroadcastEmergencyAnnouncementError::InternalServerError);
+        }
+
+        Ok(announcement_id)
+    }
+
+    async fn clear_emergency_announcement(
+        &self,
+        announcement_id: i32,
+    ) -> Result<(), ClearEmergencyAnnouncementError> {
+        let announcement = match self
+            ._announcement_repository
+            .find_one(announcement_id)
+            .await
+        {
+            Ok(announcement) => announcement,
+            Err(e) => match e {
+                sqlx::Error::RowNotFound => {
+                    return Err(ClearEmergencyAnnouncementError::AnnouncementNotFound(
+                        "Announcement not found".into(),
+                    ))
+                }
+                _ => return Err(ClearEmergencyAnnouncementError::InternalServerError),
+            },
+        };
+
+        if !announcement.is_emergency {
+            return Err(ClearEmergencyAnnouncementError::NotAnEmergency(
+                "Announcement is not an emergency".into(),
+            ));
+        }
+
+        if announcement.status != AnnouncementStatus::Active {
+            return Err(ClearEmergencyAnnouncementError::EmergencyAlreadyCleared(
+                "Emergency has already been cleared".into(),
+            ));
+        }
+
+        let device_ids: Vec<i32> = announcement
+            .devices
+            .iter()
+            .map(|device| device.id)
+            .collect();
+
+        let mut unit_of_work = match self._unit_of_work.begin().await {
+            Ok(unit_of_work) => unit_of_work,
+            Err(_) => return Err(ClearEmergencyAnnouncementError::InternalServerError),
+        };
+
+        if let Err(_) = self
+            ._announcement_queue
+            .clear_emergency(&mut unit_of_work, device_ids, announcement_id)
+            .await
+        {
+            return Err(ClearEmergencyAnnouncementError::InternalServerError);
+        }
+
+        if let Err(_) = self
+            ._announcement_repository
+            .update_status(&mut unit_of_work, announcement_id, AnnouncementStatus::Done)
+            .await
+        {
+            return Err(ClearEmergencyAnnouncementError::InternalServerError);
+        }
+
+        if let Err(_) = self
+            ._announcement_repository
+            .extend_end_date(&mut unit_of_work, announcement_id, chrono::Utc::now())
+            .await
+        {
+            return Err(ClearEmergencyAnnouncementError::InternalServerError);
+        }
+
+        if let Err(_) = unit_of_work.commit().await {
+            return Err(ClearEmergencyAnnouncementError::InternalServerError);
         }
 
         Ok(())
@@ -269,6 +961,102 @@ impl AnnouncementServiceInterface for AnnouncementService {
         Ok(AnnouncementMediaObject { filename, media })
     }
 
+    async fn report_delivery_status(
+        &self,
+        params: ReportAnnouncementDeliveryParams,
+    ) -> Result<(), ReportAnnouncementDeliveryError> {
+        if params.status == AnnouncementDeliveryStatus::Pending {
+            return Err(ReportAnnouncementDeliveryError::InvalidDeliveryStatus(
+                "Pending is not a status that can be reported by a device".into(),
+            ));
+        }
+
+        // The error is only kept for failed deliveries
+        let mut error = None;
+        if params.status == AnnouncementDeliveryStatus::Failed {
+            error = params.error;
+        }
+
+        if let Err(e) = self
+            ._announcement_repository
+            .update_delivery_status(
+                params.announcement_id,
+                params.device_id,
+                params.status,
+                error,
+            )
+            .await
+        {
+            match e {
+                sqlx::Error::RowNotFound => {
+                    return Err(ReportAnnouncementDeliveryError::AnnouncementNotFound(
+                        "Announcement not found for the device".into(),
+                    ))
+                }
+                _ => return Err(ReportAnnouncementDeliveryError::InternalServerError),
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn get_device_playlist(
+        &self,
+        device_id: i32,
+    ) -> Result<Vec<PlaylistItem>, GetDevicePlaylistError> {
+        let announcements = match self
+            ._announcement_repository
+            .find_playlist_announcements(device_id)
+            .await
+        {
+            Ok(announcements) => announcements,
+            Err(_) => return Err(GetDevicePlaylistError::InternalServerError),
+        };
+
+        Ok(build_playlist(announcements))
+    }
+
+    async fn get_device_manifest(
+        &self,
+        device_id: i32,
+    ) -> Result<DeviceManifest, GetDeviceManifestError> {
+        let announcements = match self
+            ._announcement_repository
+            .find_manifest_announcements(device_id)
+            .await
+        {
+            Ok(announcements) => announcements,
+            Err(_) => return Err(GetDeviceManifestError::InternalServerError),
+        };
+
+        let now = chrono::Utc::now();
+        let etag = manifest_etag(&announcements, now);
+
+        let mut items = vec![];
+        for announcement in announcements {
+            let media_url = match self
+                ._cloud_storage
+                .get_object(announcement.media.clone())
+                .await
+            {
+                Ok(url) => url,
+                Err(_) => return Err(GetDeviceManifestError::InternalServerError),
+            };
+
+            items.push(DeviceManifestItem {
+                announcement,
+                media_url,
+            });
+        }
+
+        Ok(DeviceManifest {
+            etag,
+            items,
+            media_urls_expire_at: now
+                + chrono::Duration::seconds(cloud_storage::PRESIGNED_URL_EXPIRY_SECS as i64),
+        })
+    }
+
     async fn handle_waiting_for_approval_announcements(
         &self,
         now: chrono::DateTime<chrono::Utc>,
@@ -288,22 +1076,35 @@ impl AnnouncementServiceInterface for AnnouncementService {
             return Ok(());
         }
 
+        let mut unit_of_work = match self._unit_of_work.begin().await {
+            Ok(unit_of_work) => unit_of_work,
+            Err(_) => return Err(HandleScheduledAnnouncementsError::InternalServerError),
+        };
+
         if let Err(_) = self
             ._announcement_repository
-            .batch_update_status(announcement_ids.clone(), AnnouncementStatus::Rejected)
+            .batch_update_status(
+                &mut unit_of_work,
+                announcement_ids.clone(),
+                AnnouncementStatus::Rejected,
+            )
             .await
         {
             return Err(HandleScheduledAnnouncementsError::InternalServerError);
         }
 
         if let Err(_) = self
-            ._request_service
-            .batch_reject_requests_from_announcement_ids(announcement_ids.clone())
+            ._request_repository
+            .batch_reject_requests_from_announcement_ids(&mut unit_of_work, announcement_ids)
             .await
         {
             return Err(HandleScheduledAnnouncementsError::InternalServerError);
         }
 
+        if let Err(_) = unit_of_work.commit().await {
+            return Err(HandleScheduledAnnouncementsError::InternalServerError);
+        }
+
         Ok(())
     }
 
@@ -316,7 +1117,7 @@ impl AnnouncementServiceInterface for AnnouncementService {
             .count(
                 CountAnnouncementParams::default()
                     .status(AnnouncementStatus::WaitingForSync)
-                    .start_date_gte(now),
+                    .start_date_lte(now),
             )
             .await
         {
@@ -336,7 +1137,7 @@ impl AnnouncementServiceInterface for AnnouncementService {
                 FindListAnnouncementParams::default()
                     .limit(count)
                     .status(AnnouncementStatus::WaitingForSync)
-                    .start_date_gte(now),
+                    .start_date_lte(now),
             )
             .await
         {
@@ -344,9 +1145,17 @@ impl AnnouncementServiceInterface for AnnouncementService {
             Err(_) => return Err(HandleScheduledAnnouncementsError::InternalServerError),
         };
 
+        let announcement_ids: Vec<i32> = announcements
+            .contents
+            .iter()
+            .map(|announcement| announcement.id)
+            .collect();
+
+        // Recurring announcements are pushed to the devices by the recurrence handler once an occurrence starts
         let announcement_data: Vec<(i32, String, Option<f64>)> = announcements
             .contents
             .into_iter()
+            .filter(|announcement| announcement.recurrence.is_none())
             .map(|announcement| {
                 (
                     announcement.id,
@@ -356,8 +1165,6 @@ impl AnnouncementServiceInterface for AnnouncementService {
             })
             .collect();
 
-        let announcement_ids: Vec<i32> = announcement_data.iter().map(|data| data.0).collect();
-
         let announcement_device_map = match self
             ._announcement_repository
             .find_announcement_device_map(announcement_ids.clone())
@@ -367,6 +1174,11 @@ impl AnnouncementServiceInterface for AnnouncementService {
             Err(_) => return Err(HandleScheduledAnnouncementsError::InternalServerError),
         };
 
+        let mut unit_of_work = match self._unit_of_work.begin().await {
+            Ok(unit_of_work) => unit_of_work,
+            Err(_) => return Err(HandleScheduledAnnouncementsError::InternalServerError),
+        };
+
         for (id, media_type, media_duration) in &announcement_data {
             let device_ids = match announcement_device_map.get(id) {
                 Some(ids) => ids,
@@ -376,10 +1188,12 @@ impl AnnouncementServiceInterface for AnnouncementService {
             if let Err(_) = self
                 ._announcement_queue
                 .create(
+                    &mut unit_of_work,
                     device_ids.clone(),
                     *id,
                     media_type.to_string(),
                     *media_duration,
+                    None,
                 )
                 .await
             {
@@ -389,12 +1203,20 @@ impl AnnouncementServiceInterface for AnnouncementService {
 
         if let Err(_) = self
             ._announcement_repository
-            .batch_update_status(announcement_ids.clone(), AnnouncementStatus::Active)
+            .batch_update_status(
+                &mut unit_of_work,
+                announcement_ids,
+                AnnouncementStatus::Active,
+            )
             .await
         {
             return Err(HandleScheduledAnnouncementsError::InternalServerError);
         }
 
+        if let Err(_) = unit_of_work.commit().await {
+            return Err(HandleScheduledAnnouncementsError::InternalServerError);
+        }
+
         Ok(())
     }
 
@@ -407,6 +1229,7 @@ impl AnnouncementServiceInterface for AnnouncementService {
             .count(
                 CountAnnouncementParams::default()
                     .status(AnnouncementStatus::Active)
+                    .is_emergency(false)
                     .end_date_lte(now),
             )
             .await
@@ -427,6 +1250,7 @@ impl AnnouncementServiceInterface for AnnouncementService {
                 FindListAnnouncementParams::default()
                     .limit(count)
                     .status(AnnouncementStatus::Active)
+                    .is_emergency(false)
                     .end_date_lte(now),
             )
             .await
@@ -450,6 +1274,11 @@ impl AnnouncementServiceInterface for AnnouncementService {
             Err(_) => return Err(HandleScheduledAnnouncementsError::InternalServerError),
         };
 
+        let mut unit_of_work = match self._unit_of_work.begin().await {
+            Ok(unit_of_work) => unit_of_work,
+            Err(_) => return Err(HandleScheduledAnnouncementsError::InternalServerError),
+        };
+
         for id in &announcement_ids {
             let device_ids = match announcement_device_map.get(id) {
                 Some(ids) => ids,
@@ -458,7 +1287,7 @@ impl AnnouncementServiceInterface for AnnouncementService {
 
             if let Err(_) = self
                 ._announcement_queue
-                .delete(device_ids.clone(), *id)
+                .delete(&mut unit_of_work, device_ids.clone(), *id)
                 .await
             {
                 return Err(HandleScheduledAnnouncementsError::InternalServerError);
@@ -467,20 +1296,144 @@ impl AnnouncementServiceInterface for AnnouncementService {
 
         if let Err(_) = self
             ._announcement_repository
-            .batch_update_status(announcement_ids.clone(), AnnouncementStatus::Done)
+            .batch_update_status(
+                &mut unit_of_work,
+                announcement_ids.clone(),
+                AnnouncementStatus::Done,
+            )
             .await
         {
             return Err(HandleScheduledAnnouncementsError::InternalServerError);
         }
 
         if let Err(_) = self
-            ._request_service
-            .batch_reject_requests_from_announcement_ids(announcement_ids)
+            ._request_repository
+            .batch_reject_requests_from_announcement_ids(&mut unit_of_work, announcement_ids)
             .await
         {
             return Err(HandleScheduledAnnouncementsError::InternalServerError);
         }
 
+        if let Err(_) = unit_of_work.commit().await {
+            return Err(HandleScheduledAnnouncementsError::InternalServerError);
+        }
+
+        Ok(())
+    }
+
+    async fn handle_recurring_announcements(
+        &self,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> Result<(), HandleScheduledAnnouncementsError> {
+        let announcements = match self
+            ._announcement_repository
+            .find_active_recurring_announcements()
+            .await
+        {
+            Ok(data) => data,
+            Err(_) => return Err(HandleScheduledAnnouncementsError::InternalServerError),
+        };
+
+        let (starting, ending): (Vec<_>, Vec<_>) = announcements
+            .into_iter()
+            .filter(|announcement| {
+                announcement.recurrence.is_occurring_at(now) != announcement.occurrence_active
+            })
+            .partition(|announcement| !announcement.occurrence_active);
+
+        if starting.len() == 0 && ending.len() == 0 {
+            return Ok(());
+        }
+
+        let announcement_ids: Vec<i32> = starting
+            .iter()
+            .chain(ending.iter())
+            .map(|announcement| announcement.id)
+            .collect();
+
+        let announcement_device_map = match self
+            ._announcement_repository
+            .find_announcement_device_map(announcement_ids)
+            .await
+        {
+            Ok(map) => map,
+            Err(_) => return Err(HandleScheduledAnnouncementsError::InternalServerError),
+        };
+
+        let mut unit_of_work = match self._unit_of_work.begin().await {
+            Ok(unit_of_work) => unit_of_work,
+            Err(_) => return Err(HandleScheduledAnnouncementsError::InternalServerError),
+        };
+
+        for announcement in &starting {
+            let device_ids = match announcement_device_map.get(&announcement.id) {
+                Some(ids) => ids,
+                None => return Err(HandleScheduledAnnouncementsError::InternalServerError),
+            };
+
+            if let Err(_) = self
+                ._announcement_queue
+                .create(
+                    &mut unit_of_work,
+                    device_ids.clone(),
+                    announcement.id,
+                    announcement.media_type.to_string(),
+                    announcement.media_duration,
+                    Some(announcement.recurrence.clone()),
+                )
+                .await
+            {
+                return Err(HandleScheduledAnnouncementsError::InternalServerError);
+            }
+        }
+
+        for announcement in &ending {
+            let device_ids = match announcement_device_map.get(&announcement.id) {
+                Some(ids) => ids,
+                None => return Err(HandleScheduledAnnouncementsError::InternalServerError),
+            };
+
+            if let Err(_) = self
+                ._announcement_queue
+                .delete(&mut unit_of_work, device_ids.clone(), announcement.id)
+                .await
+            {
+                return Err(HandleScheduledAnnouncementsError::InternalServerError);
+            }
+        }
+
+        if starting.len() > 0 {
+            if let Err(_) = self
+                ._announcement_repository
+                .batch_update_occurrence_active(
+                    &mut unit_of_work,
+                    starting.iter().map(|announcement| announcement.id).collect(),
+                    true,
+                )
+                .await
+            {
+                return Err(HandleScheduledAnnouncementsError::InternalServerError);
+            }
+        }
+
+        if ending.len() > 0 {
+            if let Err(_) = self
+                ._announcement_repository
+                .batch_update_occurrence_active(
+                    &mut unit_of_work,
+                    ending.iter().map(|announcement| announcement.id).collect(),
+                    false,
+                )
+                .await
+            {
+                return Err(HandleScheduledAnnouncementsError::InternalServerError);
+            }
+        }
+
+        if let Err(_) = unit_of_work.commit().await {
+            return Err(HandleScheduledAnnouncementsError::InternalServerError);
+        }
+
         Ok(())
     }
 }
@@ -2,6 +2,11 @@ use core::fmt;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use crate::features::{
+    announcement::{AnnouncementRecurrence, AnnouncementStatus},
+    media::domain::MediaType,
+};
+
 #[derive(Debug)]
 pub struct Device {
     pub id: i32,
@@ -39,6 +44,27 @@ pub struct DeviceDetailLocation {
     pub floor_name: String,
 }
 
+/// An announcement attached to a device because the device is located in one of its
+/// targeted buildings or floors
+#[derive(Debug)]
+pub struct TargetedAnnouncement {
+    pub id: i32,
+    pub status: AnnouncementStatus,
+    pub is_emergency: bool,
+    pub media_type: MediaType,
+    pub media_duration: Option<f64>,
+    pub recurrence: Option<AnnouncementRecurrence>,
+    pub occurrence_active: bool,
+}
+
+impl TargetedAnnouncement {
+    /// Whether the announcement is currently shown by the devices it is attached to
+    pub fn is_displayed(&self) -> bool {
+        self.status == AnnouncementStatus::Active
+            && (self.recurrence.is_none() || self.occurrence_active)
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct DeviceAuthCache {
@@ -319,3 +345,11 @@ pub enum SynchronizeDeviceStatusError {
     RedisError(#[from] redis::RedisError),
 
 }
+
+#[derive(Debug, Error)]
+pub enum SyncTargetedAnnouncementsError {
+    #[error("An error occurred with the request to the database")]
+    DatabaseError(#[from] sqlx::Error),
+    #[error("An error occurred while pushing to the device queue")]
+    QueueError,
+}
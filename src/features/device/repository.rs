@@ -4,17 +4,33 @@ use deadpool_redis::redis::{cmd, RedisError};
 use sqlx::{postgres::PgRow, Pool, Postgres, Row};
 
 use crate::{
-    database::PaginationResult,
-    features::device_status::definition::{
-        DeviceStatus, DEVICE_STATUS_REDIS_KEY, TIMEOUT_DURATION_SECS,
+    database::{PaginationResult, UnitOfWork},
+    features::{
+        announcement::AnnouncementRecurrence,
+        device_status::definition::{DeviceStatus, DEVICE_STATUS_REDIS_KEY, TIMEOUT_DURATION_SECS},
     },
 };
 
 use super::{
     CountDeviceParams, Device, DeviceAuthCache, DeviceDetail, DeviceDetailLocation,
-    ListDeviceParams,
+    ListDeviceParams, TargetedAnnouncement,
 };
 
+fn map_targeted_announcement(row: PgRow) -> TargetedAnnouncement {
+    let recurrence: Option<sqlx::types::Json<AnnouncementRecurrence>> =
+        row.get("announcement_recurrence");
+
+    TargetedAnnouncement {
+        id: row.get("announcement_id"),
+        status: row.get("announcement_status"),
+        is_emergency: row.get("announcement_is_emergency"),
+        media_type: row.get("announcement_media_type"),
+        media_duration: row.get("announcement_media_duration"),
+        recurrence: recurrence.map(|recurrence| recurrence.0),
+        occurrence_active: row.get("announcement_occurrence_active"),
+    }
+}
+
 pub struct InsertDeviceParams {
     pub name: String,
     pub description: String,
@@ -49,14 +65,49 @@ pub trait DeviceRepositoryInterface {
         &self,
         access_key_id: String,
     ) -> Result<DeviceDetail, sqlx::Error>;
-    async fn insert(&self, params: InsertDeviceParams) -> Result<i32, sqlx::Error>;
-    async fn update(&self, device_id: i32, params: UpdateDeviceParams) -> Result<(), sqlx::Error>;
+    async fn insert(
+        &self,
+        unit_of_work: &mut UnitOfWork,
+        params: InsertDeviceParams,
+    ) -> Result<i32, sqlx::Error>;
+    async fn update(
+        &self,
+        unit_of_work: &mut UnitOfWork,
+        device_id: i32,
+        params: UpdateDeviceParams,
+    ) -> Result<(), sqlx::Error>;
     async fn delete(&self, device_id: i32) -> Result<(), sqlx::Error>;
     async fn exists(&self, device_ids: &Vec<i32>) -> Result<bool, sqlx::Error>;
-    async fn find_announcement_ids_in_device(
+    async fn find_displayed_announcement_ids_in_device(
         &self,
         device_id: i32,
     ) -> Result<Vec<i32>, sqlx::Error>;
+    async fn find_active_emergency_announcement_id_in_device(
+        &self,
+        device_id: i32,
+    ) -> Result<Option<i32>, sqlx::Error>;
+    async fn find_matching_announcements(
+        &self,
+        unit_of_work: &mut UnitOfWork,
+        device_id: i32,
+    ) -> Result<Vec<TargetedAnnouncement>, sqlx::Error>;
+    async fn find_unmatched_announcements(
+        &self,
+        unit_of_work: &mut UnitOfWork,
+        device_id: i32,
+    ) -> Result<Vec<TargetedAnnouncement>, sqlx::Error>;
+    async fn attach_announcements(
+        &self,
+        unit_of_work: &mut UnitOfWork,
+        device_id: i32,
+        announcement_ids: Vec<i32>,
+    ) -> Result<(), sqlx::Error>;
+    async fn detach_announcements(
+        &self,
+        unit_of_work: &mut UnitOfWork,
+        device_id: i32,
+        announcement_ids: Vec<i32>,
+    ) -> Result<(), sqlx::Error>;
     async fn update_device_link(&self, device_id: i32, link: bool) -> Result<(), sqlx::Error>;
     async fn update_camera_enabled(
         &self,
@@ -356,7 +407,11 @@ impl DeviceRepositoryInterface for DeviceRepository {
         Ok(result)
     }
 
-    async fn insert(&self, params: InsertDeviceParams) -> Result<i32, sqlx::Error> {
+    async fn insert(
+        &self,
+        unit_of_work: &mut UnitOfWork,
+        params: InsertDeviceParams,
+    ) -> Result<i32, sqlx::Error> {
         let result = sqlx::query!(
             r#"
             insert into "device" ("name", "description", "floor_id", "access_key_id", "secret_access_key", "secret_access_key_salt")
@@ -370,13 +425,18 @@ impl DeviceRepositoryInterface for DeviceRepository {
             params.secret_access_key.as_bytes(),
             params.secret_access_key_salt,
         )
-        .fetch_one(&self._db)
+        .fetch_one(unit_of_work.connection())
         .await?;
 
         Ok(result.id)
     }
 
-    async fn update(&self, device_id: i32, params: UpdateDeviceParams) -> Result<(), sqlx::Error> {
+    async fn update(
+        &self,
+        unit_of_work: &mut UnitOfWork,
+        device_id: i32,
+        params: UpdateDeviceParams,
+    ) -> Result<(), sqlx::Error> {
         let rows_affected = sqlx::query!(
             r#"
             update "device"
@@ -391,7 +451,7 @@ impl DeviceRepositoryInterface for DeviceRepository {
             params.description,
             params.floor_id,
         )
-        .execute(&self._db)
+        .execute(unit_of_work.connection())
         .await?
         .rows_affected();
 
@@ -437,21 +497,186 @@ impl DeviceRepositoryInterface for DeviceRepository {
         Ok(result.count.unwrap() == device_ids.len() as i32)
     }
 
-    async fn find_announcement_ids_in_device(
+    async fn find_displayed_announcement_ids_in_device(
         &self,
         device_id: i32,
     ) -> Result<Vec<i32>, sqlx::Error> {
-        let result = sqlx::query!(
+        // Same rule as `TargetedAnnouncement::is_displayed`, recurring announcements outside of
+        // their occurrence stay attached to the device but are not queued
+        let result = sqlx::query(
             r#"
-            select "announcement_id" from "device_announcement"
-            where "device_id" = $1
+            select "device_announcement"."announcement_id"
+            from "device_announcement"
+            join "announcement" on "announcement"."id" = "device_announcement"."announcement_id"
+            where
+                "device_announcement"."device_id" = $1 and
+                "announcement"."status" = 'active' and
+                ("announcement"."recurrence" is null or "announcement"."occurrence_active")
             "#,
-            device_id,
         )
+        .bind(device_id)
+        .map(|row: PgRow| row.get("announcement_id"))
         .fetch_all(&self._db)
         .await?;
 
-        Ok(result.into_iter().map(|row| row.announcement_id).collect())
+        Ok(result)
+    }
+
+    async fn find_active_emergency_announcement_id_in_device(
+        &self,
+        device_id: i32,
+    ) -> Result<Option<i32>, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            select "announcement"."id" as "announcement_id"
+            from "announcement"
+            join "device_announcement" on "device_announcement"."announcement_id" = "announcement"."id"
+            where
+                "device_announcement"."device_id" = $1 and
+                "announcement"."is_emergency" and
+                "announcement"."status" = 'active'
+            order by "announcement"."id" desc
+            limit 1
+            "#,
+        )
+        .bind(device_id)
+        .map(|row: PgRow| row.get("announcement_id"))
+        .fetch_optional(&self._db)
+        .await?;
+
+        Ok(result)
+    }
+
+    async fn find_matching_announcements(
+        &self,
+        unit_of_work: &mut UnitOfWork,
+        device_id: i32,
+    ) -> Result<Vec<TargetedAnnouncement>, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            select
+                "announcement"."id" as "announcement_id",
+                "announcement"."status" as "announcement_status",
+                "announcement"."is_emergency" as "announcement_is_emergency",
+                "media"."media_type" as "announcement_media_type",
+                "media"."media_duration" as "announcement_media_duration",
+                "announcement"."recurrence" as "announcement_recurrence",
+                "announcement"."occurrence_active" as "announcement_occurrence_active"
+            from "announcement"
+            join "media" on "media"."id" = "announcement"."media_id"
+            join "device" on "device"."id" = $1
+            join "floor" on "floor"."id" = "device"."floor_id"
+            where
+                "announcement"."status" in ('draft', 'waiting_for_approval', 'waiting_for_sync', 'active') and
+                exists (
+                    select 1 from "announcement_target"
+                    where
+                        "announcement_target"."announcement_id" = "announcement"."id" and
+                        (
+                            "announcement_target"."floor_id" = "floor"."id" or
+                            "announcement_target"."building_id" = "floor"."building_id"
+                        )
+                ) and
+                not exists (
+                    select 1 from "device_announcement"
+                    where
+                        "device_announcement"."announcement_id" = "announcement"."id" and
+                        "device_announcement"."device_id" = "device"."id"
+                )
+            order by "announcement"."id"
+            "#,
+        )
+        .bind(device_id)
+        .map(map_targeted_announcement)
+        .fetch_all(unit_of_work.connection())
+        .await?;
+
+        Ok(result)
+    }
+
+    async fn find_unmatched_announcements(
+        &self,
+        unit_of_work: &mut UnitOfWork,
+        device_id: i32,
+    ) -> Result<Vec<TargetedAnnouncement>, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            select
+                "announcement"."id" as "announcement_id",
+                "announcement"."status" as "announcement_status",
+                "announcement"."is_emergency" as "announcement_is_emergency",
+                "media"."media_type" as "announcement_media_type",
+                "media"."media_duration" as "announcement_media_duration",
+                "announcement"."recurrence" as "announcement_recurrence",
+                "announcement"."occurrence_active" as "announcement_occurrence_active"
+            from "device_announcement"
+            join "announcement" on "announcement"."id" = "device_announcement"."announcement_id"
+            join "media" on "media"."id" = "announcement"."media_id"
+            join "device" on "device"."id" = "device_announcement"."device_id"
+            join "floor" on "floor"."id" = "device"."floor_id"
+            where
+                "device_announcement"."device_id" = $1 and
+                "device_announcement"."targeted" and
+                "announcement"."status" in ('draft', 'waiting_for_approval', 'waiting_for_sync', 'active') and
+                not exists (
+                    select 1 from "announcement_target"
+                    where
+                        "announcement_target"."announcement_id" = "announcement"."id" and
+                        (
+                            "announcement_target"."floor_id" = "floor"."id" or
+                            "announcement_target"."building_id" = "floor"."building_id"
+                        )
+                )
+            order by "announcement"."id"
+            "#,
+        )
+        .bind(device_id)
+        .map(map_targeted_announcement)
+        .fetch_all(unit_of_work.connection())
+        .await?;
+
+        Ok(result)
+    }
+
+    async fn attach_announcements(
+        &self,
+        unit_of_work: &mut UnitOfWork,
+        device_id: i32,
+        announcement_ids: Vec<i32>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            insert into "device_announcement" ("device_id", "announcement_id", "targeted")
+            select $1, "announcement_id", true
+            from unnest($2::integer[]) as "announcement_id"
+            "#,
+        )
+        .bind(device_id)
+        .bind(announcement_ids)
+        .execute(unit_of_work.connection())
+        .await?;
+
+        Ok(())
+    }
+
+    async fn detach_announcements(
+        &self,
+        unit_of_work: &mut UnitOfWork,
+        device_id: i32,
+        announcement_ids: Vec<i32>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            delete from "device_announcement"
+            where "device_id" = $1 and "announcement_id" = any($2)
+            "#,
+        )
+        .bind(device_id)
+        .bind(announcement_ids)
+        .execute(unit_of_work.connection())
+        .await?;
+
+        Ok(())
     }
 
     async fn update_device_link(&self, device_id: i32, link: bool) -> Result<(), sqlx::Error> {
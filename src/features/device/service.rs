@@ -10,7 +10,7 @@ use rand::distributions::{Alphanumeric, DistString};
 use regex::Regex;
 
 use crate::{
-    database::{DatabaseError, PaginationResult},
+    database::{DatabaseError, PaginationResult, UnitOfWork, UnitOfWorkFactoryInterface},
     features::{device_status::definition::DeviceStatus, AnnouncementQueueInterface},
 };
 
@@ -19,7 +19,7 @@ use super::{
     DeviceAuthCache, DeviceDetail, DeviceRepositoryInterface, GetDeviceAuthCacheError,
     GetDeviceDetailByAccessKeyIdError, GetDeviceDetailByIdError, InsertDeviceParams,
     LinkDeviceError, ListDeviceError, ListDeviceParams, ResyncDeviceError,
-    SynchronizeDeviceStatusError, UnlinkDeviceError, UpdateCameraEnabledError, UpdateDeviceError,
+    SyncTargetedAnnouncementsError, SynchronizeDeviceStatusError, UnlinkDeviceError, UpdateCameraEnabledError, UpdateDeviceError,
     UpdateDeviceParams,
 };
 
@@ -88,18 +88,106 @@ pub trait DeviceServiceInterface {
 pub struct DeviceService {
     _device_repository: Arc<dyn DeviceRepositoryInterface + Send + Sync + 'static>,
     _announcement_queue: Arc<dyn AnnouncementQueueInterface + Send + Sync + 'static>,
+    _unit_of_work: Arc<dyn UnitOfWorkFactoryInterface + Send + Sync + 'static>,
 }
 
 impl DeviceService {
     pub fn new(
         _device_repository: Arc<dyn DeviceRepositoryInterface + Send + Sync + 'static>,
         _announcement_queue: Arc<dyn AnnouncementQueueInterface + Send + Sync + 'static>,
+        _unit_of_work: Arc<dyn UnitOfWorkFactoryInterface + Send + Sync + 'static>,
     ) -> Self {
         DeviceService {
             _device_repository,
             _announcement_queue,
+            _unit_of_work,
         }
     }
+
+    /// Attaches the announcements targeting the current location of the device and detaches the
+    /// targeted ones it has left, the announcements already displayed are pushed to the device queue
+    async fn sync_targeted_announcements(
+        &self,
+        unit_of_work: &mut UnitOfWork,
+        device_id: i32,
+    ) -> Result<(), SyncTargetedAnnouncementsError> {
+        let unmatched = self
+            ._device_repository
+            .find_unmatched_announcements(unit_of_work, device_id)
+            .await?;
+
+        if unmatched.len() > 0 {
+            self._device_repository
+                .detach_announcements(
+                    unit_of_work,
+                    device_id,
+                    unmatched.iter().map(|announcement| announcement.id).collect(),
+                )
+                .await?;
+        }
+
+        for announcement in unmatched.iter().filter(|a| a.is_displayed()) {
+            let result = if announcement.is_emergency {
+                self._announcement_queue
+                    .clear_emergency(unit_of_work, vec![device_id], announcement.id)
+                    .await
+            } else {
+                self._announcement_queue
+                    .delete(unit_of_work, vec![device_id], announcement.id)
+                    .await
+            };
+
+            if let Err(_) = result {
+                return Err(SyncTargetedAnnouncementsError::QueueError);
+            }
+        }
+
+        let matching = self
+            ._device_repository
+            .find_matching_announcements(unit_of_work, device_id)
+            .await?;
+
+        if matching.len() > 0 {
+            self._device_repository
+                .attach_announcements(
+                    unit_of_work,
+                    device_id,
+                    matching.iter().map(|announcement| announcement.id).collect(),
+                )
+                .await?;
+        }
+
+        for announcement in matching.iter().filter(|a| a.is_displayed()) {
+            let result = if announcement.is_emergency {
+                self._announcement_queue
+                    .emergency(
+                        unit_of_work,
+                        vec![device_id],
+                        announcement.id,
+                        announcement.media_type.to_string(),
+                        announcement.media_duration,
+                    )
+                    .await
+            } else {
+                self._announcement_queue
+                    .create(
+                        unit_of_work,
+                        vec![device_id],
+                        announcement.id,
+                        announcement.media_type.to_string(),
+                        announcement.media_duration,
+                        announcement.recurrence.clone(),
+                    )
+                    .await
+            };
+
+            if let Err(_) = result {
+                return Err(SyncTargetedAnnouncementsError::QueueError);
+            }
+        }
+
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -236,16 +324,24 @@ impl DeviceServiceInterface for DeviceService {
             Err(_) => return Err(CreateDeviceError::InternalServerError),
         };
 
+        let mut unit_of_work = match self._unit_of_work.begin().await {
+            Ok(unit_of_work) => unit_of_work,
+            Err(_) => return Err(CreateDeviceError::InternalServerError),
+        };
+
         let id = match self
             ._device_repository
-            .insert(InsertDeviceParams {
-                name: params.name.clone(),
-                description: params.description.clone(),
-                floor_id: params.floor_id,
-                access_key_id: access_key_id.clone(),
-                secret_access_key: secret_access_key_hash.to_string(),
-                secret_access_key_salt: secret_access_key_salt.clone(),
-            })
+            .insert(
+                &mut unit_of_work,
+                InsertDeviceParams {
+                    name: params.name.clone(),
+                    description: params.description.clone(),
+                    floor_id: params.floor_id,
+                    access_key_id: access_key_id.clone(),
+                    secret_access_key: secret_access_key_hash.to_string(),
+                    secret_access_key_salt: secret_access_key_salt.clone(),
+                },
+            )
             .await
         {
             Ok(id) => id,
@@ -271,6 +367,17 @@ impl DeviceServiceInterface for DeviceService {
             },
         };
 
+        if let Err(_) = self
+            .sync_targeted_announcements(&mut unit_of_work, id)
+            .await
+        {
+            return Err(CreateDeviceError::InternalServerError);
+        }
+
+        if let Err(_) = unit_of_work.commit().await {
+            return Err(CreateDeviceError::InternalServerError);
+        }
+
         Ok(CreateDeviceResult {
             id,
             access_key_id,
@@ -283,9 +390,15 @@ impl DeviceServiceInterface for DeviceService {
         device_id: i32,
         params: UpdateDeviceInfoParams,
     ) -> Result<(), UpdateDeviceError> {
+        let mut unit_of_work = match self._unit_of_work.begin().await {
+            Ok(unit_of_work) => unit_of_work,
+            Err(_) => return Err(UpdateDeviceError::InternalServerError),
+        };
+
         if let Err(e) = self
             ._device_repository
             .update(
+                &mut unit_of_work,
                 device_id,
                 UpdateDeviceParams {
                     name: params.name.clone(),
@@ -320,6 +433,18 @@ impl DeviceServiceInterface for DeviceService {
             }
         }
 
+        // Moving the device to another floor changes which targeted announcements it displays
+        if let Err(_) = self
+            .sync_targeted_announcements(&mut unit_of_work, device_id)
+            .await
+        {
+            return Err(UpdateDeviceError::InternalServerError);
+        }
+
+        if let Err(_) = unit_of_work.commit().await {
+            return Err(UpdateDeviceError::InternalServerError);
+        }
+
         Ok(())
     }
 
@@ -345,6 +470,18 @@ impl DeviceServiceInterface for DeviceService {
             }
         }
 
+        // The device is already gone at this point, a leftover queue only costs memory
+        if let Err(e) = self
+            ._announcement_queue
+            .remove_device_queue(device_id)
+            .await
+        {
+            eprintln!(
+                "Something went wrong when removing the queue of device {}: {}",
+                device_id, e
+            );
+        }
+
         Ok(())
     }
 
@@ -362,21 +499,45 @@ impl DeviceServiceInterface for DeviceService {
 
         let announcement_ids = match self
             ._device_repository
-            .find_announcement_ids_in_device(device_id)
+            .find_displayed_announcement_ids_in_device(device_id)
             .await
         {
             Ok(ids) => ids,
             Err(_) => return Err(ResyncDeviceError::InternalServerError),
         };
 
+        // A device that resyncs during an emergency has to go back to displaying it
+        let emergency_announcement_id = match self
+            ._device_repository
+            .find_active_emergency_announcement_id_in_device(device_id)
+            .await
+        {
+            Ok(id) => id,
+            Err(_) => return Err(ResyncDeviceError::InternalServerError),
+        };
+
+        let mut unit_of_work = match self._unit_of_work.begin().await {
+            Ok(unit_of_work) => unit_of_work,
+            Err(_) => return Err(ResyncDeviceError::InternalServerError),
+        };
+
         if let Err(_) = self
             ._announcement_queue
-            .resync(device_id, announcement_ids)
+            .resync(
+                &mut unit_of_work,
+                device_id,
+                announcement_ids,
+                emergency_announcement_id,
+            )
             .await
         {
             return Err(ResyncDeviceError::InternalServerError);
         }
 
+        if let Err(_) = unit_of_work.commit().await {
+            return Err(ResyncDeviceError::InternalServerError);
+        }
+
         Ok(())
     }
 
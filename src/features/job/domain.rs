@@ -0,0 +1,38 @@
+use serde::{Deserialize, Serialize};
+
+pub struct JobRun {
+    pub id: i32,
+    pub job_name: String,
+    pub trigger: JobRunTrigger,
+    pub outcome: JobRunOutcome,
+    pub error: Option<String>,
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    pub finished_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+pub struct JobSummary {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub cron: &'static str,
+    pub timezone: String,
+    pub next_run_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub last_run: Option<JobRun>,
+}
+
+#[derive(Debug, Clone, PartialEq, sqlx::Type, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "job_run_trigger", rename_all = "snake_case")]
+pub enum JobRunTrigger {
+    Scheduled,
+    Manual,
+    CatchUp,
+}
+
+#[derive(Debug, Clone, PartialEq, sqlx::Type, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "job_run_outcome", rename_all = "snake_case")]
+pub enum JobRunOutcome {
+    Running,
+    Succeeded,
+    Failed,
+}
@@ -0,0 +1,50 @@
+use thiserror::Error;
+
+pub enum JobErrorCode {
+    JobNotFound,
+    JobAlreadyRunning,
+    InternalServerError,
+}
+
+impl std::fmt::Display for JobErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            JobErrorCode::JobNotFound => write!(f, "JOB_NOT_FOUND"),
+            JobErrorCode::JobAlreadyRunning => write!(f, "JOB_ALREADY_RUNNING"),
+            JobErrorCode::InternalServerError => write!(f, "INTERNAL_SERVER_ERROR"),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ListJobError {
+    #[error("An error occurred with the request to the database")]
+    Database(#[from] sqlx::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum ListJobRunError {
+    #[error("Job not found")]
+    JobNotFound,
+
+    #[error("An error occurred with the request to the database")]
+    Database(#[from] sqlx::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum ExecuteJobError {
+    #[error("Job not found")]
+    JobNotFound,
+
+    #[error("Job is already running")]
+    JobAlreadyRunning,
+
+    #[error("Job failed: {0}")]
+    Failed(String),
+
+    #[error("An error occurred with the request to the job lock: {0}")]
+    Lock(String),
+
+    #[error("An error occurred with the request to the database")]
+    Database(#[from] sqlx::Error),
+}
@@ -0,0 +1,203 @@
+use std::sync::Arc;
+
+use actix_web::{web, HttpResponse};
+use serde::{Deserialize, Serialize};
+
+use crate::http::{
+    derive_authentication_middleware_error, derive_user_id, AuthenticationContext,
+    HttpErrorResponse,
+};
+
+use super::{
+    domain::{JobRun, JobRunOutcome, JobRunTrigger},
+    error::{ExecuteJobError, JobErrorCode, ListJobError, ListJobRunError},
+    service::{JobServiceInterface, ListJobRunParams},
+};
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobRunContent {
+    id: i32,
+    job_name: String,
+    trigger: JobRunTrigger,
+    outcome: JobRunOutcome,
+    error: Option<String>,
+    started_at: String,
+    finished_at: Option<String>,
+}
+
+impl From<JobRun> for JobRunContent {
+    fn from(run: JobRun) -> Self {
+        JobRunContent {
+            id: run.id,
+            job_name: run.job_name,
+            trigger: run.trigger,
+            outcome: run.outcome,
+            error: run.error,
+            started_at: run.started_at.to_rfc3339(),
+            finished_at: run.finished_at.map(|date| date.to_rfc3339()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListJobContent {
+    name: &'static str,
+    description: &'static str,
+    cron: &'static str,
+    timezone: String,
+    next_run_at: Option<String>,
+    last_run: Option<JobRunContent>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListJobResponse {
+    contents: Vec<ListJobContent>,
+}
+
+pub async fn list_jobs(
+    job_service: web::Data<Arc<dyn JobServiceInterface>>,
+    auth: AuthenticationContext,
+) -> HttpResponse {
+    if let Err(e) = derive_user_id(auth) {
+        return derive_authentication_middleware_error(e);
+    }
+
+    let result = match job_service.list_jobs().await {
+        Ok(result) => result,
+        Err(e) => match e {
+            ListJobError::Database(_) => {
+                return HttpResponse::InternalServerError().json(HttpErrorResponse::new(
+                    JobErrorCode::InternalServerError.to_string(),
+                    vec![e.to_string()],
+                ))
+            }
+        },
+    };
+
+    HttpResponse::Ok().json(ListJobResponse {
+        contents: result
+            .into_iter()
+            .map(|job| ListJobContent {
+                name: job.name,
+                description: job.description,
+                cron: job.cron,
+                timezone: job.timezone,
+                next_run_at: job.next_run_at.map(|date| date.to_rfc3339()),
+                last_run: job.last_run.map(JobRunContent::from),
+            })
+            .collect(),
+    })
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListJobRunQueryParams {
+    pub page: Option<i32>,
+    pub limit: Option<i32>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListJobRunResponse {
+    count: i32,
+    total_pages: i32,
+    has_next: bool,
+    contents: Vec<JobRunContent>,
+}
+
+pub async fn list_job_runs(
+    job_service: web::Data<Arc<dyn JobServiceInterface>>,
+    auth: AuthenticationContext,
+    job_name: web::Path<String>,
+    query_params: web::Query<ListJobRunQueryParams>,
+) -> HttpResponse {
+    if let Err(e) = derive_user_id(auth) {
+        return derive_authentication_middleware_error(e);
+    }
+
+    let mut page = 1;
+    if let Some(raw_page) = query_params.page {
+        page = raw_page;
+    }
+
+    let mut limit = 25;
+    if let Some(raw_limit) = query_params.limit {
+        limit = raw_limit;
+    }
+
+    let result = match job_service
+        .list_job_runs(ListJobRunParams {
+            page,
+            limit,
+            job_name: job_name.into_inner(),
+        })
+        .await
+    {
+        Ok(result) => result,
+        Err(e) => match e {
+            ListJobRunError::JobNotFound => {
+                return HttpResponse::NotFound().json(HttpErrorResponse::new(
+                    JobErrorCode::JobNotFound.to_string(),
+                    vec![e.to_string()],
+                ))
+            }
+            ListJobRunError::Database(_) => {
+                return HttpResponse::InternalServerError().json(HttpErrorResponse::new(
+                    JobErrorCode::InternalServerError.to_string(),
+                    vec![e.to_string()],
+                ))
+            }
+        },
+    };
+
+    HttpResponse::Ok().json(ListJobRunResponse {
+        count: result.count,
+        total_pages: result.total_pages,
+        has_next: result.has_next,
+        contents: result
+            .contents
+            .into_iter()
+            .map(JobRunContent::from)
+            .collect(),
+    })
+}
+
+pub async fn run_job(
+    job_service: web::Data<Arc<dyn JobServiceInterface>>,
+    auth: AuthenticationContext,
+    job_name: web::Path<String>,
+) -> HttpResponse {
+    if let Err(e) = derive_user_id(auth) {
+        return derive_authentication_middleware_error(e);
+    }
+
+    if let Err(e) = job_service.run_job(job_name.into_inner()).await {
+        match e {
+            ExecuteJobError::JobNotFound => {
+                return HttpResponse::NotFound().json(HttpErrorResponse::new(
+                    JobErrorCode::JobNotFound.to_string(),
+                    vec![e.to_string()],
+                ))
+            }
+            ExecuteJobError::JobAlreadyRunning => {
+                return HttpResponse::Conflict().json(HttpErrorResponse::new(
+                    JobErrorCode::JobAlreadyRunning.to_string(),
+                    vec![e.to_string()],
+                ))
+            }
+            ExecuteJobError::Failed(_)
+            | ExecuteJobError::Lock(_)
+            | ExecuteJobError::Database(_) => {
+                return HttpResponse::InternalServerError().json(HttpErrorResponse::new(
+                    JobErrorCode::InternalServerError.to_string(),
+                    vec![e.to_string()],
+                ))
+            }
+        }
+    }
+
+    HttpResponse::Accepted().finish()
+}
@@ -0,0 +1,5 @@
+pub mod error;
+pub mod domain;
+pub mod repository;
+pub mod service;
+pub mod http;
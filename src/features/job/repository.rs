@@ -0,0 +1,240 @@
+use async_trait::async_trait;
+use sqlx::{postgres::PgRow, Pool, Postgres, Row};
+
+use crate::database::PaginationResult;
+
+use super::domain::{JobRun, JobRunOutcome, JobRunTrigger};
+
+pub struct FindJobRunParams {
+    pub page: i32,
+    pub limit: i32,
+    pub job_name: Option<String>,
+}
+
+impl FindJobRunParams {
+    pub fn default() -> Self {
+        FindJobRunParams {
+            page: 1,
+            limit: 25,
+            job_name: None,
+        }
+    }
+
+    pub fn page(mut self, page: i32) -> Self {
+        self.page = page;
+        self
+    }
+
+    pub fn limit(mut self, limit: i32) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    pub fn job_name(mut self, job_name: String) -> Self {
+        self.job_name = Some(job_name);
+        self
+    }
+}
+
+#[async_trait]
+pub trait JobRepositoryInterface: Send + Sync + 'static {
+    async fn insert_run(&self, job_name: String, trigger: JobRunTrigger)
+        -> Result<i32, sqlx::Error>;
+    async fn finish_run(
+        &self,
+        job_run_id: i32,
+        outcome: JobRunOutcome,
+        error: Option<String>,
+    ) -> Result<(), sqlx::Error>;
+    async fn find_runs(
+        &self,
+        params: FindJobRunParams,
+    ) -> Result<PaginationResult<JobRun>, sqlx::Error>;
+    async fn find_latest_runs(&self) -> Result<Vec<JobRun>, sqlx::Error>;
+    async fn find_last_tick(
+        &self,
+        job_name: String,
+    ) -> Result<Option<chrono::DateTime<chrono::Utc>>, sqlx::Error>;
+    async fn upsert_last_tick(
+        &self,
+        job_name: String,
+        tick: chrono::DateTime<chrono::Utc>,
+    ) -> Result<(), sqlx::Error>;
+}
+
+pub struct JobRepository {
+    _db: Pool<Postgres>,
+}
+
+impl JobRepository {
+    pub fn new(_db: Pool<Postgres>) -> Self {
+        JobRepository { _db }
+    }
+}
+
+fn map_job_run(row: &PgRow) -> JobRun {
+    JobRun {
+        id: row.get("id"),
+        job_name: row.get("job_name"),
+        trigger: row.get("trigger"),
+        outcome: row.get("outcome"),
+        error: row.get("error"),
+        started_at: row.get("started_at"),
+        finished_at: row.get("finished_at"),
+    }
+}
+
+#[async_trait]
+impl JobRepositoryInterface for JobRepository {
+    async fn insert_run(
+        &self,
+        job_name: String,
+        trigger: JobRunTrigger,
+    ) -> Result<i32, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            insert into "job_run" ("job_name", "trigger")
+            values ($1, $2)
+            returning "id"
+            "#,
+        )
+        .bind(job_name)
+        .bind(trigger)
+        .map(|row: PgRow| row.get("id"))
+        .fetch_one(&self._db)
+        .await?;
+
+        Ok(result)
+    }
+
+    async fn finish_run(
+        &self,
+        job_run_id: i32,
+        outcome: JobRunOutcome,
+        error: Option<String>,
+    ) -> Result<(), sqlx::Error> {
+        let rows_affected = sqlx::query(
+            r#"
+            update "job_run"
+            set
+                "outcome" = $2,
+                "error" = $3,
+                "finished_at" = now()
+            where "id" = $1
+            "#,
+        )
+        .bind(job_run_id)
+        .bind(outcome)
+        .bind(error)
+        .execute(&self._db)
+        .await?
+        .rows_affected();
+
+        if rows_affected == 0 {
+            return Err(sqlx::Error::RowNotFound);
+        }
+
+        Ok(())
+    }
+
+    async fn find_runs(
+        &self,
+        params: FindJobRunParams,
+    ) -> Result<PaginationResult<JobRun>, sqlx::Error> {
+        let offset = (params.page - 1) * params.limit;
+
+        let count: i32 = sqlx::query(
+            r#"
+            select cast(count(*) as integer) as "count"
+            from "job_run"
+            where $1::text is null or "job_name" = $1
+            "#,
+        )
+        .bind(params.job_name.clone())
+        .map(|row: PgRow| row.get("count"))
+        .fetch_one(&self._db)
+        .await?;
+
+        let contents = sqlx::query(
+            r#"
+            select "id", "job_name", "trigger", "outcome", "error", "started_at", "finished_at"
+            from "job_run"
+            where $3::text is null or "job_name" = $3
+            order by "started_at" desc, "id" desc
+            offset $1 limit $2
+            "#,
+        )
+        .bind(offset)
+        .bind(params.limit)
+        .bind(params.job_name)
+        .map(|row: PgRow| map_job_run(&row))
+        .fetch_all(&self._db)
+        .await?;
+
+        let total_pages = (count as f64 / params.limit as f64).ceil() as i32;
+        let has_next = ((params.page as f64 * params.limit as f64) / count as f64) < 1.0;
+
+        Ok(PaginationResult {
+            count,
+            total_pages,
+            has_next,
+            contents,
+        })
+    }
+
+    async fn find_latest_runs(&self) -> Result<Vec<JobRun>, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            select distinct on ("job_name")
+                "id", "job_name", "trigger", "outcome", "error", "started_at", "finished_at"
+            from "job_run"
+            order by "job_name", "started_at" desc, "id" desc
+            "#,
+        )
+        .map(|row: PgRow| map_job_run(&row))
+        .fetch_all(&self._db)
+        .await?;
+
+        Ok(result)
+    }
+
+    async fn find_last_tick(
+        &self,
+        job_name: String,
+    ) -> Result<Option<chrono::DateTime<chrono::Utc>>, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            select "last_tick_at"
+            from "job_tick"
+            where "job_name" = $1
+            "#,
+        )
+        .bind(job_name)
+        .map(|row: PgRow| row.get("last_tick_at"))
+        .fetch_optional(&self._db)
+        .await?;
+
+        Ok(result)
+    }
+
+    async fn upsert_last_tick(
+        &self,
+        job_name: String,
+        tick: chrono::DateTime<chrono::Utc>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            insert into "job_tick" ("job_name", "last_tick_at")
+            values ($1, $2)
+            on conflict ("job_name") do update
+            set "last_tick_at" = greatest("job_tick"."last_tick_at", excluded."last_tick_at")
+            "#,
+        )
+        .bind(job_name)
+        .bind(tick)
+        .execute(&self._db)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,120 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::{database::PaginationResult, scheduler::JobRegistry};
+
+use super::{
+    domain::{JobRun, JobRunTrigger, JobSummary},
+    error::{ExecuteJobError, ListJobError, ListJobRunError},
+    repository::{FindJobRunParams, JobRepositoryInterface},
+};
+
+pub struct ListJobRunParams {
+    pub page: i32,
+    pub limit: i32,
+    pub job_name: String,
+}
+
+#[async_trait]
+pub trait JobServiceInterface: Send + Sync + 'static {
+    async fn list_jobs(&self) -> Result<Vec<JobSummary>, ListJobError>;
+    async fn list_job_runs(
+        &self,
+        params: ListJobRunParams,
+    ) -> Result<PaginationResult<JobRun>, ListJobRunError>;
+    async fn run_job(&self, job_name: String) -> Result<(), ExecuteJobError>;
+}
+
+pub struct JobService {
+    _job_repository: Arc<dyn JobRepositoryInterface>,
+    _registry: Arc<JobRegistry>,
+}
+
+impl JobService {
+    pub fn new(
+        _job_repository: Arc<dyn JobRepositoryInterface>,
+        _registry: Arc<JobRegistry>,
+    ) -> Self {
+        JobService {
+            _job_repository,
+            _registry,
+        }
+    }
+}
+
+#[async_trait]
+impl JobServiceInterface for JobService {
+    async fn list_jobs(&self) -> Result<Vec<JobSummary>, ListJobError> {
+        let mut latest_runs = self._job_repository.find_latest_runs().await?;
+
+        let summaries = self
+            ._registry
+            .jobs()
+            .into_iter()
+            .map(|job| {
+                let last_run = latest_runs
+                    .iter()
+                    .position(|run| run.job_name == job.name)
+                    .map(|index| latest_runs.remove(index));
+                let now = chrono::Utc::now().with_timezone(&job.timezone);
+
+                JobSummary {
+                    name: job.name,
+                    description: job.description,
+                    cron: job.cron,
+                    timezone: job.timezone.name().to_string(),
+                    next_run_at: job
+                        .next_run_after(now)
+                        .map(|event| event.with_timezone(&chrono::Utc)),
+                    last_run,
+                }
+            })
+            .collect();
+
+        Ok(summaries)
+    }
+
+    async fn list_job_runs(
+        &self,
+        params: ListJobRunParams,
+    ) -> Result<PaginationResult<JobRun>, ListJobRunError> {
+        if self._registry.find(params.job_name.as_str()).is_none() {
+            return Err(ListJobRunError::JobNotFound);
+        }
+
+        let result = self
+            ._job_repository
+            .find_runs(
+                FindJobRunParams::default()
+                    .page(params.page)
+                    .limit(params.limit)
+                    .job_name(params.job_name),
+            )
+            .await?;
+
+        Ok(result)
+    }
+
+    async fn run_job(&self, job_name: String) -> Result<(), ExecuteJobError> {
+        if self._registry.find(job_name.as_str()).is_none() {
+            return Err(ExecuteJobError::JobNotFound);
+        }
+        if self._registry.is_running(job_name.as_str()).await? {
+            return Err(ExecuteJobError::JobAlreadyRunning);
+        }
+
+        // The job is executed in the background, its outcome is available through the run history
+        let registry = self._registry.clone();
+        actix_web::rt::spawn(async move {
+            if let Err(e) = registry
+                .execute(job_name.as_str(), JobRunTrigger::Manual, chrono::Utc::now())
+                .await
+            {
+                eprintln!("Something went wrong when running the {} job: {}", job_name, e);
+            }
+        });
+
+        Ok(())
+    }
+}
@@ -1,16 +1,22 @@
-use std::sync::Arc;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use chrono::DateTime;
-use tokio::sync::{mpsc, oneshot};
+use tokio::{
+    sync::{mpsc, oneshot},
+    time::sleep,
+};
 
 use crate::{
-    features::livestream::definition::DEVICE_LIVESTREAM_QUEUE_NAME,
-    queue::{Consumer, ConsumerError},
+    queue::{ConsumerError, ConsumerInterface, PendingMessage, RetryPolicy},
     shutdown::Shutdown,
 };
 
 use super::{
     definition::{LivestreamDeviceMap, LivestreamMessagePayload, LivestreamSessionMap},
+    error::InsertLivestreamError,
     service::LivestreamServiceInterface,
     socket::LivestreamMessage,
 };
@@ -18,7 +24,8 @@ use super::{
 pub async fn run(
     mut shutdown: Shutdown,
     _sender: mpsc::Sender<()>,
-    redis: deadpool_redis::Pool,
+    mut consumer: Box<dyn ConsumerInterface>,
+    retry_policy: RetryPolicy,
     livestream_service: Arc<dyn LivestreamServiceInterface>,
     sessions: LivestreamSessionMap,
     devices: LivestreamDeviceMap,
@@ -27,18 +34,42 @@ pub async fn run(
     let tx_2 = tx.clone();
 
     let listener = actix_web::rt::spawn(async move {
-        let mut consumer = Consumer::new(redis, DEVICE_LIVESTREAM_QUEUE_NAME.to_string());
-
         println!("Livestream consumer has started");
 
+        // Claiming right away recovers the messages of an instance that crashed before this one started
+        let mut last_claimed_at: Option<Instant> = None;
+
         loop {
             if let Ok(resp) = rx.try_recv() {
                 let _ = resp.send(true);
                 break;
             }
 
-            let pending_message_id = match consumer.get_pending_message_id().await {
-                Ok(id) => id,
+            if last_claimed_at.is_none_or(|at| at.elapsed() >= consumer.claim_min_idle()) {
+                match consumer.claim_stale_messages().await {
+                    Ok(ids) if !ids.is_empty() => {
+                        println!("Livestream consumer claimed {} stale messages", ids.len())
+                    }
+                    Ok(_) => (),
+                    Err(e) => eprintln!(
+                        "Something went wrong when claiming the stale livestream messages: {}",
+                        e
+                    ),
+                }
+
+                // The devices append to the stream directly, so it is trimmed from here
+                if let Err(e) = consumer.trim().await {
+                    eprintln!(
+                        "Something went wrong when trimming the livestream queue: {}",
+                        e
+                    );
+                }
+
+                last_claimed_at = Some(Instant::now());
+            }
+
+            let pending_message = match consumer.get_pending_message().await {
+                Ok(message) => message,
                 Err(_) => continue,
             };
 
@@ -47,35 +78,77 @@ pub async fn run(
             let sessions = sessions.clone();
             let devices = devices.clone();
 
-            let message_id = if let Some(message_id) = pending_message_id {
+            let outcome = if let Some(pending_message) = pending_message {
+                // A failed message is left pending and only retried once its backoff has elapsed
+                let backoff = retry_policy.backoff(pending_message.times_delivered);
+                if pending_message.idle < backoff {
+                    tokio::select! {
+                        _response = rx.recv() => {
+                            break;
+                        },
+                        _ = sleep(backoff - pending_message.idle) => {
+                            continue;
+                        },
+                    }
+                }
+
                 handle_pending_message(
                     &mut consumer,
                     livestream_service,
                     sessions,
                     devices,
-                    message_id,
+                    pending_message,
+                    backoff,
                 )
                 .await
             } else {
                 #[allow(unused_assignments)]
-                let mut message_id: Option<String> = None;
+                let mut outcome: Option<MessageOutcome> = None;
 
                 tokio::select! {
                     _response = rx.recv() => {
                         break;
                     },
                     data = consumer.consume_raw() => {
-                        message_id = handle_upcoming_message(data, livestream_service, sessions, devices).await;
+                        outcome = handle_upcoming_message(data, livestream_service, sessions, devices).await;
                     },
                 }
 
-                message_id
+                outcome
             };
 
-            if let Some(id) = message_id {
-                if let Err(_) = consumer.ack(id).await {
-                    continue;
+            match outcome {
+                Some(MessageOutcome::Processed(message_id)) => {
+                    if let Err(_) = consumer.ack(message_id).await {
+                        continue;
+                    }
                 }
+                Some(MessageOutcome::Failed {
+                    message_id,
+                    payload,
+                    reason,
+                    times_delivered,
+                    is_retryable,
+                }) => {
+                    if is_retryable && !retry_policy.is_exhausted(times_delivered) {
+                        eprintln!(
+                            "Livestream message {} failed (delivery {}), retrying later: {}",
+                            message_id, times_delivered, reason
+                        );
+                        continue;
+                    }
+
+                    if let Err(e) = consumer
+                        .dead_letter(message_id.clone(), payload, reason, times_delivered)
+                        .await
+                    {
+                        eprintln!(
+                            "Something went wrong when dead-lettering the livestream message {}: {}",
+                            message_id, e
+                        );
+                    }
+                }
+                None => (),
             }
         }
     });
@@ -99,39 +172,51 @@ pub async fn run(
     tokio::try_join!(listener, shutdown_listener).unwrap();
 }
 
+enum MessageOutcome {
+    Processed(String),
+    Failed {
+        message_id: String,
+        payload: String,
+        reason: String,
+        times_delivered: usize,
+        is_retryable: bool,
+    },
+}
+
 async fn handle_pending_message(
-    consumer: &mut Consumer,
+    consumer: &mut Box<dyn ConsumerInterface>,
     livestream_service: Arc<dyn LivestreamServiceInterface>,
     sessions: LivestreamSessionMap,
     devices: LivestreamDeviceMap,
-    message_id: String,
-) -> Option<String> {
+    pending_message: PendingMessage,
+    backoff: Duration,
+) -> Option<MessageOutcome> {
     let data = match consumer
-        .read_raw_by_message_id(message_id.to_string())
+        .claim_raw(pending_message.id.to_string(), backoff)
         .await
     {
         Ok(res) => res,
         Err(_) => return None,
     };
 
+    // The entry no longer exists in the stream, there is nothing left to retry
     if data.len() == 0 {
-        return Some(message_id.to_string());
+        return Some(MessageOutcome::Processed(pending_message.id));
     }
 
     let (message_id, payload) = &data[0];
 
-    let livestream_message = match parse_livestream_message(payload.to_string()) {
-        Some(msg) => msg,
-        None => return Some(message_id.to_string()),
-    };
-
-    if let Err(_) = livestream_service.insert(livestream_message.clone()).await {
-        return Some(message_id.to_string());
-    };
-
-    publish(sessions, devices, livestream_message);
-
-    return Some(message_id.to_string());
+    Some(
+        process_message(
+            message_id.to_string(),
+            payload.to_string(),
+            pending_message.times_delivered + 1,
+            livestream_service,
+            sessions,
+            devices,
+        )
+        .await,
+    )
 }
 
 async fn handle_upcoming_message(
@@ -139,7 +224,7 @@ async fn handle_upcoming_message(
     livestream_service: Arc<dyn LivestreamServiceInterface>,
     sessions: LivestreamSessionMap,
     devices: LivestreamDeviceMap,
-) -> Option<String> {
+) -> Option<MessageOutcome> {
     let data = match result {
         Ok(res) => res,
         Err(_) => return None,
@@ -151,18 +236,56 @@ async fn handle_upcoming_message(
 
     let (message_id, payload) = &data[0];
 
+    Some(
+        process_message(
+            message_id.to_string(),
+            payload.to_string(),
+            1,
+            livestream_service,
+            sessions,
+            devices,
+        )
+        .await,
+    )
+}
+
+async fn process_message(
+    message_id: String,
+    payload: String,
+    times_delivered: usize,
+    livestream_service: Arc<dyn LivestreamServiceInterface>,
+    sessions: LivestreamSessionMap,
+    devices: LivestreamDeviceMap,
+) -> MessageOutcome {
+    // A malformed message would fail the same way on every delivery
     let livestream_message = match parse_livestream_message(payload.to_string()) {
         Some(msg) => msg,
-        None => return Some(message_id.to_string()),
+        None => {
+            return MessageOutcome::Failed {
+                message_id,
+                payload,
+                reason: "Unable to parse the livestream message".into(),
+                times_delivered,
+                is_retryable: false,
+            }
+        }
     };
 
-    if let Err(_) = livestream_service.insert(livestream_message.clone()).await {
-        return Some(message_id.to_string());
+    if let Err(e) = livestream_service.insert(livestream_message.clone()).await {
+        return MessageOutcome::Failed {
+            message_id,
+            payload,
+            reason: match &e {
+                InsertLivestreamError::DatabaseError(inner) => format!("{}: {}", e, inner),
+            },
+            times_delivered,
+            is_retryable: true,
+        };
     };
 
     publish(sessions, devices, livestream_message);
 
-    return Some(message_id.to_string());
+    MessageOutcome::Processed(message_id)
 }
 
 fn parse_livestream_message(message: String) -> Option<LivestreamMessagePayload> {
@@ -7,11 +7,13 @@ pub struct InsertMediaParams {
     pub path: String,
     pub media_type: MediaType,
     pub media_duration: Option<f64>,
+    pub content_hash: String,
 }
 
 #[async_trait]
 pub trait MediaRepositoryInterface: Send + Sync + 'static {
     async fn insert(&self, params: InsertMediaParams) -> Result<i32, sqlx::Error>;
+    async fn exists(&self, media_id: i32) -> Result<bool, sqlx::Error>;
 }
 
 pub struct MediaRepository {
@@ -29,18 +31,33 @@ impl MediaRepositoryInterface for MediaRepository {
     async fn insert(&self, params: InsertMediaParams) -> Result<i32, sqlx::Error> {
         let result = sqlx::query(
             r#"
-                insert into "media" ("path", "media_type", "media_duration")
-                values ($1, $2, $3)
+                insert into "media" ("path", "media_type", "media_duration", "content_hash")
+                values ($1, $2, $3, $4)
                 returning "id"
             "#,
         )
         .bind(params.path)
         .bind(params.media_type)
         .bind(params.media_duration)
+        .bind(params.content_hash)
         .map(|row: PgRow| row.get("id"))
         .fetch_one(&self._db)
         .await?;
 
         Ok(result)
     }
+
+    async fn exists(&self, media_id: i32) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+                select exists (select 1 from "media" where "id" = $1) as "exists"
+            "#,
+        )
+        .bind(media_id)
+        .map(|row: PgRow| row.get("exists"))
+        .fetch_one(&self._db)
+        .await?;
+
+        Ok(result)
+    }
 }
@@ -58,6 +58,12 @@ impl MediaServiceInterface for MediaService {
 
         let path = media.key();
 
+        // Hashed before the upload, the temporary file is removed once it is uploaded
+        let content_hash = match media.content_hash() {
+            Ok(hash) => hash,
+            Err(_) => return Err(CreateMediaError::Unknown),
+        };
+
         if self._cloud_storage.upload(media).await.is_err() {
             println!("error 1");
             return Err(CreateMediaError::Unknown);
@@ -69,6 +75,7 @@ impl MediaServiceInterface for MediaService {
                 path: path.clone(),
                 media_type: params.media_type.clone(),
                 media_duration: params.media_duration,
+                content_hash,
             })
             .await?;
 
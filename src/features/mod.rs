@@ -9,6 +9,8 @@ pub mod user;
 pub mod device_status;
 pub mod livestream;
 pub mod media;
+pub mod job;
+pub mod queue;
 
 pub use announcement::*;
 pub use auth::*;
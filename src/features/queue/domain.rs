@@ -0,0 +1,18 @@
+use crate::{
+    features::announcement::DeviceSynchronizationParams,
+    queue::{Envelope, QueueInfo},
+};
+
+pub struct DeviceQueueMessage {
+    pub id: String,
+    pub data: String,
+    /// Empty when the message is not a valid synchronization envelope
+    pub envelope: Option<Envelope<DeviceSynchronizationParams>>,
+    pub is_pending: bool,
+}
+
+pub struct DeviceQueueInspection {
+    pub queue_name: String,
+    pub info: QueueInfo,
+    pub messages: Vec<DeviceQueueMessage>,
+}
@@ -0,0 +1,62 @@
+use thiserror::Error;
+
+pub enum QueueErrorCode {
+    DeadLetterNotFound,
+    DeviceNotFound,
+    InternalServerError,
+}
+
+impl std::fmt::Display for QueueErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            QueueErrorCode::DeadLetterNotFound => write!(f, "DEAD_LETTER_NOT_FOUND"),
+            QueueErrorCode::DeviceNotFound => write!(f, "DEVICE_NOT_FOUND"),
+            QueueErrorCode::InternalServerError => write!(f, "INTERNAL_SERVER_ERROR"),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ListDeadLetterError {
+    #[error("An error occurred with the request to redis: {0}")]
+    Redis(String),
+}
+
+#[derive(Debug, Error)]
+pub enum ReplayDeadLetterError {
+    #[error("Dead letter not found")]
+    DeadLetterNotFound,
+
+    #[error("An error occurred with the request to redis: {0}")]
+    Redis(String),
+}
+
+#[derive(Debug, Error)]
+pub enum PurgeDeadLetterError {
+    #[error("An error occurred with the request to redis: {0}")]
+    Redis(String),
+}
+
+#[derive(Debug, Error)]
+pub enum InspectDeviceQueueError {
+    #[error("Device not found")]
+    DeviceNotFound,
+
+    #[error("An error occurred with the request to redis: {0}")]
+    Redis(String),
+
+    #[error("An error occurred with the request to the database")]
+    Database(#[from] sqlx::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum PurgeDeviceQueueError {
+    #[error("Device not found")]
+    DeviceNotFound,
+
+    #[error("An error occurred with the request to redis: {0}")]
+    Redis(String),
+
+    #[error("An error occurred with the request to the database")]
+    Database(#[from] sqlx::Error),
+}
@@ -0,0 +1,307 @@
+use std::sync::Arc;
+
+use actix_web::{web, HttpResponse};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    features::announcement::DeviceSynchronizationParams,
+    http::{
+        derive_authentication_middleware_error, derive_user_id, AuthenticationContext,
+        HttpErrorResponse,
+    },
+    queue::{message_timestamp, DeadLetter, Envelope},
+};
+
+use super::{
+    domain::DeviceQueueMessage,
+    error::{
+        InspectDeviceQueueError, ListDeadLetterError, PurgeDeadLetterError,
+        PurgeDeviceQueueError, QueueErrorCode, ReplayDeadLetterError,
+    },
+    service::{InspectDeviceQueueParams, ListDeadLetterParams, QueueServiceInterface},
+};
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeadLetterContent {
+    id: String,
+    original_id: String,
+    data: String,
+    reason: String,
+    delivery_count: usize,
+    failed_at: String,
+}
+
+impl From<DeadLetter> for DeadLetterContent {
+    fn from(dead_letter: DeadLetter) -> Self {
+        DeadLetterContent {
+            id: dead_letter.id,
+            original_id: dead_letter.original_id,
+            data: dead_letter.data,
+            reason: dead_letter.reason,
+            delivery_count: dead_letter.delivery_count,
+            failed_at: dead_letter.failed_at.to_rfc3339(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListDeadLetterQueryParams {
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListDeadLetterResponse {
+    count: usize,
+    contents: Vec<DeadLetterContent>,
+}
+
+pub async fn list_dead_letters(
+    queue_service: web::Data<Arc<dyn QueueServiceInterface>>,
+    auth: AuthenticationContext,
+    queue_name: web::Path<String>,
+    query_params: web::Query<ListDeadLetterQueryParams>,
+) -> HttpResponse {
+    if let Err(e) = derive_user_id(auth) {
+        return derive_authentication_middleware_error(e);
+    }
+
+    let mut limit = 25;
+    if let Some(raw_limit) = query_params.limit {
+        limit = raw_limit;
+    }
+
+    let result = match queue_service
+        .list_dead_letters(ListDeadLetterParams {
+            queue_name: queue_name.into_inner(),
+            limit,
+        })
+        .await
+    {
+        Ok(result) => result,
+        Err(e) => match e {
+            ListDeadLetterError::Redis(_) => {
+                return HttpResponse::InternalServerError().json(HttpErrorResponse::new(
+                    QueueErrorCode::InternalServerError.to_string(),
+                    vec![e.to_string()],
+                ))
+            }
+        },
+    };
+
+    HttpResponse::Ok().json(ListDeadLetterResponse {
+        count: result.count,
+        contents: result
+            .contents
+            .into_iter()
+            .map(DeadLetterContent::from)
+            .collect(),
+    })
+}
+
+pub async fn replay_dead_letter(
+    queue_service: web::Data<Arc<dyn QueueServiceInterface>>,
+    auth: AuthenticationContext,
+    path: web::Path<(String, String)>,
+) -> HttpResponse {
+    if let Err(e) = derive_user_id(auth) {
+        return derive_authentication_middleware_error(e);
+    }
+
+    let (queue_name, dead_letter_id) = path.into_inner();
+
+    if let Err(e) = queue_service
+        .replay_dead_letter(queue_name, dead_letter_id)
+        .await
+    {
+        match e {
+            ReplayDeadLetterError::DeadLetterNotFound => {
+                return HttpResponse::NotFound().json(HttpErrorResponse::new(
+                    QueueErrorCode::DeadLetterNotFound.to_string(),
+                    vec![e.to_string()],
+                ))
+            }
+            ReplayDeadLetterError::Redis(_) => {
+                return HttpResponse::InternalServerError().json(HttpErrorResponse::new(
+                    QueueErrorCode::InternalServerError.to_string(),
+                    vec![e.to_string()],
+                ))
+            }
+        }
+    }
+
+    HttpResponse::NoContent().finish()
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PurgeDeadLetterResponse {
+    count: usize,
+}
+
+pub async fn purge_dead_letters(
+    queue_service: web::Data<Arc<dyn QueueServiceInterface>>,
+    auth: AuthenticationContext,
+    queue_name: web::Path<String>,
+) -> HttpResponse {
+    if let Err(e) = derive_user_id(auth) {
+        return derive_authentication_middleware_error(e);
+    }
+
+    let count = match queue_service
+        .purge_dead_letters(queue_name.into_inner())
+        .await
+    {
+        Ok(count) => count,
+        Err(e) => match e {
+            PurgeDeadLetterError::Redis(_) => {
+                return HttpResponse::InternalServerError().json(HttpErrorResponse::new(
+                    QueueErrorCode::InternalServerError.to_string(),
+                    vec![e.to_string()],
+                ))
+            }
+        },
+    };
+
+    HttpResponse::Ok().json(PurgeDeadLetterResponse { count })
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceQueueMessageContent {
+    id: String,
+    created_at: Option<String>,
+    is_pending: bool,
+    data: String,
+    envelope: Option<Envelope<DeviceSynchronizationParams>>,
+}
+
+impl From<DeviceQueueMessage> for DeviceQueueMessageContent {
+    fn from(message: DeviceQueueMessage) -> Self {
+        DeviceQueueMessageContent {
+            created_at: message_timestamp(&message.id).map(|date| date.to_rfc3339()),
+            id: message.id,
+            is_pending: message.is_pending,
+            data: message.data,
+            envelope: message.envelope,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InspectDeviceQueueQueryParams {
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InspectDeviceQueueResponse {
+    queue_name: String,
+    length: usize,
+    pending_count: usize,
+    last_delivered_id: Option<String>,
+    oldest_unacked_at: Option<String>,
+    oldest_unacked_age_seconds: Option<i64>,
+    messages: Vec<DeviceQueueMessageContent>,
+}
+
+pub async fn inspect_device_queue(
+    queue_service: web::Data<Arc<dyn QueueServiceInterface>>,
+    auth: AuthenticationContext,
+    device_id: web::Path<i32>,
+    query_params: web::Query<InspectDeviceQueueQueryParams>,
+) -> HttpResponse {
+    if let Err(e) = derive_user_id(auth) {
+        return derive_authentication_middleware_error(e);
+    }
+
+    let device_id = device_id.into_inner();
+
+    let mut limit = 25;
+    if let Some(raw_limit) = query_params.limit {
+        limit = raw_limit;
+    }
+
+    let result = match queue_service
+        .inspect_device_queue(InspectDeviceQueueParams { device_id, limit })
+        .await
+    {
+        Ok(result) => result,
+        Err(e) => match e {
+            InspectDeviceQueueError::DeviceNotFound => {
+                return HttpResponse::NotFound().json(HttpErrorResponse::new(
+                    QueueErrorCode::DeviceNotFound.to_string(),
+                    vec![e.to_string()],
+                ))
+            }
+            InspectDeviceQueueError::Redis(_) | InspectDeviceQueueError::Database(_) => {
+                return HttpResponse::InternalServerError().json(HttpErrorResponse::new(
+                    QueueErrorCode::InternalServerError.to_string(),
+                    vec![e.to_string()],
+                ))
+            }
+        },
+    };
+
+    let oldest_unacked_at = result
+        .info
+        .oldest_pending_id
+        .as_deref()
+        .and_then(message_timestamp);
+
+    HttpResponse::Ok().json(InspectDeviceQueueResponse {
+        queue_name: result.queue_name,
+        length: result.info.length,
+        pending_count: result.info.pending_count,
+        last_delivered_id: result.info.last_delivered_id,
+        oldest_unacked_at: oldest_unacked_at.map(|date| date.to_rfc3339()),
+        oldest_unacked_age_seconds: oldest_unacked_at
+            .map(|date| (chrono::Utc::now() - date).num_seconds()),
+        messages: result
+            .messages
+            .into_iter()
+            .map(DeviceQueueMessageContent::from)
+            .collect(),
+    })
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PurgeDeviceQueueResponse {
+    count: usize,
+}
+
+pub async fn purge_device_queue(
+    queue_service: web::Data<Arc<dyn QueueServiceInterface>>,
+    auth: AuthenticationContext,
+    device_id: web::Path<i32>,
+) -> HttpResponse {
+    if let Err(e) = derive_user_id(auth) {
+        return derive_authentication_middleware_error(e);
+    }
+
+    let device_id = device_id.into_inner();
+
+    let count = match queue_service.purge_device_queue(device_id).await {
+        Ok(count) => count,
+        Err(e) => match e {
+            PurgeDeviceQueueError::DeviceNotFound => {
+                return HttpResponse::NotFound().json(HttpErrorResponse::new(
+                    QueueErrorCode::DeviceNotFound.to_string(),
+                    vec![e.to_string()],
+                ))
+            }
+            PurgeDeviceQueueError::Redis(_) | PurgeDeviceQueueError::Database(_) => {
+                return HttpResponse::InternalServerError().json(HttpErrorResponse::new(
+                    QueueErrorCode::InternalServerError.to_string(),
+                    vec![e.to_string()],
+                ))
+            }
+        },
+    };
+
+    HttpResponse::Ok().json(PurgeDeviceQueueResponse { count })
+}
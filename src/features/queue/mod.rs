@@ -0,0 +1,4 @@
+pub mod error;
+pub mod domain;
+pub mod service;
+pub mod http;
@@ -0,0 +1,208 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    sync::Arc,
+};
+
+use async_trait::async_trait;
+
+use crate::{
+    features::{
+        announcement::{device_queue_name, DeviceSynchronizationParams},
+        device::DeviceRepositoryInterface,
+    },
+    queue::{
+        Consumer, ConsumerInterface, DeadLetter, DeadLetterQueue, Envelope, Producer,
+        ProducerInterface,
+    },
+};
+
+use super::{
+    domain::{DeviceQueueInspection, DeviceQueueMessage},
+    error::{
+        InspectDeviceQueueError, ListDeadLetterError, PurgeDeadLetterError,
+        PurgeDeviceQueueError, ReplayDeadLetterError,
+    },
+};
+
+/// The inspection only looks at the group, it never reads as a consumer
+const INSPECTION_CONSUMER_NAME: &str = "dashboard-inspection";
+
+pub struct ListDeadLetterParams {
+    pub queue_name: String,
+    pub limit: usize,
+}
+
+pub struct ListDeadLetterResult {
+    pub count: usize,
+    pub contents: Vec<DeadLetter>,
+}
+
+pub struct InspectDeviceQueueParams {
+    pub device_id: i32,
+    pub limit: usize,
+}
+
+#[async_trait]
+pub trait QueueServiceInterface: Send + Sync + 'static {
+    async fn list_dead_letters(
+        &self,
+        params: ListDeadLetterParams,
+    ) -> Result<ListDeadLetterResult, ListDeadLetterError>;
+    async fn replay_dead_letter(
+        &self,
+        queue_name: String,
+        dead_letter_id: String,
+    ) -> Result<(), ReplayDeadLetterError>;
+    async fn purge_dead_letters(&self, queue_name: String) -> Result<usize, PurgeDeadLetterError>;
+    async fn inspect_device_queue(
+        &self,
+        params: InspectDeviceQueueParams,
+    ) -> Result<DeviceQueueInspection, InspectDeviceQueueError>;
+    async fn purge_device_queue(&self, device_id: i32) -> Result<usize, PurgeDeviceQueueError>;
+}
+
+pub struct QueueService {
+    _redis: deadpool_redis::Pool,
+    _device_repository: Arc<dyn DeviceRepositoryInterface + Send + Sync + 'static>,
+}
+
+impl QueueService {
+    pub fn new(
+        _redis: deadpool_redis::Pool,
+        _device_repository: Arc<dyn DeviceRepositoryInterface + Send + Sync + 'static>,
+    ) -> Self {
+        QueueService {
+            _redis,
+            _device_repository,
+        }
+    }
+
+    fn device_queue_consumer(&self, device_id: i32) -> Consumer {
+        Consumer::new(
+            self._redis.clone(),
+            device_queue_name(device_id),
+            INSPECTION_CONSUMER_NAME.to_string(),
+        )
+    }
+}
+
+#[async_trait]
+impl QueueServiceInterface for QueueService {
+    async fn list_dead_letters(
+        &self,
+        params: ListDeadLetterParams,
+    ) -> Result<ListDeadLetterResult, ListDeadLetterError> {
+        let dead_letter_queue = DeadLetterQueue::new(self._redis.clone(), params.queue_name);
+
+        let count = dead_letter_queue
+            .count()
+            .await
+            .map_err(|e| ListDeadLetterError::Redis(e.to_string()))?;
+        let contents = dead_letter_queue
+            .list(params.limit)
+            .await
+            .map_err(|e| ListDeadLetterError::Redis(e.to_string()))?;
+
+        Ok(ListDeadLetterResult { count, contents })
+    }
+
+    async fn replay_dead_letter(
+        &self,
+        queue_name: String,
+        dead_letter_id: String,
+    ) -> Result<(), ReplayDeadLetterError> {
+        let dead_letter_queue = DeadLetterQueue::new(self._redis.clone(), queue_name.clone());
+
+        let dead_letter = match dead_letter_queue
+            .find(dead_letter_id.clone())
+            .await
+            .map_err(|e| ReplayDeadLetterError::Redis(e.to_string()))?
+        {
+            Some(dead_letter) => dead_letter,
+            None => return Err(ReplayDeadLetterError::DeadLetterNotFound),
+        };
+
+        // The message is pushed as a new entry, its delivery count starts over
+        let mut payload: BTreeMap<String, String> = BTreeMap::new();
+        payload.insert("data".into(), dead_letter.data);
+
+        let producer = Producer::new(self._redis.clone(), queue_name);
+        if let Err(e) = producer.push(payload).await {
+            return Err(ReplayDeadLetterError::Redis(e.to_string()));
+        }
+
+        dead_letter_queue
+            .delete(dead_letter_id)
+            .await
+            .map_err(|e| ReplayDeadLetterError::Redis(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn purge_dead_letters(&self, queue_name: String) -> Result<usize, PurgeDeadLetterError> {
+        let dead_letter_queue = DeadLetterQueue::new(self._redis.clone(), queue_name);
+
+        dead_letter_queue
+            .purge()
+            .await
+            .map_err(|e| PurgeDeadLetterError::Redis(e.to_string()))
+    }
+
+    async fn inspect_device_queue(
+        &self,
+        params: InspectDeviceQueueParams,
+    ) -> Result<DeviceQueueInspection, InspectDeviceQueueError> {
+        if let Err(e) = self._device_repository.find_one(params.device_id).await {
+            match e {
+                sqlx::Error::RowNotFound => return Err(InspectDeviceQueueError::DeviceNotFound),
+                _ => return Err(InspectDeviceQueueError::Database(e)),
+            }
+        }
+
+        let mut consumer = self.device_queue_consumer(params.device_id);
+
+        let info = consumer
+            .info()
+            .await
+            .map_err(|e| InspectDeviceQueueError::Redis(e.to_string()))?;
+        let pending_ids: HashSet<String> = consumer
+            .pending_message_ids(params.limit)
+            .await
+            .map_err(|e| InspectDeviceQueueError::Redis(e.to_string()))?
+            .into_iter()
+            .collect();
+        let messages = consumer
+            .read_latest_raw(params.limit)
+            .await
+            .map_err(|e| InspectDeviceQueueError::Redis(e.to_string()))?
+            .into_iter()
+            .map(|(id, data)| DeviceQueueMessage {
+                envelope: serde_json::from_str::<Envelope<DeviceSynchronizationParams>>(&data)
+                    .ok(),
+                is_pending: pending_ids.contains(&id),
+                id,
+                data,
+            })
+            .collect();
+
+        Ok(DeviceQueueInspection {
+            queue_name: device_queue_name(params.device_id),
+            info,
+            messages,
+        })
+    }
+
+    async fn purge_device_queue(&self, device_id: i32) -> Result<usize, PurgeDeviceQueueError> {
+        if let Err(e) = self._device_repository.find_one(device_id).await {
+            match e {
+                sqlx::Error::RowNotFound => return Err(PurgeDeviceQueueError::DeviceNotFound),
+                _ => return Err(PurgeDeviceQueueError::Database(e)),
+            }
+        }
+
+        self.device_queue_consumer(device_id)
+            .purge()
+            .await
+            .map_err(|e| PurgeDeviceQueueError::Redis(e.to_string()))
+    }
+}
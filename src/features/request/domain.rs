@@ -10,13 +10,85 @@ pub struct Request {
     pub user_id: i32,
     pub user_name: String,
     pub description: String,
-    pub approved_by_lsc: Option<bool>,
-    pub lsc_approver: Option<i32>,
-    pub approved_by_bm: Option<bool>,
-    pub bm_approver: Option<i32>,
+    pub approval_steps: Vec<RequestApprovalStep>,
     pub created_at: chrono::DateTime<chrono::Utc>,
+    pub withdrawn_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
+impl Request {
+    pub fn status(&self) -> RequestStatus {
+        if self.withdrawn_at.is_some() {
+            return RequestStatus::Withdrawn;
+        }
+
+        RequestStatus::from_steps(&self.approval_steps)
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.status() == RequestStatus::Pending
+    }
+
+    /// The decision of the first stage naming the role, or the outcome of the whole request when no stage
+    /// names it. Kept for clients of the former LSC and BM approval columns.
+    pub fn approval_by_role(&self, role: &str) -> Option<bool> {
+        if let Some(step) = self
+            .approval_steps
+            .iter()
+            .find(|step| step.roles.iter().any(|r| r == role))
+        {
+            return step.approved;
+        }
+
+        match RequestStatus::from_steps(&self.approval_steps) {
+            RequestStatus::Approved => Some(true),
+            RequestStatus::Rejected => Some(false),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RequestStatus {
+    Pending,
+    Approved,
+    Rejected,
+    Withdrawn,
+}
+
+impl RequestStatus {
+    /// A request is rejected as soon as one stage rejects it and approved once every stage approved it
+    pub fn from_steps(steps: &[RequestApprovalStep]) -> Self {
+        if steps.iter().any(|step| step.approved == Some(false)) {
+            RequestStatus::Rejected
+        } else if steps.iter().all(|step| step.approved == Some(true)) {
+            RequestStatus::Approved
+        } else {
+            RequestStatus::Pending
+        }
+    }
+
+    pub fn value(&self) -> &'static str {
+        match self {
+            RequestStatus::Pending => "pending",
+            RequestStatus::Approved => "approved",
+            RequestStatus::Rejected => "rejected",
+            RequestStatus::Withdrawn => "withdrawn",
+        }
+    }
+}
+
+/// One stage of the approval workflow of a request, copied from the workflow when the request was created
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RequestApprovalStep {
+    pub id: i32,
+    pub position: i32,
+    pub roles: Vec<String>,
+    pub approved: Option<bool>,
+    pub approver_id: Option<i32>,
+    pub on_behalf_of: Option<i32>,
+    pub approved_at: Option<chrono::DateTime<chrono::Utc>>,
+}
 
 #[derive(Debug,Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -24,6 +96,9 @@ pub struct RequestMetadata {
     #[serde(with = "ts_seconds_option")]
     pub extended_end_date: Option<chrono::DateTime<chrono::Utc>>,
     pub new_device_ids: Option<Vec<i32>>,
+    pub new_title: Option<String>,
+    pub new_notes: Option<String>,
+    pub new_media_id: Option<i32>,
 }
 
 impl RequestMetadata {
@@ -31,6 +106,9 @@ impl RequestMetadata {
         RequestMetadata {
             extended_end_date: None,
             new_device_ids: None,
+            new_title: None,
+            new_notes: None,
+            new_media_id: None,
         }
     }
 
@@ -43,6 +121,21 @@ impl RequestMetadata {
         self.new_device_ids = Some(new_device_ids);
         self
     }
+
+    pub fn new_title(mut self, new_title: String) -> Self {
+        self.new_title = Some(new_title);
+        self
+    }
+
+    pub fn new_notes(mut self, new_notes: String) -> Self {
+        self.new_notes = Some(new_notes);
+        self
+    }
+
+    pub fn new_media_id(mut self, new_media_id: i32) -> Self {
+        self.new_media_id = Some(new_media_id);
+        self
+    }
 }
 
 #[derive(Deserialize)]
@@ -50,13 +143,222 @@ impl RequestMetadata {
 pub struct RawRequestMetadata {
     pub extended_end_date: Option<String>,
     pub new_device_ids: Option<Vec<i32>>,
+    pub new_title: Option<String>,
+    pub new_notes: Option<String>,
+    pub new_media_id: Option<i32>,
+}
+
+/// The user deciding on a request, along with the users who delegated their approval rights to them
+#[derive(Debug)]
+pub struct RequestApprover {
+    pub id: i32,
+    pub role: String,
+    pub delegators: Vec<RequestApproverDelegator>,
+}
+
+#[derive(Debug)]
+pub struct RequestApproverDelegator {
+    pub user_id: i32,
+    pub role: String,
 }
 
+/// The steps an approver decides on, along with the status of the request once the decision is recorded
+#[derive(Debug)]
 pub struct RequestApproval {
-    pub approved_by_lsc: Option<bool>,
-    pub approved_by_bm: Option<bool>,
-    pub lsc_approver: Option<i32>,
-    pub bm_approver: Option<i32>,
+    pub step_ids: Vec<i32>,
+    pub approved: bool,
+    pub approver_id: i32,
+    pub on_behalf_of: Option<i32>,
+    pub reason: Option<String>,
+    pub status: RequestStatus,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum RequestApprovalDecisionError {
+    AlreadyDecided,
+    AlreadyApprovedByApprover,
+    RoleNotAllowed,
+    RejectionReasonRequired,
+}
+
+/// Applies the decision of an approver to the earliest stage that is still waiting. Stages are decided in
+/// order of their position, stages sharing a position can be decided in any order. A role named on every
+/// remaining stage decides all of them at once, the way an admin stands in for both LSC and BM. Otherwise
+/// the rights of a single person can only be used on one stage of a request, by themselves or by a
+/// delegate, and a rejection has to be explained. The own role of the approver is tried first, then the
+/// roles delegated to them.
+pub fn decide_request_approval(
+    steps: &[RequestApprovalStep],
+    approver: &RequestApprover,
+    approved: bool,
+    reason: Option<String>,
+) -> Result<RequestApproval, RequestApprovalDecisionError> {
+    let reason = reason
+        .map(|reason| reason.trim().to_string())
+        .filter(|reason| !reason.is_empty());
+    if !approved && reason.is_none() {
+        return Err(RequestApprovalDecisionError::RejectionReasonRequired);
+    }
+
+    if RequestStatus::from_steps(steps) != RequestStatus::Pending {
+        return Err(RequestApprovalDecisionError::AlreadyDecided);
+    }
+
+    let has_decided = |user_id: i32| {
+        steps.iter().any(|step| {
+            step.approver_id == Some(user_id) || step.on_behalf_of == Some(user_id)
+        })
+    };
+    let waiting_steps: Vec<&RequestApprovalStep> =
+        steps.iter().filter(|step| step.approved.is_none()).collect();
+    let current_position = match waiting_steps.iter().map(|step| step.position).min() {
+        Some(position) => position,
+        None => return Err(RequestApprovalDecisionError::AlreadyDecided),
+    };
+
+    let capacities = std::iter::once((&approver.role, None)).chain(
+        approver
+            .delegators
+            .iter()
+            .map(|delegator| (&delegator.role, Some(delegator.user_id))),
+    );
+
+    let mut is_role_allowed = false;
+    let mut decision = None;
+    for (role, delegator_id) in capacities {
+        let current_step = match waiting_steps
+            .iter()
+            .find(|step| step.position == current_position && step.roles.contains(role))
+        {
+            Some(step) => step,
+            None => continue,
+        };
+        is_role_allowed = true;
+
+        if has_decided(delegator_id.unwrap_or(approver.id)) {
+            continue;
+        }
+
+        let step_ids = if waiting_steps.iter().all(|step| step.roles.contains(role)) {
+            waiting_steps.iter().map(|step| step.id).collect()
+        } else {
+            vec![current_step.id]
+        };
+        decision = Some((step_ids, delegator_id));
+        break;
+    }
+
+    let (step_ids, on_behalf_of) = match decision {
+        Some(decision) => decision,
+        None if is_role_allowed => {
+            return Err(RequestApprovalDecisionError::AlreadyApprovedByApprover)
+        }
+        None => return Err(RequestApprovalDecisionError::RoleNotAllowed),
+    };
+
+    let status = if !approved {
+        RequestStatus::Rejected
+    } else if step_ids.len() == waiting_steps.len() {
+        RequestStatus::Approved
+    } else {
+        RequestStatus::Pending
+    };
+
+    Ok(RequestApproval {
+        step_ids,
+        approved,
+        approver_id: approver.id,
+        on_behalf_of,
+        reason,
+        status,
+    })
+}
+
+/// Only the author of a request and the users who can approve its current stage, in their own role or on
+/// behalf of a delegator, take part in its comment thread
+pub fn can_comment_on_request(
+    author_id: i32,
+    steps: &[RequestApprovalStep],
+    user: &RequestApprover,
+) -> bool {
+    if user.id == author_id {
+        return true;
+    }
+
+    let waiting_steps: Vec<&RequestApprovalStep> =
+        steps.iter().filter(|step| step.approved.is_none()).collect();
+    let current_position = match waiting_steps.iter().map(|step| step.position).min() {
+        Some(position) => position,
+        None => return false,
+    };
+
+    let mut roles = std::iter::once(&user.role)
+        .chain(user.delegators.iter().map(|delegator| &delegator.role));
+    roles.any(|role| {
+        waiting_steps
+            .iter()
+            .any(|step| step.position == current_position && step.roles.contains(role))
+    })
+}
+
+pub struct RequestComment {
+    pub id: i32,
+    pub request_id: i32,
+    pub user_id: i32,
+    pub user_name: String,
+    pub approval_step_id: Option<i32>,
+    pub body: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+pub struct ApprovalDelegation {
+    pub id: i32,
+    pub delegator_id: i32,
+    pub delegator_name: String,
+    pub delegate_id: i32,
+    pub delegate_name: String,
+    pub start_date: chrono::DateTime<chrono::Utc>,
+    pub end_date: chrono::DateTime<chrono::Utc>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl ApprovalDelegation {
+    /// The end date is inclusive, a delegation lasts until the end of that day
+    pub fn is_active_at(&self, at: chrono::DateTime<chrono::Utc>) -> bool {
+        self.start_date <= at && at < self.end_date + chrono::Duration::days(1)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ApprovalWorkflow {
+    pub id: i32,
+    pub building_id: Option<i32>,
+    pub action: Option<RequestActionType>,
+    pub stages: Vec<ApprovalWorkflowStage>,
+}
+
+impl ApprovalWorkflow {
+    /// The workflow without a building or action is the fallback for every request
+    pub fn is_default(&self) -> bool {
+        self.building_id.is_none() && self.action.is_none()
+    }
+}
+
+/// Only users whose role is named on a stage of some workflow have approval rights to delegate
+pub fn can_delegate_approval(workflows: &[ApprovalWorkflow], role: &str) -> bool {
+    workflows.iter().any(|workflow| {
+        workflow
+            .stages
+            .iter()
+            .any(|stage| stage.roles.iter().any(|stage_role| stage_role == role))
+    })
+}
+
+/// Stages are approved in order of their position, stages sharing a position can be approved in any order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalWorkflowStage {
+    pub position: i32,
+    pub roles: Vec<String>,
 }
 
 #[derive(Debug, sqlx::Type, PartialEq, Clone, Serialize, Deserialize)]
@@ -67,6 +369,7 @@ pub enum RequestActionType {
     ExtendDate,
     Delete,
     ChangeDevices,
+    ChangeContent,
 }
 
 impl RequestActionType {
@@ -76,6 +379,7 @@ impl RequestActionType {
             RequestActionType::ExtendDate => "Extend Date",
             RequestActionType::Delete => "Delete",
             RequestActionType::ChangeDevices => "Change Devices",
+            RequestActionType::ChangeContent => "Change Content",
         }
     }
 
@@ -85,6 +389,7 @@ impl RequestActionType {
             RequestActionType::ExtendDate => "extend_date",
             RequestActionType::Delete => "delete",
             RequestActionType::ChangeDevices => "change_devices",
+            RequestActionType::ChangeContent => "change_content",
         }
     }
 }
@@ -99,6 +404,23 @@ pub enum RequestErrorCode {
     InvalidAnnouncementStatus,
     InvalidExtendedEndDate,
     InvalidDeviceIds,
+    MediaNotFound,
+    RequestWithdrawn,
+    RequestNotPending,
+    NotRequestOwner,
+    ApprovalWorkflowNotFound,
+    InvalidApprovalWorkflow,
+    BuildingNotFound,
+    DefaultApprovalWorkflowRequired,
+    ApprovalWorkflowNotConfigured,
+    RejectionReasonRequired,
+    ApprovalDelegationNotFound,
+    NotDelegationOwner,
+    DelegateNotFound,
+    InvalidDelegate,
+    DelegatorNotApprover,
+    InvalidDateRange,
+    NotAllowedToComment,
     InternalServerError,
 }
 
@@ -114,6 +436,31 @@ impl std::fmt::Display for RequestErrorCode {
             RequestErrorCode::InvalidAnnouncementStatus => write!(f, "INVALID_ANNOUNCEMENT_STATUS"),
             RequestErrorCode::InvalidExtendedEndDate => write!(f, "INVALID_EXTENDED_END_DATE"),
             RequestErrorCode::InvalidDeviceIds => write!(f, "INVALID_DEVICE_IDS"),
+            RequestErrorCode::MediaNotFound => write!(f, "MEDIA_NOT_FOUND"),
+            RequestErrorCode::RequestWithdrawn => write!(f, "REQUEST_WITHDRAWN"),
+            RequestErrorCode::RequestNotPending => write!(f, "REQUEST_NOT_PENDING"),
+            RequestErrorCode::NotRequestOwner => write!(f, "NOT_REQUEST_OWNER"),
+            RequestErrorCode::ApprovalWorkflowNotFound => {
+                write!(f, "APPROVAL_WORKFLOW_NOT_FOUND")
+            }
+            RequestErrorCode::InvalidApprovalWorkflow => write!(f, "INVALID_APPROVAL_WORKFLOW"),
+            RequestErrorCode::BuildingNotFound => write!(f, "BUILDING_NOT_FOUND"),
+            RequestErrorCode::RejectionReasonRequired => write!(f, "REJECTION_REASON_REQUIRED"),
+            RequestErrorCode::ApprovalWorkflowNotConfigured => {
+                write!(f, "APPROVAL_WORKFLOW_NOT_CONFIGURED")
+            }
+            RequestErrorCode::ApprovalDelegationNotFound => {
+                write!(f, "APPROVAL_DELEGATION_NOT_FOUND")
+            }
+            RequestErrorCode::NotDelegationOwner => write!(f, "NOT_DELEGATION_OWNER"),
+            RequestErrorCode::DelegateNotFound => write!(f, "DELEGATE_NOT_FOUND"),
+            RequestErrorCode::InvalidDelegate => write!(f, "INVALID_DELEGATE"),
+            RequestErrorCode::DelegatorNotApprover => write!(f, "DELEGATOR_NOT_APPROVER"),
+            RequestErrorCode::InvalidDateRange => write!(f, "INVALID_DATE_RANGE"),
+            RequestErrorCode::NotAllowedToComment => write!(f, "NOT_ALLOWED_TO_COMMENT"),
+            RequestErrorCode::DefaultApprovalWorkflowRequired => {
+                write!(f, "DEFAULT_APPROVAL_WORKFLOW_REQUIRED")
+            }
             RequestErrorCode::InternalServerError => write!(f, "INTERNAL_SERVER_ERROR"),
         }
     }
@@ -126,6 +473,8 @@ pub enum CreateRequestError {
     InvalidExtendedEndDate(&'static str),
     InvalidAnnouncementStatus(&'static str),
     InvalidDeviceIds(&'static str),
+    MediaNotFound(&'static str),
+    ApprovalWorkflowNotConfigured(&'static str),
     InternalServerError,
 }
 
@@ -137,6 +486,8 @@ impl std::fmt::Display for CreateRequestError {
             CreateRequestError::InvalidExtendedEndDate(message) => write!(f, "{}", message),
             CreateRequestError::InvalidAnnouncementStatus(message) => write!(f, "{}", message),
             CreateRequestError::InvalidDeviceIds(message) => write!(f, "{}", message),
+            CreateRequestError::MediaNotFound(message) => write!(f, "{}", message),
+            CreateRequestError::ApprovalWorkflowNotConfigured(message) => write!(f, "{}", message),
             CreateRequestError::InternalServerError => write!(f, "Internal Server Error"),
         }
     }
@@ -163,6 +514,8 @@ pub enum UpdateRequestApprovalError {
     AnnouncementNotFound(String),
     RequestAlreadyApproved(String),
     InvalidAnnouncementStatus(String),
+    RequestWithdrawn(String),
+    RejectionReasonRequired(String),
     InternalServerError,
 }
 
@@ -177,11 +530,225 @@ impl std::fmt::Display for UpdateRequestApprovalError {
             UpdateRequestApprovalError::InvalidAnnouncementStatus(message) => {
                 write!(f, "{}", message)
             }
+            UpdateRequestApprovalError::RequestWithdrawn(message) => write!(f, "{}", message),
+            UpdateRequestApprovalError::RejectionReasonRequired(message) => {
+                write!(f, "{}", message)
+            }
             UpdateRequestApprovalError::InternalServerError => write!(f, "Internal Server Error"),
         }
     }
 }
 
+impl UpdateRequestApprovalError {
+    pub fn code(&self) -> RequestErrorCode {
+        match self {
+            UpdateRequestApprovalError::RequestNotFound(_) => RequestErrorCode::RequestNotFound,
+            UpdateRequestApprovalError::UserNotFound(_) => RequestErrorCode::UserNotFound,
+            UpdateRequestApprovalError::UserForbiddenToApprove(_) => {
+                RequestErrorCode::UserForbiddenToApprove
+            }
+            UpdateRequestApprovalError::AnnouncementNotFound(_) => {
+                RequestErrorCode::AnnouncementNotFound
+            }
+            UpdateRequestApprovalError::RequestAlreadyApproved(_) => {
+                RequestErrorCode::RequestAlreadyApproved
+            }
+            UpdateRequestApprovalError::InvalidAnnouncementStatus(_) => {
+                RequestErrorCode::InvalidAnnouncementStatus
+            }
+            UpdateRequestApprovalError::RequestWithdrawn(_) => RequestErrorCode::RequestWithdrawn,
+            UpdateRequestApprovalError::RejectionReasonRequired(_) => {
+                RequestErrorCode::RejectionReasonRequired
+            }
+            UpdateRequestApprovalError::InternalServerError => {
+                RequestErrorCode::InternalServerError
+            }
+        }
+    }
+}
+
+/// The outcome of one request of a bulk approval, the requests are decided independently of each other
+pub struct BulkRequestApprovalResult {
+    pub request_id: i32,
+    pub result: Result<(), UpdateRequestApprovalError>,
+}
+
+#[derive(Debug)]
+pub enum BulkUpdateRequestApprovalError {
+    RejectionReasonRequired(&'static str),
+}
+
+impl std::fmt::Display for BulkUpdateRequestApprovalError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BulkUpdateRequestApprovalError::RejectionReasonRequired(message) => {
+                write!(f, "{}", message)
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum WithdrawRequestError {
+    RequestNotFound(&'static str),
+    NotRequestOwner(&'static str),
+    RequestNotPending(&'static str),
+    InternalServerError,
+}
+
+impl std::fmt::Display for WithdrawRequestError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WithdrawRequestError::RequestNotFound(message) => write!(f, "{}", message),
+            WithdrawRequestError::NotRequestOwner(message) => write!(f, "{}", message),
+            WithdrawRequestError::RequestNotPending(message) => write!(f, "{}", message),
+            WithdrawRequestError::InternalServerError => write!(f, "Internal Server Error"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ListRequestCommentError {
+    RequestNotFound(&'static str),
+    InternalServerError,
+}
+
+impl std::fmt::Display for ListRequestCommentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ListRequestCommentError::RequestNotFound(message) => write!(f, "{}", message),
+            ListRequestCommentError::InternalServerError => write!(f, "Internal Server Error"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum CreateRequestCommentError {
+    RequestNotFound(&'static str),
+    NotAllowedToComment(&'static str),
+    InternalServerError,
+}
+
+impl std::fmt::Display for CreateRequestCommentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CreateRequestCommentError::RequestNotFound(message) => write!(f, "{}", message),
+            CreateRequestCommentError::NotAllowedToComment(message) => write!(f, "{}", message),
+            CreateRequestCommentError::InternalServerError => write!(f, "Internal Server Error"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ListApprovalWorkflowError {
+    InternalServerError,
+}
+
+impl std::fmt::Display for ListApprovalWorkflowError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ListApprovalWorkflowError::InternalServerError => write!(f, "Internal Server Error"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum SaveApprovalWorkflowError {
+    InvalidApprovalWorkflow(String),
+    BuildingNotFound(&'static str),
+    InternalServerError,
+}
+
+impl std::fmt::Display for SaveApprovalWorkflowError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SaveApprovalWorkflowError::InvalidApprovalWorkflow(message) => write!(f, "{}", message),
+            SaveApprovalWorkflowError::BuildingNotFound(message) => write!(f, "{}", message),
+            SaveApprovalWorkflowError::InternalServerError => write!(f, "Internal Server Error"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum DeleteApprovalWorkflowError {
+    ApprovalWorkflowNotFound(&'static str),
+    DefaultApprovalWorkflowRequired(&'static str),
+    InternalServerError,
+}
+
+impl std::fmt::Display for DeleteApprovalWorkflowError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DeleteApprovalWorkflowError::ApprovalWorkflowNotFound(message) => {
+                write!(f, "{}", message)
+            }
+            DeleteApprovalWorkflowError::DefaultApprovalWorkflowRequired(message) => {
+                write!(f, "{}", message)
+            }
+            DeleteApprovalWorkflowError::InternalServerError => write!(f, "Internal Server Error"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ListApprovalDelegationError {
+    InternalServerError,
+}
+
+impl std::fmt::Display for ListApprovalDelegationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ListApprovalDelegationError::InternalServerError => write!(f, "Internal Server Error"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum CreateApprovalDelegationError {
+    DelegateNotFound(&'static str),
+    InvalidDelegate(&'static str),
+    DelegatorNotApprover(&'static str),
+    InvalidDateRange(&'static str),
+    InternalServerError,
+}
+
+impl std::fmt::Display for CreateApprovalDelegationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CreateApprovalDelegationError::DelegateNotFound(message) => write!(f, "{}", message),
+            CreateApprovalDelegationError::InvalidDelegate(message) => write!(f, "{}", message),
+            CreateApprovalDelegationError::DelegatorNotApprover(message) => {
+                write!(f, "{}", message)
+            }
+            CreateApprovalDelegationError::InvalidDateRange(message) => write!(f, "{}", message),
+            CreateApprovalDelegationError::InternalServerError => {
+                write!(f, "Internal Server Error")
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum DeleteApprovalDelegationError {
+    ApprovalDelegationNotFound(&'static str),
+    NotDelegationOwner(&'static str),
+    InternalServerError,
+}
+
+impl std::fmt::Display for DeleteApprovalDelegationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DeleteApprovalDelegationError::ApprovalDelegationNotFound(message) => {
+                write!(f, "{}", message)
+            }
+            DeleteApprovalDelegationError::NotDelegationOwner(message) => write!(f, "{}", message),
+            DeleteApprovalDelegationError::InternalServerError => {
+                write!(f, "Internal Server Error")
+            }
+        }
+    }
+}
+
 pub enum BatchRejectRequestsFromAnnouncementIdsError {
     InternalServerError,
 }
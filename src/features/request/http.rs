@@ -3,15 +3,21 @@ use std::sync::Arc;
 use actix_web::{web, HttpResponse};
 use serde::{Deserialize, Serialize};
 
+use crate::features::announcement::parse_announcement_date_input;
 use crate::http::{
     derive_authentication_middleware_error, derive_user_id, validate_date_format,
     AuthenticationContext, HttpErrorResponse, API_VALIDATION_ERROR_CODE,
 };
 
 use super::{
-    CreateRequestError, CreateRequestParams, ListRequestError, ListRequestParams,
-    RequestActionType, RequestErrorCode, RequestMetadata, RequestServiceInterface,
-    UpdateRequestApprovalError, UpdateRequestApprovalParams,
+    ApprovalWorkflowStage, BulkUpdateRequestApprovalError, BulkUpdateRequestApprovalParams,
+    CreateApprovalDelegationError, CreateApprovalDelegationParams, CreateRequestCommentError,
+    CreateRequestCommentParams, CreateRequestError, CreateRequestParams,
+    DeleteApprovalDelegationError, DeleteApprovalWorkflowError, ListApprovalDelegationError,
+    ListApprovalWorkflowError, ListRequestCommentError, ListRequestError, ListRequestParams,
+    RequestActionType, RequestErrorCode, RequestMetadata, RequestServiceInterface, RequestStatus,
+    SaveApprovalWorkflowError, SaveApprovalWorkflowParams, UpdateRequestApprovalError,
+    UpdateRequestApprovalParams, WithdrawRequestError,
 };
 
 #[derive(Debug, Deserialize)]
@@ -23,6 +29,8 @@ pub struct ListRequestQueryParams {
     pub announcement_id: Option<i32>,
     pub user_id: Option<i32>,
     pub action_type: Option<RequestActionType>,
+    pub status: Option<RequestStatus>,
+    pub awaiting_role: Option<String>,
     pub approved_by_lsc: Option<bool>,
     pub approved_by_bm: Option<bool>,
 }
@@ -42,11 +50,14 @@ pub struct ListRequestContent {
     id: i32,
     announcement: ListRequestContentAnnouncement,
     author: ListRequestContentAuthor,
+    status: RequestStatus,
     approval_status: ListRequestContentApprovalStatus,
+    approval_steps: Vec<ListRequestContentApprovalStep>,
     action: ListRequestContentAction,
     metadata: RequestMetadata,
     description: String,
     created_at: String,
+    withdrawn_at: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -63,6 +74,7 @@ pub struct ListRequestContentAuthor {
     name: String,
 }
 
+/// Deprecated, derived from the approval steps for clients of the former LSC and BM approval columns
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ListRequestContentApprovalStatus {
@@ -70,6 +82,17 @@ pub struct ListRequestContentApprovalStatus {
     bm: Option<bool>,
 }
 
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListRequestContentApprovalStep {
+    position: i32,
+    roles: Vec<String>,
+    approved: Option<bool>,
+    approver_id: Option<i32>,
+    on_behalf_of: Option<i32>,
+    approved_at: Option<String>,
+}
+
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ListRequestContentAction {
@@ -104,6 +127,8 @@ pub async fn list_request(
             announcement_id: query_params.announcement_id,
             user_id: query_params.user_id,
             action_type: query_params.action_type.clone(),
+            status: query_params.status,
+            awaiting_role: query_params.awaiting_role.clone(),
             approved_by_lsc: query_params.approved_by_lsc,
             approved_by_bm: query_params.approved_by_bm,
         })
@@ -128,6 +153,11 @@ pub async fn list_request(
             .contents
             .into_iter()
             .map(|row| ListRequestContent {
+                status: row.status(),
+                approval_status: ListRequestContentApprovalStatus {
+                    lsc: row.approval_by_role("lsc"),
+                    bm: row.approval_by_role("bm"),
+                },
                 id: row.id,
                 announcement: ListRequestContentAnnouncement {
                     id: row.announcement_id,
@@ -137,10 +167,18 @@ pub async fn list_request(
                     id: row.user_id,
                     name: row.user_name,
                 },
-                approval_status: ListRequestContentApprovalStatus {
-                    lsc: row.approved_by_lsc,
-                    bm: row.approved_by_bm,
-                },
+                approval_steps: row
+                    .approval_steps
+                    .into_iter()
+                    .map(|step| ListRequestContentApprovalStep {
+                        position: step.position,
+                        roles: step.roles,
+                        approved: step.approved,
+                        approver_id: step.approver_id,
+                        on_behalf_of: step.on_behalf_of,
+                        approved_at: step.approved_at.map(|date| date.to_rfc3339()),
+                    })
+                    .collect(),
                 action: ListRequestContentAction {
                     label: row.action.clone().label().to_string(),
                     value: row.action.clone().value().to_string(),
@@ -148,6 +186,7 @@ pub async fn list_request(
                 metadata: row.metadata,
                 description: row.description,
                 created_at: row.created_at.to_rfc3339(),
+                withdrawn_at: row.withdrawn_at.map(|date| date.to_rfc3339()),
             })
             .collect(),
     })
@@ -157,6 +196,7 @@ pub async fn list_request(
 #[serde(rename_all = "camelCase")]
 pub struct UpdateRequestApprovalBody {
     action: String,
+    reason: Option<String>,
 }
 
 pub async fn update_request_approval(
@@ -186,6 +226,7 @@ pub async fn update_request_approval(
             request_id,
             approval,
             approver_id: user_id,
+            reason: body.reason.clone(),
         })
         .await
     {
@@ -226,6 +267,18 @@ pub async fn update_request_approval(
                     vec![message],
                 ))
             }
+            UpdateRequestApprovalError::RequestWithdrawn(message) => {
+                return HttpResponse::Conflict().json(HttpErrorResponse::new(
+                    RequestErrorCode::RequestWithdrawn.to_string(),
+                    vec![message],
+                ))
+            }
+            UpdateRequestApprovalError::RejectionReasonRequired(message) => {
+                return HttpResponse::BadRequest().json(HttpErrorResponse::new(
+                    RequestErrorCode::RejectionReasonRequired.to_string(),
+                    vec![message],
+                ))
+            }
             UpdateRequestApprovalError::InternalServerError => {
                 return HttpResponse::InternalServerError().json(HttpErrorResponse::new(
                     RequestErrorCode::InternalServerError.to_string(),
@@ -238,6 +291,99 @@ pub async fn update_request_approval(
     HttpResponse::NoContent().finish()
 }
 
+const BULK_REQUEST_APPROVAL_LIMIT: usize = 100;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkUpdateRequestApprovalBody {
+    request_ids: Vec<i32>,
+    action: String,
+    reason: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkUpdateRequestApprovalResponse {
+    results: Vec<BulkRequestApprovalResultContent>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkRequestApprovalResultContent {
+    request_id: i32,
+    status: String,
+    message: Option<String>,
+}
+
+pub async fn bulk_update_request_approval(
+    request_service: web::Data<Arc<dyn RequestServiceInterface + Send + Sync + 'static>>,
+    auth: AuthenticationContext,
+    body: web::Json<BulkUpdateRequestApprovalBody>,
+) -> HttpResponse {
+    let user_id = match derive_user_id(auth) {
+        Ok(id) => id,
+        Err(e) => return derive_authentication_middleware_error(e),
+    };
+    let approval = match body.action.as_str() {
+        "approve" => true,
+        "reject" => false,
+        _ => {
+            return HttpResponse::BadRequest().json(HttpErrorResponse::new(
+                API_VALIDATION_ERROR_CODE.to_string(),
+                vec!["action should be approve or reject".into()],
+            ))
+        }
+    };
+    if body.request_ids.is_empty() || body.request_ids.len() > BULK_REQUEST_APPROVAL_LIMIT {
+        return HttpResponse::BadRequest().json(HttpErrorResponse::new(
+            API_VALIDATION_ERROR_CODE.to_string(),
+            vec![format!(
+                "requestIds should contain between 1 and {} ids",
+                BULK_REQUEST_APPROVAL_LIMIT
+            )],
+        ));
+    }
+
+    let body = body.into_inner();
+    let results = match request_service
+        .bulk_update_request_approval(BulkUpdateRequestApprovalParams {
+            request_ids: body.request_ids,
+            approver_id: user_id,
+            approval,
+            reason: body.reason,
+        })
+        .await
+    {
+        Ok(results) => results,
+        Err(e) => match e {
+            BulkUpdateRequestApprovalError::RejectionReasonRequired(message) => {
+                return HttpResponse::BadRequest().json(HttpErrorResponse::new(
+                    RequestErrorCode::RejectionReasonRequired.to_string(),
+                    vec![message.to_string()],
+                ))
+            }
+        },
+    };
+
+    HttpResponse::Ok().json(BulkUpdateRequestApprovalResponse {
+        results: results
+            .into_iter()
+            .map(|item| match item.result {
+                Ok(_) => BulkRequestApprovalResultContent {
+                    request_id: item.request_id,
+                    status: "SUCCESS".into(),
+                    message: None,
+                },
+                Err(e) => BulkRequestApprovalResultContent {
+                    request_id: item.request_id,
+                    status: e.code().to_string(),
+                    message: Some(e.to_string()),
+                },
+            })
+            .collect(),
+    })
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CreateRequestBody {
@@ -246,6 +392,9 @@ pub struct CreateRequestBody {
     announcement_id: i32,
     extended_end_date: Option<String>,
     new_device_ids: Option<Vec<i32>>,
+    new_title: Option<String>,
+    new_notes: Option<String>,
+    new_media_id: Option<i32>,
 }
 
 pub async fn create_request(
@@ -276,12 +425,12 @@ pub async fn create_request(
             }
         };
 
-        let date = match validate_date_format(date.as_str(), "%Y-%m-%d") {
-            Ok(date) => date,
-            Err(_) => {
+        let date = match parse_announcement_date_input(date.clone()) {
+            Some(date) => date,
+            None => {
                 return HttpResponse::BadRequest().json(HttpErrorResponse::new(
                     API_VALIDATION_ERROR_CODE.to_string(),
-                    vec!["Date format must be yyyy-mm-dd".into()],
+                    vec!["Extended end date is invalid".into()],
                 ))
             }
         };
@@ -295,6 +444,23 @@ pub async fn create_request(
                     API_VALIDATION_ERROR_CODE.to_string(),
                     vec!["New device ids is required in the data".into()]));
         }
+    } else if body.action == RequestActionType::ChangeContent {
+        if body.new_title.is_none() && body.new_notes.is_none() && body.new_media_id.is_none() {
+            return HttpResponse::BadRequest().json(HttpErrorResponse::new(
+                API_VALIDATION_ERROR_CODE.to_string(),
+                vec!["At least one of new title, new notes, or new media id is required in the data".into()],
+            ));
+        }
+
+        if let Some(new_title) = &body.new_title {
+            params = params.new_title(new_title.clone());
+        }
+        if let Some(new_notes) = &body.new_notes {
+            params = params.new_notes(new_notes.clone());
+        }
+        if let Some(new_media_id) = body.new_media_id {
+            params = params.new_media_id(new_media_id);
+        }
     } else if body.action == RequestActionType::Create {
         return HttpResponse::BadRequest().json(HttpErrorResponse::new(
             API_VALIDATION_ERROR_CODE.to_string(),
@@ -334,6 +500,18 @@ pub async fn create_request(
                     vec![message.to_string()],
                 ))
             }
+            CreateRequestError::MediaNotFound(message) => {
+                return HttpResponse::NotFound().json(HttpErrorResponse::new(
+                    RequestErrorCode::MediaNotFound.to_string(),
+                    vec![message.to_string()],
+                ))
+            }
+            CreateRequestError::ApprovalWorkflowNotConfigured(message) => {
+                return HttpResponse::Conflict().json(HttpErrorResponse::new(
+                    RequestErrorCode::ApprovalWorkflowNotConfigured.to_string(),
+                    vec![message.to_string()],
+                ))
+            }
             CreateRequestError::InternalServerError => {
                 return HttpResponse::InternalServerError().json(HttpErrorResponse::new(
                     RequestErrorCode::InternalServerError.to_string(),
@@ -345,3 +523,525 @@ pub async fn create_request(
 
     HttpResponse::NoContent().finish()
 }
+
+pub async fn withdraw_request(
+    request_service: web::Data<Arc<dyn RequestServiceInterface + Send + Sync + 'static>>,
+    auth: AuthenticationContext,
+    request_id: web::Path<i32>,
+) -> HttpResponse {
+    let user_id = match derive_user_id(auth) {
+        Ok(id) => id,
+        Err(e) => return derive_authentication_middleware_error(e),
+    };
+
+    if let Err(e) = request_service
+        .withdraw_request(request_id.into_inner(), user_id)
+        .await
+    {
+        match e {
+            WithdrawRequestError::RequestNotFound(message) => {
+                return HttpResponse::NotFound().json(HttpErrorResponse::new(
+                    RequestErrorCode::RequestNotFound.to_string(),
+                    vec![message.to_string()],
+                ))
+            }
+            WithdrawRequestError::NotRequestOwner(message) => {
+                return HttpResponse::Forbidden().json(HttpErrorResponse::new(
+                    RequestErrorCode::NotRequestOwner.to_string(),
+                    vec![message.to_string()],
+                ))
+            }
+            WithdrawRequestError::RequestNotPending(message) => {
+                return HttpResponse::Conflict().json(HttpErrorResponse::new(
+                    RequestErrorCode::RequestNotPending.to_string(),
+                    vec![message.to_string()],
+                ))
+            }
+            WithdrawRequestError::InternalServerError => {
+                return HttpResponse::InternalServerError().json(HttpErrorResponse::new(
+                    RequestErrorCode::InternalServerError.to_string(),
+                    vec![WithdrawRequestError::InternalServerError.to_string()],
+                ))
+            }
+        }
+    }
+
+    HttpResponse::NoContent().finish()
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListRequestCommentResponse {
+    contents: Vec<RequestCommentContent>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestCommentContent {
+    id: i32,
+    author: ListRequestContentAuthor,
+    approval_step_id: Option<i32>,
+    body: String,
+    created_at: String,
+}
+
+pub async fn list_request_comments(
+    request_service: web::Data<Arc<dyn RequestServiceInterface + Send + Sync + 'static>>,
+    auth: AuthenticationContext,
+    request_id: web::Path<i32>,
+) -> HttpResponse {
+    if let Err(e) = derive_user_id(auth) {
+        return derive_authentication_middleware_error(e);
+    }
+
+    let comments = match request_service
+        .list_request_comments(request_id.into_inner())
+        .await
+    {
+        Ok(comments) => comments,
+        Err(e) => match e {
+            ListRequestCommentError::RequestNotFound(message) => {
+                return HttpResponse::NotFound().json(HttpErrorResponse::new(
+                    RequestErrorCode::RequestNotFound.to_string(),
+                    vec![message.to_string()],
+                ))
+            }
+            ListRequestCommentError::InternalServerError => {
+                return HttpResponse::InternalServerError().json(HttpErrorResponse::new(
+                    RequestErrorCode::InternalServerError.to_string(),
+                    vec![ListRequestCommentError::InternalServerError.to_string()],
+                ))
+            }
+        },
+    };
+
+    HttpResponse::Ok().json(ListRequestCommentResponse {
+        contents: comments
+            .into_iter()
+            .map(|comment| RequestCommentContent {
+                id: comment.id,
+                author: ListRequestContentAuthor {
+                    id: comment.user_id,
+                    name: comment.user_name,
+                },
+                approval_step_id: comment.approval_step_id,
+                body: comment.body,
+                created_at: comment.created_at.to_rfc3339(),
+            })
+            .collect(),
+    })
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateRequestCommentBody {
+    body: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateRequestCommentResponse {
+    id: i32,
+}
+
+pub async fn create_request_comment(
+    request_service: web::Data<Arc<dyn RequestServiceInterface + Send + Sync + 'static>>,
+    auth: AuthenticationContext,
+    request_id: web::Path<i32>,
+    body: web::Json<CreateRequestCommentBody>,
+) -> HttpResponse {
+    let user_id = match derive_user_id(auth) {
+        Ok(id) => id,
+        Err(e) => return derive_authentication_middleware_error(e),
+    };
+
+    let comment_body = body.into_inner().body.trim().to_string();
+    if comment_body.is_empty() {
+        return HttpResponse::BadRequest().json(HttpErrorResponse::new(
+            API_VALIDATION_ERROR_CODE.to_string(),
+            vec!["body should not be empty".into()],
+        ));
+    }
+
+    let id = match request_service
+        .create_request_comment(CreateRequestCommentParams {
+            request_id: request_id.into_inner(),
+            user_id,
+            body: comment_body,
+        })
+        .await
+    {
+        Ok(id) => id,
+        Err(e) => match e {
+            CreateRequestCommentError::RequestNotFound(message) => {
+                return HttpResponse::NotFound().json(HttpErrorResponse::new(
+                    RequestErrorCode::RequestNotFound.to_string(),
+                    vec![message.to_string()],
+                ))
+            }
+            CreateRequestCommentError::NotAllowedToComment(message) => {
+                return HttpResponse::Forbidden().json(HttpErrorResponse::new(
+                    RequestErrorCode::NotAllowedToComment.to_string(),
+                    vec![message.to_string()],
+                ))
+            }
+            CreateRequestCommentError::InternalServerError => {
+                return HttpResponse::InternalServerError().json(HttpErrorResponse::new(
+                    RequestErrorCode::InternalServerError.to_string(),
+                    vec![CreateRequestCommentError::InternalServerError.to_string()],
+                ))
+            }
+        },
+    };
+
+    HttpResponse::Created().json(CreateRequestCommentResponse { id })
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListApprovalWorkflowResponse {
+    contents: Vec<ApprovalWorkflowContent>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApprovalWorkflowContent {
+    id: i32,
+    building_id: Option<i32>,
+    action: Option<RequestActionType>,
+    stages: Vec<ApprovalWorkflowStage>,
+}
+
+pub async fn list_approval_workflows(
+    request_service: web::Data<Arc<dyn RequestServiceInterface + Send + Sync + 'static>>,
+    auth: AuthenticationContext,
+) -> HttpResponse {
+    if let Err(e) = derive_user_id(auth) {
+        return derive_authentication_middleware_error(e);
+    }
+
+    let workflows = match request_service.list_approval_workflows().await {
+        Ok(workflows) => workflows,
+        Err(e) => match e {
+            ListApprovalWorkflowError::InternalServerError => {
+                return HttpResponse::InternalServerError().json(HttpErrorResponse::new(
+                    RequestErrorCode::InternalServerError.to_string(),
+                    vec![ListApprovalWorkflowError::InternalServerError.to_string()],
+                ))
+            }
+        },
+    };
+
+    HttpResponse::Ok().json(ListApprovalWorkflowResponse {
+        contents: workflows
+            .into_iter()
+            .map(|workflow| ApprovalWorkflowContent {
+                id: workflow.id,
+                building_id: workflow.building_id,
+                action: workflow.action,
+                stages: workflow.stages,
+            })
+            .collect(),
+    })
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveApprovalWorkflowBody {
+    building_id: Option<i32>,
+    action: Option<RequestActionType>,
+    stages: Vec<SaveApprovalWorkflowStageBody>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveApprovalWorkflowStageBody {
+    position: i32,
+    roles: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveApprovalWorkflowResponse {
+    id: i32,
+}
+
+pub async fn save_approval_workflow(
+    request_service: web::Data<Arc<dyn RequestServiceInterface + Send + Sync + 'static>>,
+    auth: AuthenticationContext,
+    body: web::Json<SaveApprovalWorkflowBody>,
+) -> HttpResponse {
+    if let Err(e) = derive_user_id(auth) {
+        return derive_authentication_middleware_error(e);
+    }
+
+    let body = body.into_inner();
+    let id = match request_service
+        .save_approval_workflow(SaveApprovalWorkflowParams {
+            building_id: body.building_id,
+            action: body.action,
+            stages: body
+                .stages
+                .into_iter()
+                .map(|stage| ApprovalWorkflowStage {
+                    position: stage.position,
+                    roles: stage.roles,
+                })
+                .collect(),
+        })
+        .await
+    {
+        Ok(id) => id,
+        Err(e) => match e {
+            SaveApprovalWorkflowError::InvalidApprovalWorkflow(message) => {
+                return HttpResponse::BadRequest().json(HttpErrorResponse::new(
+                    RequestErrorCode::InvalidApprovalWorkflow.to_string(),
+                    vec![message],
+                ))
+            }
+            SaveApprovalWorkflowError::BuildingNotFound(message) => {
+                return HttpResponse::NotFound().json(HttpErrorResponse::new(
+                    RequestErrorCode::BuildingNotFound.to_string(),
+                    vec![message.to_string()],
+                ))
+            }
+            SaveApprovalWorkflowError::InternalServerError => {
+                return HttpResponse::InternalServerError().json(HttpErrorResponse::new(
+                    RequestErrorCode::InternalServerError.to_string(),
+                    vec![SaveApprovalWorkflowError::InternalServerError.to_string()],
+                ))
+            }
+        },
+    };
+
+    HttpResponse::Ok().json(SaveApprovalWorkflowResponse { id })
+}
+
+pub async fn delete_approval_workflow(
+    request_service: web::Data<Arc<dyn RequestServiceInterface + Send + Sync + 'static>>,
+    auth: AuthenticationContext,
+    workflow_id: web::Path<i32>,
+) -> HttpResponse {
+    if let Err(e) = derive_user_id(auth) {
+        return derive_authentication_middleware_error(e);
+    }
+
+    if let Err(e) = request_service
+        .delete_approval_workflow(workflow_id.into_inner())
+        .await
+    {
+        match e {
+            DeleteApprovalWorkflowError::ApprovalWorkflowNotFound(message) => {
+                return HttpResponse::NotFound().json(HttpErrorResponse::new(
+                    RequestErrorCode::ApprovalWorkflowNotFound.to_string(),
+                    vec![message.to_string()],
+                ))
+            }
+            DeleteApprovalWorkflowError::DefaultApprovalWorkflowRequired(message) => {
+                return HttpResponse::Conflict().json(HttpErrorResponse::new(
+                    RequestErrorCode::DefaultApprovalWorkflowRequired.to_string(),
+                    vec![message.to_string()],
+                ))
+            }
+            DeleteApprovalWorkflowError::InternalServerError => {
+                return HttpResponse::InternalServerError().json(HttpErrorResponse::new(
+                    RequestErrorCode::InternalServerError.to_string(),
+                    vec![DeleteApprovalWorkflowError::InternalServerError.to_string()],
+                ))
+            }
+        }
+    }
+
+    HttpResponse::NoContent().finish()
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListApprovalDelegationResponse {
+    contents: Vec<ApprovalDelegationContent>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApprovalDelegationContent {
+    id: i32,
+    delegator: ListRequestContentAuthor,
+    delegate: ListRequestContentAuthor,
+    start_date: String,
+    end_date: String,
+    is_active: bool,
+    created_at: String,
+}
+
+pub async fn list_approval_delegations(
+    request_service: web::Data<Arc<dyn RequestServiceInterface + Send + Sync + 'static>>,
+    auth: AuthenticationContext,
+) -> HttpResponse {
+    let user_id = match derive_user_id(auth) {
+        Ok(id) => id,
+        Err(e) => return derive_authentication_middleware_error(e),
+    };
+
+    let delegations = match request_service.list_approval_delegations(user_id).await {
+        Ok(delegations) => delegations,
+        Err(e) => match e {
+            ListApprovalDelegationError::InternalServerError => {
+                return HttpResponse::InternalServerError().json(HttpErrorResponse::new(
+                    RequestErrorCode::InternalServerError.to_string(),
+                    vec![ListApprovalDelegationError::InternalServerError.to_string()],
+                ))
+            }
+        },
+    };
+
+    let now = chrono::Utc::now();
+    HttpResponse::Ok().json(ListApprovalDelegationResponse {
+        contents: delegations
+            .into_iter()
+            .map(|delegation| ApprovalDelegationContent {
+                is_active: delegation.is_active_at(now),
+                id: delegation.id,
+                delegator: ListRequestContentAuthor {
+                    id: delegation.delegator_id,
+                    name: delegation.delegator_name,
+                },
+                delegate: ListRequestContentAuthor {
+                    id: delegation.delegate_id,
+                    name: delegation.delegate_name,
+                },
+                start_date: delegation.start_date.format("%Y-%m-%d").to_string(),
+                end_date: delegation.end_date.format("%Y-%m-%d").to_string(),
+                created_at: delegation.created_at.to_rfc3339(),
+            })
+            .collect(),
+    })
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateApprovalDelegationBody {
+    delegate_id: i32,
+    start_date: String,
+    end_date: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateApprovalDelegationResponse {
+    id: i32,
+}
+
+pub async fn create_approval_delegation(
+    request_service: web::Data<Arc<dyn RequestServiceInterface + Send + Sync + 'static>>,
+    auth: AuthenticationContext,
+    body: web::Json<CreateApprovalDelegationBody>,
+) -> HttpResponse {
+    let user_id = match derive_user_id(auth) {
+        Ok(id) => id,
+        Err(e) => return derive_authentication_middleware_error(e),
+    };
+
+    let start_date = match validate_date_format(body.start_date.as_str(), "%Y-%m-%d") {
+        Ok(date) => date,
+        Err(_) => {
+            return HttpResponse::BadRequest().json(HttpErrorResponse::new(
+                API_VALIDATION_ERROR_CODE.to_string(),
+                vec!["startDate format must be yyyy-mm-dd".into()],
+            ))
+        }
+    };
+    let end_date = match validate_date_format(body.end_date.as_str(), "%Y-%m-%d") {
+        Ok(date) => date,
+        Err(_) => {
+            return HttpResponse::BadRequest().json(HttpErrorResponse::new(
+                API_VALIDATION_ERROR_CODE.to_string(),
+                vec!["endDate format must be yyyy-mm-dd".into()],
+            ))
+        }
+    };
+
+    let id = match request_service
+        .create_approval_delegation(CreateApprovalDelegationParams {
+            delegator_id: user_id,
+            delegate_id: body.delegate_id,
+            start_date,
+            end_date,
+        })
+        .await
+    {
+        Ok(id) => id,
+        Err(e) => match e {
+            CreateApprovalDelegationError::DelegateNotFound(message) => {
+                return HttpResponse::NotFound().json(HttpErrorResponse::new(
+                    RequestErrorCode::DelegateNotFound.to_string(),
+                    vec![message.to_string()],
+                ))
+            }
+            CreateApprovalDelegationError::InvalidDelegate(message) => {
+                return HttpResponse::BadRequest().json(HttpErrorResponse::new(
+                    RequestErrorCode::InvalidDelegate.to_string(),
+                    vec![message.to_string()],
+                ))
+            }
+            CreateApprovalDelegationError::DelegatorNotApprover(message) => {
+                return HttpResponse::Forbidden().json(HttpErrorResponse::new(
+                    RequestErrorCode::DelegatorNotApprover.to_string(),
+                    vec![message.to_string()],
+                ))
+            }
+            CreateApprovalDelegationError::InvalidDateRange(message) => {
+                return HttpResponse::BadRequest().json(HttpErrorResponse::new(
+                    RequestErrorCode::InvalidDateRange.to_string(),
+                    vec![message.to_string()],
+                ))
+            }
+            CreateApprovalDelegationError::InternalServerError => {
+                return HttpResponse::InternalServerError().json(HttpErrorResponse::new(
+                    RequestErrorCode::InternalServerError.to_string(),
+                    vec![CreateApprovalDelegationError::InternalServerError.to_string()],
+                ))
+            }
+        },
+    };
+
+    HttpResponse::Created().json(CreateApprovalDelegationResponse { id })
+}
+
+pub async fn delete_approval_delegation(
+    request_service: web::Data<Arc<dyn RequestServiceInterface + Send + Sync + 'static>>,
+    auth: AuthenticationContext,
+    delegation_id: web::Path<i32>,
+) -> HttpResponse {
+    let user_id = match derive_user_id(auth) {
+        Ok(id) => id,
+        Err(e) => return derive_authentication_middleware_error(e),
+    };
+
+    if let Err(e) = request_service
+        .delete_approval_delegation(delegation_id.into_inner(), user_id)
+        .await
+    {
+        match e {
+            DeleteApprovalDelegationError::ApprovalDelegationNotFound(message) => {
+                return HttpResponse::NotFound().json(HttpErrorResponse::new(
+                    RequestErrorCode::ApprovalDelegationNotFound.to_string(),
+                    vec![message.to_string()],
+                ))
+            }
+            DeleteApprovalDelegationError::NotDelegationOwner(message) => {
+                return HttpResponse::Forbidden().json(HttpErrorResponse::new(
+                    RequestErrorCode::NotDelegationOwner.to_string(),
+                    vec![message.to_string()],
+                ))
+            }
+            DeleteApprovalDelegationError::InternalServerError => {
+                return HttpResponse::InternalServerError().json(HttpErrorResponse::new(
+                    RequestErrorCode::InternalServerError.to_string(),
+                    vec![DeleteApprovalDelegationError::InternalServerError.to_string()],
+                ))
+            }
+        }
+    }
+
+    HttpResponse::NoContent().finish()
+}
@@ -1,9 +1,13 @@
 use async_trait::async_trait;
-use sqlx::{postgres::PgRow, Pool, Postgres, Row};
+use sqlx::{postgres::PgRow, PgConnection, Pool, Postgres, Row};
 
-use crate::database::PaginationResult;
+use crate::database::{PaginationResult, UnitOfWork};
 
-use super::{RawRequestMetadata, Request, RequestActionType, RequestMetadata};
+use super::{
+    ApprovalDelegation, ApprovalWorkflow, ApprovalWorkflowStage, RawRequestMetadata, Request,
+    RequestActionType, RequestApprovalStep, RequestApproverDelegator, RequestComment,
+    RequestMetadata, RequestStatus,
+};
 
 pub struct FindRequestParams {
     pub page: i32,
@@ -12,6 +16,8 @@ pub struct FindRequestParams {
     pub announcement_id: Option<i32>,
     pub user_id: Option<i32>,
     pub action_type: Option<RequestActionType>,
+    pub status: Option<RequestStatus>,
+    pub awaiting_role: Option<String>,
     pub approved_by_lsc: Option<bool>,
     pub approved_by_bm: Option<bool>,
 }
@@ -24,6 +30,9 @@ pub struct InsertRequestParams {
 
     pub extended_end_date: Option<chrono::DateTime<chrono::Utc>>,
     pub new_device_ids: Option<Vec<i32>>,
+    pub new_title: Option<String>,
+    pub new_notes: Option<String>,
+    pub new_media_id: Option<i32>,
 }
 
 impl InsertRequestParams {
@@ -41,6 +50,9 @@ impl InsertRequestParams {
 
             extended_end_date: None,
             new_device_ids: None,
+            new_title: None,
+            new_notes: None,
+            new_media_id: None,
         }
     }
 
@@ -53,14 +65,50 @@ impl InsertRequestParams {
         self.new_device_ids = Some(new_device_ids);
         self
     }
+
+    pub fn new_title(mut self, new_title: String) -> Self {
+        self.new_title = Some(new_title);
+        self
+    }
+
+    pub fn new_notes(mut self, new_notes: String) -> Self {
+        self.new_notes = Some(new_notes);
+        self
+    }
+
+    pub fn new_media_id(mut self, new_media_id: i32) -> Self {
+        self.new_media_id = Some(new_media_id);
+        self
+    }
 }
 
 pub struct UpdateApprovalParams {
     pub request_id: i32,
-    pub approved_by_lsc: Option<bool>,
-    pub lsc_approver: Option<i32>,
-    pub approved_by_bm: Option<bool>,
-    pub bm_approver: Option<i32>,
+    pub step_ids: Vec<i32>,
+    pub approved: bool,
+    pub approver_id: i32,
+    pub on_behalf_of: Option<i32>,
+    pub reason: Option<String>,
+}
+
+pub struct InsertApprovalDelegationParams {
+    pub delegator_id: i32,
+    pub delegate_id: i32,
+    pub start_date: chrono::DateTime<chrono::Utc>,
+    pub end_date: chrono::DateTime<chrono::Utc>,
+}
+
+pub struct InsertRequestCommentParams {
+    pub request_id: i32,
+    pub user_id: i32,
+    pub approval_step_id: Option<i32>,
+    pub body: String,
+}
+
+pub struct SaveApprovalWorkflowParams {
+    pub building_id: Option<i32>,
+    pub action: Option<RequestActionType>,
+    pub stages: Vec<ApprovalWorkflowStage>,
 }
 
 pub struct ListRequestRow {
@@ -69,11 +117,9 @@ pub struct ListRequestRow {
     request_action: RequestActionType,
     request_metadata: sqlx::types::Json<RawRequestMetadata>,
     request_description: String,
-    request_approved_by_lsc: Option<bool>,
-    request_lsc_approver: Option<i32>,
-    request_approved_by_bm: Option<bool>,
-    request_bm_approver: Option<i32>,
+    request_approval_steps: sqlx::types::Json<Vec<RequestApprovalStep>>,
     request_created_at: chrono::DateTime<chrono::Utc>,
+    request_withdrawn_at: Option<chrono::DateTime<chrono::Utc>>,
     announcement_id: i32,
     announcement_title: String,
     user_id: i32,
@@ -88,11 +134,9 @@ pub struct RawRequestDetail {
     pub user_id: i32,
     pub user_name: String,
     pub description: String,
-    pub approved_by_lsc: Option<bool>,
-    pub lsc_approver: Option<i32>,
-    pub approved_by_bm: Option<bool>,
-    pub bm_approver: Option<i32>,
+    pub approval_steps: sqlx::types::Json<Vec<RequestApprovalStep>>,
     pub created_at: chrono::DateTime<chrono::Utc>,
+    pub withdrawn_at: Option<chrono::DateTime<chrono::Utc>>,
     pub metadata: sqlx::types::Json<RawRequestMetadata>,
 }
 
@@ -103,12 +147,166 @@ pub trait RequestRepositoryInterface {
         params: FindRequestParams,
     ) -> Result<PaginationResult<Request>, sqlx::Error>;
     async fn find_one(&self, request_id: i32) -> Result<Request, sqlx::Error>;
-    async fn insert(&self, params: InsertRequestParams) -> Result<i32, sqlx::Error>;
-    async fn update_approval(&self, params: UpdateApprovalParams) -> Result<(), sqlx::Error>;
+    async fn find_one_for_update(
+        &self,
+        unit_of_work: &mut UnitOfWork,
+        request_id: i32,
+    ) -> Result<Request, sqlx::Error>;
+    async fn insert(
+        &self,
+        unit_of_work: &mut UnitOfWork,
+        params: InsertRequestParams,
+    ) -> Result<i32, sqlx::Error>;
+    async fn update_approval(
+        &self,
+        unit_of_work: &mut UnitOfWork,
+        params: UpdateApprovalParams,
+    ) -> Result<(), sqlx::Error>;
     async fn batch_reject_requests_from_announcement_ids(
         &self,
+        unit_of_work: &mut UnitOfWork,
         announcement_ids: Vec<i32>,
     ) -> Result<(), sqlx::Error>;
+    async fn withdraw(
+        &self,
+        unit_of_work: &mut UnitOfWork,
+        request_id: i32,
+    ) -> Result<(), sqlx::Error>;
+    async fn withdraw_pending_requests_from_announcement_id(
+        &self,
+        unit_of_work: &mut UnitOfWork,
+        announcement_id: i32,
+    ) -> Result<(), sqlx::Error>;
+    async fn find_comments(&self, request_id: i32) -> Result<Vec<RequestComment>, sqlx::Error>;
+    async fn insert_comment(
+        &self,
+        unit_of_work: &mut UnitOfWork,
+        params: InsertRequestCommentParams,
+    ) -> Result<i32, sqlx::Error>;
+    async fn find_active_delegators(
+        &self,
+        delegate_id: i32,
+        at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<RequestApproverDelegator>, sqlx::Error>;
+    async fn find_delegations(&self, user_id: i32) -> Result<Vec<ApprovalDelegation>, sqlx::Error>;
+    async fn find_one_delegation(
+        &self,
+        delegation_id: i32,
+    ) -> Result<ApprovalDelegation, sqlx::Error>;
+    async fn insert_delegation(
+        &self,
+        unit_of_work: &mut UnitOfWork,
+        params: InsertApprovalDelegationParams,
+    ) -> Result<i32, sqlx::Error>;
+    async fn delete_delegation(
+        &self,
+        unit_of_work: &mut UnitOfWork,
+        delegation_id: i32,
+    ) -> Result<(), sqlx::Error>;
+    async fn find_approval_workflows(&self) -> Result<Vec<ApprovalWorkflow>, sqlx::Error>;
+    async fn find_one_approval_workflow(
+        &self,
+        workflow_id: i32,
+    ) -> Result<ApprovalWorkflow, sqlx::Error>;
+    async fn save_approval_workflow(
+        &self,
+        unit_of_work: &mut UnitOfWork,
+        params: SaveApprovalWorkflowParams,
+    ) -> Result<i32, sqlx::Error>;
+    async fn delete_approval_workflow(
+        &self,
+        unit_of_work: &mut UnitOfWork,
+        workflow_id: i32,
+    ) -> Result<(), sqlx::Error>;
+}
+
+async fn find_request_detail(
+    conn: &mut PgConnection,
+    request_id: i32,
+) -> Result<Request, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        select
+            "request"."id" as "request_id",
+            "request"."action" as "request_action",
+            "request"."metadata" as "request_metadata",
+            "request"."description" as "request_description",
+            "approval"."steps" as "request_approval_steps",
+            "request"."created_at" as "request_created_at",
+            "request"."withdrawn_at" as "request_withdrawn_at",
+            "announcement"."id" as "announcement_id",
+            "announcement"."title" as "announcement_title",
+            "user"."id" as "user_id",
+            "user"."name" as "user_name"
+        from "request"
+        join "announcement" on "announcement"."id" = "request"."announcement_id"
+        join "user" on "user"."id"= "request"."user_id"
+        left join lateral (
+            select coalesce(jsonb_agg(jsonb_build_object(
+                'id', "request_approval_step"."id",
+                'position', "request_approval_step"."position",
+                'roles', "request_approval_step"."roles",
+                'approved', "request_approval_step"."approved",
+                'approver_id', "request_approval_step"."approver_id",
+                'on_behalf_of', "request_approval_step"."on_behalf_of",
+                'approved_at', "request_approval_step"."approved_at"
+            ) order by "request_approval_step"."position", "request_approval_step"."id"), '[]'::jsonb) as "steps"
+            from "request_approval_step"
+            where "request_approval_step"."request_id" = "request"."id"
+        ) "approval" on true
+        where "request"."id" = $1
+        "#,
+    )
+    .bind(request_id)
+    .map(|row: PgRow| RawRequestDetail {
+        id: row.get("request_id"),
+        action: row.get("request_action"),
+        description: row.get("request_description"),
+        approval_steps: row.get("request_approval_steps"),
+        created_at: row.get("request_created_at"),
+        withdrawn_at: row.get("request_withdrawn_at"),
+        announcement_id: row.get("announcement_id"),
+        announcement_title: row.get("announcement_title"),
+        user_id: row.get("user_id"),
+        user_name: row.get("user_name"),
+        metadata: row.get("request_metadata"),
+    })
+    .fetch_one(conn)
+    .await?;
+    let mut metadata = RequestMetadata::default();
+    if let Some(extended_end_date) = &result.metadata.extended_end_date {
+        let date = chrono::DateTime::parse_from_rfc3339(extended_end_date.as_str())
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+
+        metadata = metadata.extended_end_date(date);
+    }
+    if let Some(new_device_ids) = &result.metadata.new_device_ids {
+        metadata = metadata.new_device_ids(new_device_ids.clone());
+    }
+    if let Some(new_title) = &result.metadata.new_title {
+        metadata = metadata.new_title(new_title.clone());
+    }
+    if let Some(new_notes) = &result.metadata.new_notes {
+        metadata = metadata.new_notes(new_notes.clone());
+    }
+    if let Some(new_media_id) = result.metadata.new_media_id {
+        metadata = metadata.new_media_id(new_media_id);
+    }
+
+    Ok(Request {
+        metadata,
+        id: result.id,
+        action: result.action,
+        announcement_id: result.announcement_id,
+        announcement_title: result.announcement_title,
+        user_id: result.user_id,
+        user_name: result.user_name,
+        description: result.description,
+        approval_steps: result.approval_steps.0,
+        created_at: result.created_at,
+        withdrawn_at: result.withdrawn_at,
+    })
 }
 
 pub struct RequestRepository {
@@ -137,11 +335,9 @@ impl RequestRepositoryInterface for RequestRepository {
                 "request"."action" as "request_action",
                 "request"."metadata" as "request_metadata",
                 "request"."description" as "request_description",
-                "request"."approved_by_lsc" as "request_approved_by_lsc",
-                "request"."lsc_approver" as "request_lsc_approver",
-                "request"."approved_by_bm" as "request_approved_by_bm",
-                "request"."bm_approver" as "request_bm_approver",
+                "approval"."steps" as "request_approval_steps",
                 "request"."created_at" as "request_created_at",
+                "request"."withdrawn_at" as "request_withdrawn_at",
                 "announcement"."id" as "announcement_id",
                 "announcement"."title" as "announcement_title",
                 "user"."id" as "user_id",
@@ -149,6 +345,19 @@ impl RequestRepositoryInterface for RequestRepository {
             from "request"
             join "announcement" on "announcement"."id" = "request"."announcement_id"
             join "user" on "user"."id"= "request"."user_id"
+            left join lateral (
+                select coalesce(jsonb_agg(jsonb_build_object(
+                    'id', "request_approval_step"."id",
+                    'position', "request_approval_step"."position",
+                    'roles', "request_approval_step"."roles",
+                    'approved', "request_approval_step"."approved",
+                    'approver_id', "request_approval_step"."approver_id",
+                    'on_behalf_of', "request_approval_step"."on_behalf_of",
+                    'approved_at', "request_approval_step"."approved_at"
+                ) order by "request_approval_step"."position", "request_approval_step"."id"), '[]'::jsonb) as "steps"
+                from "request_approval_step"
+                where "request_approval_step"."request_id" = "request"."id"
+            ) "approval" on true
             left join lateral (
                 select count(*) from "request"
                 join "announcement" on "announcement"."id" = "request"."announcement_id"
@@ -158,17 +367,81 @@ impl RequestRepositoryInterface for RequestRepository {
                     ($4::integer is null or "announcement"."id" = $4) and 
                     ($5::integer is null or "user"."id" = $5) and 
                     ($6::text is null or "request"."action" = $6) and 
-                    ($7::bool is null or "request"."approved_by_lsc" = $7) and 
-                    ($8::bool is null or "request"."approved_by_bm" = $8)
+                    ($7::text is null or $7 = (
+                        case
+                            when "request"."withdrawn_at" is not null then 'withdrawn'
+                            when exists (
+                                select 1 from "request_approval_step"
+                                where "request_approval_step"."request_id" = "request"."id" and "request_approval_step"."approved" is false
+                            ) then 'rejected'
+                            when not exists (
+                                select 1 from "request_approval_step"
+                                where "request_approval_step"."request_id" = "request"."id" and "request_approval_step"."approved" is not true
+                            ) then 'approved'
+                            else 'pending'
+                        end
+                    )) and
+                    ($8::text is null or (
+                        "request"."withdrawn_at" is null and
+                        not exists (
+                            select 1 from "request_approval_step"
+                            where "request_approval_step"."request_id" = "request"."id" and "request_approval_step"."approved" is false
+                        ) and
+                        exists (
+                            select 1 from "request_approval_step"
+                            where
+                                "request_approval_step"."request_id" = "request"."id" and
+                                "request_approval_step"."approved" is null and
+                                $8 = any("request_approval_step"."roles") and
+                                "request_approval_step"."position" = (
+                                    select min("waiting_step"."position") from "request_approval_step" "waiting_step"
+                                    where "waiting_step"."request_id" = "request"."id" and "waiting_step"."approved" is null
+                                )
+                        )
+                    )) and
+                    ($9::boolean is null or request_approval_by_role("request"."id", 'lsc') = $9) and
+                    ($10::boolean is null or request_approval_by_role("request"."id", 'bm') = $10)
             ) "result" on true
             where
                 ($3::integer is null or "request"."id" = $3) and 
                 ($4::integer is null or "announcement"."id" = $4) and 
                 ($5::integer is null or "user"."id" = $5) and 
                 ($6::text is null or "request"."action" = $6) and 
-                ($7::bool is null or "request"."approved_by_lsc" = $7) and 
-                ($8::bool is null or "request"."approved_by_bm" = $8)
-            group by "request"."id", "announcement"."id", "user"."id", "result"."count"
+                ($7::text is null or $7 = (
+                    case
+                        when "request"."withdrawn_at" is not null then 'withdrawn'
+                        when exists (
+                            select 1 from "request_approval_step"
+                            where "request_approval_step"."request_id" = "request"."id" and "request_approval_step"."approved" is false
+                        ) then 'rejected'
+                        when not exists (
+                            select 1 from "request_approval_step"
+                            where "request_approval_step"."request_id" = "request"."id" and "request_approval_step"."approved" is not true
+                        ) then 'approved'
+                        else 'pending'
+                    end
+                )) and
+                ($8::text is null or (
+                    "request"."withdrawn_at" is null and
+                    not exists (
+                        select 1 from "request_approval_step"
+                        where "request_approval_step"."request_id" = "request"."id" and "request_approval_step"."approved" is false
+                    ) and
+                    exists (
+                        select 1 from "request_approval_step"
+                        where
+                            "request_approval_step"."request_id" = "request"."id" and
+                            "request_approval_step"."approved" is null and
+                            $8 = any("request_approval_step"."roles") and
+                            "request_approval_step"."position" = (
+                                select min("waiting_step"."position") from "request_approval_step" "waiting_step"
+                                where "waiting_step"."request_id" = "request"."id" and "waiting_step"."approved" is null
+                            )
+                    )
+                )) and
+                ($9::boolean is null or request_approval_by_role("request"."id", 'lsc') = $9) and
+                ($10::boolean is null or request_approval_by_role("request"."id", 'bm') = $10)
+            group by "request"."id", "announcement"."id", "user"."id", "approval"."steps", "result"."count"
             order by "request"."id" desc
             offset $1 limit $2
             "#,
@@ -179,6 +452,8 @@ impl RequestRepositoryInterface for RequestRepository {
         .bind(params.announcement_id)
         .bind(params.user_id)
         .bind(params.action_type)
+        .bind(params.status.map(|status| status.value()))
+        .bind(params.awaiting_role)
         .bind(params.approved_by_lsc)
         .bind(params.approved_by_bm)
         .map(|row: PgRow| ListRequestRow {
@@ -187,11 +462,9 @@ impl RequestRepositoryInterface for RequestRepository {
             request_action: row.get("request_action"),
             request_metadata: row.get("request_metadata"),
             request_description: row.get("request_description"),
-            request_approved_by_lsc: row.get("request_approved_by_lsc"),
-            request_lsc_approver: row.get("request_lsc_approver"),
-            request_approved_by_bm: row.get("request_approved_by_bm"),
-            request_bm_approver: row.get("request_bm_approver"),
+            request_approval_steps: row.get("request_approval_steps"),
             request_created_at: row.get("request_created_at"),
+            request_withdrawn_at: row.get("request_withdrawn_at"),
             announcement_id: row.get("announcement_id"),
             announcement_title: row.get("announcement_title"),
             user_id: row.get("user_id"),
@@ -221,6 +494,15 @@ impl RequestRepositoryInterface for RequestRepository {
             if let Some(new_device_ids) = &row.request_metadata.new_device_ids {
                 metadata = metadata.new_device_ids(new_device_ids.clone());
             }
+            if let Some(new_title) = &row.request_metadata.new_title {
+                metadata = metadata.new_title(new_title.clone());
+            }
+            if let Some(new_notes) = &row.request_metadata.new_notes {
+                metadata = metadata.new_notes(new_notes.clone());
+            }
+            if let Some(new_media_id) = row.request_metadata.new_media_id {
+                metadata = metadata.new_media_id(new_media_id);
+            }
 
             contents.push(Request {
                 metadata,
@@ -231,11 +513,9 @@ impl RequestRepositoryInterface for RequestRepository {
                 user_id: row.user_id,
                 user_name: row.user_name,
                 description: row.request_description,
-                approved_by_lsc: row.request_approved_by_lsc,
-                lsc_approver: row.request_lsc_approver,
-                approved_by_bm: row.request_approved_by_bm,
-                bm_approver: row.request_bm_approver,
+                approval_steps: row.request_approval_steps.0,
                 created_at: row.request_created_at,
+                withdrawn_at: row.request_withdrawn_at,
             })
         }
 
@@ -248,76 +528,35 @@ impl RequestRepositoryInterface for RequestRepository {
     }
 
     async fn find_one(&self, request_id: i32) -> Result<Request, sqlx::Error> {
-        let result = sqlx::query(
+        let mut conn = self._db.acquire().await?;
+
+        find_request_detail(&mut conn, request_id).await
+    }
+
+    async fn find_one_for_update(
+        &self,
+        unit_of_work: &mut UnitOfWork,
+        request_id: i32,
+    ) -> Result<Request, sqlx::Error> {
+        // Deciding and withdrawing lock the request first, so they always work on the steps left by
+        // the previous one
+        sqlx::query(
             r#"
-            select
-                "request"."id" as "request_id",
-                "request"."action" as "request_action",
-                "request"."metadata" as "request_metadata",
-                "request"."description" as "request_description",
-                "request"."approved_by_lsc" as "request_approved_by_lsc",
-                "request"."lsc_approver" as "request_lsc_approver",
-                "request"."approved_by_bm" as "request_approved_by_bm",
-                "request"."bm_approver" as "request_bm_approver",
-                "request"."created_at" as "request_created_at",
-                "announcement"."id" as "announcement_id",
-                "announcement"."title" as "announcement_title",
-                "user"."id" as "user_id",
-                "user"."name" as "user_name"
-            from "request"
-            join "announcement" on "announcement"."id" = "request"."announcement_id"
-            join "user" on "user"."id"= "request"."user_id"
-            where "request"."id" = $1
+            select "id" from "request" where "id" = $1 for update
             "#,
         )
         .bind(request_id)
-        .map(|row: PgRow| RawRequestDetail {
-            id: row.get("request_id"),
-            action: row.get("request_action"),
-            description: row.get("request_description"),
-            approved_by_lsc: row.get("request_approved_by_lsc"),
-            lsc_approver: row.get("request_lsc_approver"),
-            approved_by_bm: row.get("request_approved_by_bm"),
-            bm_approver: row.get("request_bm_approver"),
-            created_at: row.get("request_created_at"),
-            announcement_id: row.get("announcement_id"),
-            announcement_title: row.get("announcement_title"),
-            user_id: row.get("user_id"),
-            user_name: row.get("user_name"),
-            metadata: row.get("request_metadata"),
-        })
-        .fetch_one(&self._db)
+        .fetch_one(unit_of_work.connection())
         .await?;
-        let mut metadata = RequestMetadata::default();
-        if let Some(extended_end_date) = &result.metadata.extended_end_date {
-            let date = chrono::DateTime::parse_from_rfc3339(extended_end_date.as_str())
-                .unwrap()
-                .with_timezone(&chrono::Utc);
-
-            metadata = metadata.extended_end_date(date);
-        }
-        if let Some(new_device_ids) = &result.metadata.new_device_ids {
-            metadata = metadata.new_device_ids(new_device_ids.clone());
-        }
 
-        Ok(Request {
-            metadata,
-            id: result.id,
-            action: result.action,
-            announcement_id: result.announcement_id,
-            announcement_title: result.announcement_title,
-            user_id: result.user_id,
-            user_name: result.user_name,
-            description: result.description,
-            approved_by_lsc: result.approved_by_lsc,
-            lsc_approver: result.lsc_approver,
-            approved_by_bm: result.approved_by_bm,
-            bm_approver: result.bm_approver,
-            created_at: result.created_at,
-        })
+        find_request_detail(unit_of_work.connection(), request_id).await
     }
 
-    async fn insert(&self, params: InsertRequestParams) -> Result<i32, sqlx::Error> {
+    async fn insert(
+        &self,
+        unit_of_work: &mut UnitOfWork,
+        params: InsertRequestParams,
+    ) -> Result<i32, sqlx::Error> {
         let result = sqlx::query!(
             r#"
             insert into "request" ("action", "description", "announcement_id", "user_id")
@@ -329,9 +568,50 @@ impl RequestRepositoryInterface for RequestRepository {
             params.announcement_id,
             params.user_id,
         )
-        .fetch_one(&self._db)
+        .fetch_one(unit_of_work.connection())
         .await?;
 
+        // The stages of the most specific workflow are copied onto the request, a workflow for the
+        // building wins over a workflow for the action. Announcements spanning several buildings
+        // fall back to the workflows that apply to every building.
+        let steps_inserted = sqlx::query(
+            r#"
+            with "announcement_building" as (
+                select distinct "floor"."building_id"
+                from "device_announcement"
+                join "device" on "device"."id" = "device_announcement"."device_id"
+                join "floor" on "floor"."id" = "device"."floor_id"
+                where "device_announcement"."announcement_id" = $2
+            ), "request_building" as (
+                select case when count(*) = 1 then min("building_id") end as "building_id"
+                from "announcement_building"
+            ), "workflow" as (
+                select "approval_workflow"."id"
+                from "approval_workflow", "request_building"
+                where
+                    ("approval_workflow"."building_id" is null or "approval_workflow"."building_id" = "request_building"."building_id") and
+                    ("approval_workflow"."action" is null or "approval_workflow"."action" = $3)
+                order by "approval_workflow"."building_id" is null, "approval_workflow"."action" is null
+                limit 1
+            )
+            insert into "request_approval_step" ("request_id", "position", "roles")
+            select $1, "approval_workflow_stage"."position", "approval_workflow_stage"."roles"
+            from "approval_workflow_stage"
+            join "workflow" on "workflow"."id" = "approval_workflow_stage"."workflow_id"
+            "#,
+        )
+        .bind(result.id)
+        .bind(params.announcement_id)
+        .bind(params.action.clone())
+        .execute(unit_of_work.connection())
+        .await?
+        .rows_affected();
+
+        // No workflow applies to the request
+        if steps_inserted == 0 {
+            return Err(sqlx::Error::RowNotFound);
+        }
+
         if let Some(extended_end_date) = params.extended_end_date {
             let rows_affected = sqlx::query(
                 r#"
@@ -342,7 +622,7 @@ impl RequestRepositoryInterface for RequestRepository {
             )
             .bind(result.id)
             .bind(extended_end_date.to_rfc3339())
-            .execute(&self._db)
+            .execute(unit_of_work.connection())
             .await?
             .rows_affected();
 
@@ -359,7 +639,30 @@ impl RequestRepositoryInterface for RequestRepository {
             )
             .bind(result.id)
             .bind(new_device_ids)
-            .execute(&self._db)
+            .execute(unit_of_work.connection())
+            .await?
+            .rows_affected();
+
+            if rows_affected == 0 {
+                return Err(sqlx::Error::RowNotFound);
+            }
+        } else if params.action == RequestActionType::ChangeContent {
+            let rows_affected = sqlx::query(
+                r#"
+                update "request"
+                set "metadata" = "metadata" || jsonb_strip_nulls(jsonb_build_object(
+                    'new_title', $2::text,
+                    'new_notes', $3::text,
+                    'new_media_id', $4::integer
+                ))
+                where "id" = $1
+                "#,
+            )
+            .bind(result.id)
+            .bind(params.new_title)
+            .bind(params.new_notes)
+            .bind(params.new_media_id)
+            .execute(unit_of_work.connection())
             .await?
             .rows_affected();
 
@@ -371,24 +674,91 @@ impl RequestRepositoryInterface for RequestRepository {
         return Ok(result.id);
     }
 
-    async fn update_approval(&self, params: UpdateApprovalParams) -> Result<(), sqlx::Error> {
-        let rows_affected = sqlx::query!(
+    async fn update_approval(
+        &self,
+        unit_of_work: &mut UnitOfWork,
+        params: UpdateApprovalParams,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            update "request_approval_step"
+            set
+                "approved" = $3,
+                "approver_id" = $4,
+                "on_behalf_of" = $5,
+                "approved_at" = now()
+            where "request_id" = $1 and "id" = any($2) and "approved" is null
+            "#,
+        )
+        .bind(params.request_id)
+        .bind(&params.step_ids)
+        .bind(params.approved)
+        .bind(params.approver_id)
+        .bind(params.on_behalf_of)
+        .execute(unit_of_work.connection())
+        .await?;
+
+        // The reason is posted to the comment thread of the request, so the author can reply to it
+        if let Some(reason) = params.reason {
+            self.insert_comment(
+                unit_of_work,
+                InsertRequestCommentParams {
+                    request_id: params.request_id,
+                    user_id: params.approver_id,
+                    approval_step_id: params.step_ids.first().copied(),
+                    body: reason,
+                },
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn batch_reject_requests_from_announcement_ids(
+        &self,
+        unit_of_work: &mut UnitOfWork,
+        announcement_ids: Vec<i32>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
             r#"
-            update "request"
+            update "request_approval_step"
             set
-                "approved_by_lsc" = $2,
-                "approved_by_bm" = $3,
-                "lsc_approver" = $4,
-                "bm_approver" = $5
-            where "id" = $1
+                "approved" = false,
+                "approved_at" = now()
+            from "request"
+            where 
+                "request"."id" = "request_approval_step"."request_id" and
+                "request"."announcement_id" = any($1) and
+                "request"."withdrawn_at" is null and
+                "request_approval_step"."approved" is null and
+                not exists (
+                    select 1 from "request_approval_step" "rejected_step"
+                    where "rejected_step"."request_id" = "request"."id" and "rejected_step"."approved" is false
+                )
+            "#,
+        )
+        .bind(&announcement_ids)
+        .execute(unit_of_work.connection())
+        .await?;
+
+        Ok(())
+    }
+
+    async fn withdraw(
+        &self,
+        unit_of_work: &mut UnitOfWork,
+        request_id: i32,
+    ) -> Result<(), sqlx::Error> {
+        let rows_affected = sqlx::query(
+            r#"
+            update "request"
+            set "withdrawn_at" = now(), "updated_at" = now()
+            where "id" = $1 and "withdrawn_at" is null
             "#,
-            params.request_id,
-            params.approved_by_lsc,
-            params.approved_by_bm,
-            params.lsc_approver,
-            params.bm_approver,
         )
-        .execute(&self._db)
+        .bind(request_id)
+        .execute(unit_of_work.connection())
         .await?
         .rows_affected();
 
@@ -399,23 +769,369 @@ impl RequestRepositoryInterface for RequestRepository {
         Ok(())
     }
 
-    async fn batch_reject_requests_from_announcement_ids(
+    async fn withdraw_pending_requests_from_announcement_id(
         &self,
-        announcement_ids: Vec<i32>,
+        unit_of_work: &mut UnitOfWork,
+        announcement_id: i32,
     ) -> Result<(), sqlx::Error> {
-        let rows_affected = sqlx::query!(
+        sqlx::query(
+            r#"
+            select "id" from "request"
+            where "announcement_id" = $1 and "withdrawn_at" is null
+            for update
+            "#,
+        )
+        .bind(announcement_id)
+        .execute(unit_of_work.connection())
+        .await?;
+
+        sqlx::query(
             r#"
             update "request"
-            set
-                "approved_by_lsc" = false,
-                "approved_by_bm" = false
-            where 
-                "announcement_id" = any($1) and
-                "approved_by_lsc" is null and "approved_by_bm" is null
+            set "withdrawn_at" = now(), "updated_at" = now()
+            where
+                "announcement_id" = $1 and
+                "withdrawn_at" is null and
+                exists (
+                    select 1 from "request_approval_step"
+                    where "request_approval_step"."request_id" = "request"."id" and "request_approval_step"."approved" is null
+                ) and
+                not exists (
+                    select 1 from "request_approval_step"
+                    where "request_approval_step"."request_id" = "request"."id" and "request_approval_step"."approved" is false
+                )
+            "#,
+        )
+        .bind(announcement_id)
+        .execute(unit_of_work.connection())
+        .await?;
+
+        Ok(())
+    }
+    async fn find_comments(&self, request_id: i32) -> Result<Vec<RequestComment>, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            select
+                "request_comment"."id" as "comment_id",
+                "request_comment"."request_id" as "comment_request_id",
+                "request_comment"."approval_step_id" as "comment_approval_step_id",
+                "request_comment"."body" as "comment_body",
+                "request_comment"."created_at" as "comment_created_at",
+                "user"."id" as "user_id",
+                "user"."name" as "user_name"
+            from "request_comment"
+            join "user" on "user"."id" = "request_comment"."user_id"
+            where "request_comment"."request_id" = $1
+            order by "request_comment"."created_at", "request_comment"."id"
+            "#,
+        )
+        .bind(request_id)
+        .map(|row: PgRow| RequestComment {
+            id: row.get("comment_id"),
+            request_id: row.get("comment_request_id"),
+            user_id: row.get("user_id"),
+            user_name: row.get("user_name"),
+            approval_step_id: row.get("comment_approval_step_id"),
+            body: row.get("comment_body"),
+            created_at: row.get("comment_created_at"),
+        })
+        .fetch_all(&self._db)
+        .await?;
+
+        Ok(result)
+    }
+
+    async fn insert_comment(
+        &self,
+        unit_of_work: &mut UnitOfWork,
+        params: InsertRequestCommentParams,
+    ) -> Result<i32, sqlx::Error> {
+        let id = sqlx::query(
+            r#"
+            insert into "request_comment" ("request_id", "user_id", "approval_step_id", "body")
+            values ($1, $2, $3, $4)
+            returning "id"
+            "#,
+        )
+        .bind(params.request_id)
+        .bind(params.user_id)
+        .bind(params.approval_step_id)
+        .bind(params.body)
+        .map(|row: PgRow| row.get("id"))
+        .fetch_one(unit_of_work.connection())
+        .await?;
+
+        Ok(id)
+    }
+
+    async fn find_active_delegators(
+        &self,
+        delegate_id: i32,
+        at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<RequestApproverDelegator>, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            select distinct "user"."id" as "user_id", "user"."role" as "user_role"
+            from "approval_delegation"
+            join "user" on "user"."id" = "approval_delegation"."delegator_id"
+            where
+                "approval_delegation"."delegate_id" = $1 and
+                "approval_delegation"."start_date" <= $2 and
+                $2 < "approval_delegation"."end_date" + interval '1 day' and
+                "user"."status" = 'approved'
+            "#,
+        )
+        .bind(delegate_id)
+        .bind(at)
+        .map(|row: PgRow| RequestApproverDelegator {
+            user_id: row.get("user_id"),
+            role: row.get("user_role"),
+        })
+        .fetch_all(&self._db)
+        .await?;
+
+        Ok(result)
+    }
+
+    async fn find_delegations(&self, user_id: i32) -> Result<Vec<ApprovalDelegation>, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            select
+                "approval_delegation"."id" as "delegation_id",
+                "approval_delegation"."start_date" as "delegation_start_date",
+                "approval_delegation"."end_date" as "delegation_end_date",
+                "approval_delegation"."created_at" as "delegation_created_at",
+                "delegator"."id" as "delegator_id",
+                "delegator"."name" as "delegator_name",
+                "delegate"."id" as "delegate_id",
+                "delegate"."name" as "delegate_name"
+            from "approval_delegation"
+            join "user" "delegator" on "delegator"."id" = "approval_delegation"."delegator_id"
+            join "user" "delegate" on "delegate"."id" = "approval_delegation"."delegate_id"
+            where "approval_delegation"."delegator_id" = $1 or "approval_delegation"."delegate_id" = $1
+            order by "approval_delegation"."start_date" desc, "approval_delegation"."id" desc
+            "#,
+        )
+        .bind(user_id)
+        .map(map_approval_delegation)
+        .fetch_all(&self._db)
+        .await?;
+
+        Ok(result)
+    }
+
+    async fn find_one_delegation(
+        &self,
+        delegation_id: i32,
+    ) -> Result<ApprovalDelegation, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            select
+                "approval_delegation"."id" as "delegation_id",
+                "approval_delegation"."start_date" as "delegation_start_date",
+                "approval_delegation"."end_date" as "delegation_end_date",
+                "approval_delegation"."created_at" as "delegation_created_at",
+                "delegator"."id" as "delegator_id",
+                "delegator"."name" as "delegator_name",
+                "delegate"."id" as "delegate_id",
+                "delegate"."name" as "delegate_name"
+            from "approval_delegation"
+            join "user" "delegator" on "delegator"."id" = "approval_delegation"."delegator_id"
+            join "user" "delegate" on "delegate"."id" = "approval_delegation"."delegate_id"
+            where "approval_delegation"."id" = $1
+            "#,
+        )
+        .bind(delegation_id)
+        .map(map_approval_delegation)
+        .fetch_one(&self._db)
+        .await?;
+
+        Ok(result)
+    }
+
+    async fn insert_delegation(
+        &self,
+        unit_of_work: &mut UnitOfWork,
+        params: InsertApprovalDelegationParams,
+    ) -> Result<i32, sqlx::Error> {
+        let id = sqlx::query(
+            r#"
+            insert into "approval_delegation" ("delegator_id", "delegate_id", "start_date", "end_date")
+            values ($1, $2, $3, $4)
+            returning "id"
+            "#,
+        )
+        .bind(params.delegator_id)
+        .bind(params.delegate_id)
+        .bind(params.start_date)
+        .bind(params.end_date)
+        .map(|row: PgRow| row.get("id"))
+        .fetch_one(unit_of_work.connection())
+        .await?;
+
+        Ok(id)
+    }
+
+    async fn delete_delegation(
+        &self,
+        unit_of_work: &mut UnitOfWork,
+        delegation_id: i32,
+    ) -> Result<(), sqlx::Error> {
+        let rows_affected = sqlx::query(
+            r#"
+            delete from "approval_delegation" where "id" = $1
+            "#,
+        )
+        .bind(delegation_id)
+        .execute(unit_of_work.connection())
+        .await?
+        .rows_affected();
+
+        if rows_affected == 0 {
+            return Err(sqlx::Error::RowNotFound);
+        }
+
+        Ok(())
+    }
+
+    async fn find_approval_workflows(&self) -> Result<Vec<ApprovalWorkflow>, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            select
+                "approval_workflow"."id" as "workflow_id",
+                "approval_workflow"."building_id" as "workflow_building_id",
+                "approval_workflow"."action" as "workflow_action",
+                coalesce(jsonb_agg(jsonb_build_object(
+                    'position', "approval_workflow_stage"."position",
+                    'roles', "approval_workflow_stage"."roles"
+                ) order by "approval_workflow_stage"."position", "approval_workflow_stage"."id")
+                filter (where "approval_workflow_stage"."id" is not null), '[]'::jsonb) as "workflow_stages"
+            from "approval_workflow"
+            left join "approval_workflow_stage" on "approval_workflow_stage"."workflow_id" = "approval_workflow"."id"
+            group by "approval_workflow"."id"
+            order by "approval_workflow"."building_id" nulls first, "approval_workflow"."action" nulls first
+            "#,
+        )
+        .map(map_approval_workflow)
+        .fetch_all(&self._db)
+        .await?;
+
+        Ok(result)
+    }
+
+    async fn find_one_approval_workflow(
+        &self,
+        workflow_id: i32,
+    ) -> Result<ApprovalWorkflow, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            select
+                "approval_workflow"."id" as "workflow_id",
+                "approval_workflow"."building_id" as "workflow_building_id",
+                "approval_workflow"."action" as "workflow_action",
+                coalesce(jsonb_agg(jsonb_build_object(
+                    'position', "approval_workflow_stage"."position",
+                    'roles', "approval_workflow_stage"."roles"
+                ) order by "approval_workflow_stage"."position", "approval_workflow_stage"."id")
+                filter (where "approval_workflow_stage"."id" is not null), '[]'::jsonb) as "workflow_stages"
+            from "approval_workflow"
+            left join "approval_workflow_stage" on "approval_workflow_stage"."workflow_id" = "approval_workflow"."id"
+            where "approval_workflow"."id" = $1
+            group by "approval_workflow"."id"
             "#,
-            &announcement_ids,
         )
-        .execute(&self._db)
+        .bind(workflow_id)
+        .map(map_approval_workflow)
+        .fetch_one(&self._db)
+        .await?;
+
+        Ok(result)
+    }
+
+    async fn save_approval_workflow(
+        &self,
+        unit_of_work: &mut UnitOfWork,
+        params: SaveApprovalWorkflowParams,
+    ) -> Result<i32, sqlx::Error> {
+        let existing_id: Option<i32> = sqlx::query(
+            r#"
+            select "id" from "approval_workflow"
+            where "building_id" is not distinct from $1 and "action" is not distinct from $2
+            "#,
+        )
+        .bind(params.building_id)
+        .bind(params.action.clone())
+        .map(|row: PgRow| row.get("id"))
+        .fetch_optional(unit_of_work.connection())
+        .await?;
+
+        let workflow_id = match existing_id {
+            Some(id) => {
+                sqlx::query(
+                    r#"
+                    delete from "approval_workflow_stage" where "workflow_id" = $1
+                    "#,
+                )
+                .bind(id)
+                .execute(unit_of_work.connection())
+                .await?;
+
+                sqlx::query(
+                    r#"
+                    update "approval_workflow" set "updated_at" = now() where "id" = $1
+                    "#,
+                )
+                .bind(id)
+                .execute(unit_of_work.connection())
+                .await?;
+
+                id
+            }
+            None => {
+                sqlx::query(
+                    r#"
+                    insert into "approval_workflow" ("building_id", "action")
+                    values ($1, $2)
+                    returning "id"
+                    "#,
+                )
+                .bind(params.building_id)
+                .bind(params.action)
+                .map(|row: PgRow| row.get("id"))
+                .fetch_one(unit_of_work.connection())
+                .await?
+            }
+        };
+
+        for stage in params.stages {
+            sqlx::query(
+                r#"
+                insert into "approval_workflow_stage" ("workflow_id", "position", "roles")
+                values ($1, $2, $3)
+                "#,
+            )
+            .bind(workflow_id)
+            .bind(stage.position)
+            .bind(stage.roles)
+            .execute(unit_of_work.connection())
+            .await?;
+        }
+
+        Ok(workflow_id)
+    }
+
+    async fn delete_approval_workflow(
+        &self,
+        unit_of_work: &mut UnitOfWork,
+        workflow_id: i32,
+    ) -> Result<(), sqlx::Error> {
+        let rows_affected = sqlx::query(
+            r#"
+            delete from "approval_workflow" where "id" = $1
+            "#,
+        )
+        .bind(workflow_id)
+        .execute(unit_of_work.connection())
         .await?
         .rows_affected();
 
@@ -426,3 +1142,27 @@ impl RequestRepositoryInterface for RequestRepository {
         Ok(())
     }
 }
+
+fn map_approval_workflow(row: PgRow) -> ApprovalWorkflow {
+    let stages: sqlx::types::Json<Vec<ApprovalWorkflowStage>> = row.get("workflow_stages");
+
+    ApprovalWorkflow {
+        id: row.get("workflow_id"),
+        building_id: row.get("workflow_building_id"),
+        action: row.get("workflow_action"),
+        stages: stages.0,
+    }
+}
+
+fn map_approval_delegation(row: PgRow) -> ApprovalDelegation {
+    ApprovalDelegation {
+        id: row.get("delegation_id"),
+        delegator_id: row.get("delegator_id"),
+        delegator_name: row.get("delegator_name"),
+        delegate_id: row.get("delegate_id"),
+        delegate_name: row.get("delegate_name"),
+        start_date: row.get("delegation_start_date"),
+        end_date: row.get("delegation_end_date"),
+        created_at: row.get("delegation_created_at"),
+    }
+}
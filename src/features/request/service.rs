@@ -3,20 +3,33 @@ use std::sync::Arc;
 use async_trait::async_trait;
 
 use crate::{
-    database::{DatabaseError, PaginationResult},
+    database::{DatabaseError, PaginationResult, UnitOfWork, UnitOfWorkFactoryInterface},
     features::{
         announcement::{
             AnnouncementQueueInterface, AnnouncementRepositoryInterface, AnnouncementStatus,
+            UpdateAnnouncementContentParams,
         },
         auth::AuthRepositoryInterface,
+        media::repository::MediaRepositoryInterface,
+        role::DEFAULT_ROLES,
+        user::UserStatus,
         AnnouncementDetail, DeviceRepositoryInterface,
     },
 };
 
 use super::{
-    BatchRejectRequestsFromAnnouncementIdsError, CreateRequestError, FindRequestParams,
-    InsertRequestParams, ListRequestError, Request, RequestActionType, RequestApproval,
-    RequestRepositoryInterface, UpdateApprovalParams, UpdateRequestApprovalError,
+    can_comment_on_request, can_delegate_approval, decide_request_approval, ApprovalDelegation,
+    ApprovalWorkflow,
+    BatchRejectRequestsFromAnnouncementIdsError, BulkRequestApprovalResult,
+    BulkUpdateRequestApprovalError, CreateApprovalDelegationError, CreateRequestCommentError,
+    CreateRequestError, DeleteApprovalDelegationError, DeleteApprovalWorkflowError,
+    FindRequestParams, InsertApprovalDelegationParams, InsertRequestCommentParams,
+    InsertRequestParams, ListApprovalDelegationError, ListApprovalWorkflowError,
+    ListRequestCommentError, ListRequestError, Request, RequestActionType, RequestApproval,
+    RequestApprovalDecisionError, RequestApprover, RequestComment, RequestRepositoryInterface,
+    RequestStatus,
+    SaveApprovalWorkflowError, SaveApprovalWorkflowParams, UpdateApprovalParams,
+    UpdateRequestApprovalError, WithdrawRequestError,
 };
 
 pub struct ListRequestParams {
@@ -26,6 +39,8 @@ pub struct ListRequestParams {
     pub announcement_id: Option<i32>,
     pub user_id: Option<i32>,
     pub action_type: Option<RequestActionType>,
+    pub status: Option<RequestStatus>,
+    pub awaiting_role: Option<String>,
     pub approved_by_lsc: Option<bool>,
     pub approved_by_bm: Option<bool>,
 }
@@ -38,6 +53,9 @@ pub struct CreateRequestParams {
 
     pub extended_end_date: Option<chrono::DateTime<chrono::Utc>>,
     pub new_device_ids: Option<Vec<i32>>,
+    pub new_title: Option<String>,
+    pub new_notes: Option<String>,
+    pub new_media_id: Option<i32>,
 }
 
 impl CreateRequestParams {
@@ -55,6 +73,9 @@ impl CreateRequestParams {
 
             extended_end_date: None,
             new_device_ids: None,
+            new_title: None,
+            new_notes: None,
+            new_media_id: None,
         }
     }
 
@@ -67,12 +88,48 @@ impl CreateRequestParams {
         self.new_device_ids = Some(new_device_ids);
         self
     }
+
+    pub fn new_title(mut self, new_title: String) -> Self {
+        self.new_title = Some(new_title);
+        self
+    }
+
+    pub fn new_notes(mut self, new_notes: String) -> Self {
+        self.new_notes = Some(new_notes);
+        self
+    }
+
+    pub fn new_media_id(mut self, new_media_id: i32) -> Self {
+        self.new_media_id = Some(new_media_id);
+        self
+    }
 }
 
 pub struct UpdateRequestApprovalParams {
     pub request_id: i32,
     pub approver_id: i32,
     pub approval: bool,
+    pub reason: Option<String>,
+}
+
+pub struct BulkUpdateRequestApprovalParams {
+    pub request_ids: Vec<i32>,
+    pub approver_id: i32,
+    pub approval: bool,
+    pub reason: Option<String>,
+}
+
+pub struct CreateApprovalDelegationParams {
+    pub delegator_id: i32,
+    pub delegate_id: i32,
+    pub start_date: chrono::DateTime<chrono::Utc>,
+    pub end_date: chrono::DateTime<chrono::Utc>,
+}
+
+pub struct CreateRequestCommentParams {
+    pub request_id: i32,
+    pub user_id: i32,
+    pub body: String,
 }
 
 #[async_trait]
@@ -86,26 +143,41 @@ pub trait RequestServiceInterface {
         &self,
         params: UpdateRequestApprovalParams,
     ) -> Result<(), UpdateRequestApprovalError>;
+    async fn bulk_update_request_approval(
+        &self,
+        params: BulkUpdateRequestApprovalParams,
+    ) -> Result<Vec<BulkRequestApprovalResult>, BulkUpdateRequestApprovalError>;
     async fn handle_update_request_approval_create(
         &self,
+        unit_of_work: &mut UnitOfWork,
         announcement: AnnouncementDetail,
         request: Request,
         approval: RequestApproval,
     ) -> Result<(), UpdateRequestApprovalError>;
     async fn handle_update_request_approval_extend_date(
         &self,
+        unit_of_work: &mut UnitOfWork,
         announcement: AnnouncementDetail,
         request: Request,
         approval: RequestApproval,
     ) -> Result<(), UpdateRequestApprovalError>;
     async fn handle_update_request_approval_delete(
         &self,
+        unit_of_work: &mut UnitOfWork,
         announcement: AnnouncementDetail,
         request: Request,
         approval: RequestApproval,
     ) -> Result<(), UpdateRequestApprovalError>;
     async fn handle_update_request_approval_change_devices(
         &self,
+        unit_of_work: &mut UnitOfWork,
+        announcement: AnnouncementDetail,
+        request: Request,
+        approval: RequestApproval,
+    ) -> Result<(), UpdateRequestApprovalError>;
+    async fn handle_update_request_approval_change_content(
+        &self,
+        unit_of_work: &mut UnitOfWork,
         announcement: AnnouncementDetail,
         request: Request,
         approval: RequestApproval,
@@ -114,6 +186,43 @@ pub trait RequestServiceInterface {
         &self,
         announcement_ids: Vec<i32>,
     ) -> Result<(), BatchRejectRequestsFromAnnouncementIdsError>;
+    async fn withdraw_request(
+        &self,
+        request_id: i32,
+        user_id: i32,
+    ) -> Result<(), WithdrawRequestError>;
+    async fn list_request_comments(
+        &self,
+        request_id: i32,
+    ) -> Result<Vec<RequestComment>, ListRequestCommentError>;
+    async fn create_request_comment(
+        &self,
+        params: CreateRequestCommentParams,
+    ) -> Result<i32, CreateRequestCommentError>;
+    async fn list_approval_delegations(
+        &self,
+        user_id: i32,
+    ) -> Result<Vec<ApprovalDelegation>, ListApprovalDelegationError>;
+    async fn create_approval_delegation(
+        &self,
+        params: CreateApprovalDelegationParams,
+    ) -> Result<i32, CreateApprovalDelegationError>;
+    async fn delete_approval_delegation(
+        &self,
+        delegation_id: i32,
+        user_id: i32,
+    ) -> Result<(), DeleteApprovalDelegationError>;
+    async fn list_approval_workflows(
+        &self,
+    ) -> Result<Vec<ApprovalWorkflow>, ListApprovalWorkflowError>;
+    async fn save_approval_workflow(
+        &self,
+        params: SaveApprovalWorkflowParams,
+    ) -> Result<i32, SaveApprovalWorkflowError>;
+    async fn delete_approval_workflow(
+        &self,
+        workflow_id: i32,
+    ) -> Result<(), DeleteApprovalWorkflowError>;
 }
 
 pub struct RequestService {
@@ -122,6 +231,8 @@ pub struct RequestService {
     _announcement_repository: Arc<dyn AnnouncementRepositoryInterface + Send + Sync + 'static>,
     _auth_repository: Arc<dyn AuthRepositoryInterface + Send + Sync + 'static>,
     _device_repository: Arc<dyn DeviceRepositoryInterface + Send + Sync + 'static>,
+    _media_repository: Arc<dyn MediaRepositoryInterface + Send + Sync + 'static>,
+    _unit_of_work: Arc<dyn UnitOfWorkFactoryInterface + Send + Sync + 'static>,
 }
 
 impl RequestService {
@@ -131,6 +242,8 @@ impl RequestService {
         _announcement_repository: Arc<dyn AnnouncementRepositoryInterface + Send + Sync + 'static>,
         _auth_repository: Arc<dyn AuthRepositoryInterface + Send + Sync + 'static>,
         _device_repository: Arc<dyn DeviceRepositoryInterface + Send + Sync + 'static>,
+        _media_repository: Arc<dyn MediaRepositoryInterface + Send + Sync + 'static>,
+        _unit_of_work: Arc<dyn UnitOfWorkFactoryInterface + Send + Sync + 'static>,
     ) -> Self {
         RequestService {
             _announcement_queue,
@@ -138,8 +251,29 @@ impl RequestService {
             _announcement_repository,
             _auth_repository,
             _device_repository,
+            _media_repository,
+            _unit_of_work,
         }
     }
+
+    /// The user along with the delegators they can currently approve on behalf of
+    async fn find_request_approver(&self, user_id: i32) -> Result<RequestApprover, sqlx::Error> {
+        let user = self
+            ._auth_repository
+            .find_one_auth_entity_by_id(user_id)
+            .await?;
+
+        let delegators = self
+            ._request_repository
+            .find_active_delegators(user.id, chrono::Utc::now())
+            .await?;
+
+        Ok(RequestApprover {
+            id: user.id,
+            role: user.role.value.to_string(),
+            delegators,
+        })
+    }
 }
 #[async_trait]
 impl RequestServiceInterface for RequestService {
@@ -156,6 +290,8 @@ impl RequestServiceInterface for RequestService {
                 announcement_id: params.announcement_id,
                 user_id: params.user_id,
                 action_type: params.action_type,
+                status: params.status,
+                awaiting_role: params.awaiting_role,
                 approved_by_lsc: params.approved_by_lsc,
                 approved_by_bm: params.approved_by_bm,
             })
@@ -183,9 +319,17 @@ impl RequestServiceInterface for RequestService {
             },
         };
 
+        // Emergencies skip the approval flow, they are only ever cleared by an admin
+        if announcement.is_emergency {
+            return Err(CreateRequestError::InvalidAnnouncementStatus(
+                "Unable to create request, emergency announcements can only be cleared",
+            ));
+        }
+
         if (params.action == RequestActionType::ExtendDate
             || params.action == RequestActionType::Delete
-            || params.action == RequestActionType::ChangeDevices)
+            || params.action == RequestActionType::ChangeDevices
+            || params.action == RequestActionType::ChangeContent)
             && announcement.status != AnnouncementStatus::Active
         {
             return Err(CreateRequestError::InvalidAnnouncementStatus(
@@ -221,8 +365,34 @@ impl RequestServiceInterface for RequestService {
 
             insert_params = insert_params.new_device_ids(new_device_ids)
         }
+        if let Some(new_title) = params.new_title {
+            insert_params = insert_params.new_title(new_title);
+        }
+        if let Some(new_notes) = params.new_notes {
+            insert_params = insert_params.new_notes(new_notes);
+        }
+        if let Some(new_media_id) = params.new_media_id {
+            let exists = match self._media_repository.exists(new_media_id).await {
+                Ok(exists) => exists,
+                Err(_) => return Err(CreateRequestError::InternalServerError),
+            };
+            if !exists {
+                return Err(CreateRequestError::MediaNotFound("Media not found"));
+            }
+
+            insert_params = insert_params.new_media_id(new_media_id);
+        }
 
-        if let Err(e) = self._request_repository.insert(insert_params).await {
+        let mut unit_of_work = match self._unit_of_work.begin().await {
+            Ok(unit_of_work) => unit_of_work,
+            Err(_) => return Err(CreateRequestError::InternalServerError),
+        };
+
+        if let Err(e) = self
+            ._request_repository
+            .insert(&mut unit_of_work, insert_params)
+            .await
+        {
             match e {
                 sqlx::Error::Database(db_error) => {
                     if let Some(code) = db_error.code() {
@@ -235,10 +405,19 @@ impl RequestServiceInterface for RequestService {
                     }
                     return Err(CreateRequestError::InternalServerError);
                 }
+                sqlx::Error::RowNotFound => {
+                    return Err(CreateRequestError::ApprovalWorkflowNotConfigured(
+                        "No approval workflow is configured for this request",
+                    ))
+                }
                 _ => return Err(CreateRequestError::InternalServerError),
             }
         }
 
+        if let Err(_) = unit_of_work.commit().await {
+            return Err(CreateRequestError::InternalServerError);
+        }
+
         Ok(())
     }
 
@@ -246,7 +425,18 @@ impl RequestServiceInterface for RequestService {
         &self,
         params: UpdateRequestApprovalParams,
     ) -> Result<(), UpdateRequestApprovalError> {
-        let request = match self._request_repository.find_one(params.request_id).await {
+        let mut unit_of_work = match self._unit_of_work.begin().await {
+            Ok(unit_of_work) => unit_of_work,
+            Err(_) => return Err(UpdateRequestApprovalError::InternalServerError),
+        };
+
+        // The request stays locked until the decision is committed, so approvers deciding at the same
+        // time see each other's steps and a withdrawal in flight either lands before or waits
+        let request = match self
+            ._request_repository
+            .find_one_for_update(&mut unit_of_work, params.request_id)
+            .await
+        {
             Ok(data) => data,
             Err(e) => match e {
                 sqlx::Error::RowNotFound => {
@@ -258,12 +448,14 @@ impl RequestServiceInterface for RequestService {
             },
         };
 
-        let approver = match self
-            ._auth_repository
-            .find_one_auth_entity_by_id(params.approver_id)
-            .await
-        {
-            Ok(entity) => entity,
+        if request.withdrawn_at.is_some() {
+            return Err(UpdateRequestApprovalError::RequestWithdrawn(
+                "Request has been withdrawn by its author".into(),
+            ));
+        }
+
+        let approver = match self.find_request_approver(params.approver_id).await {
+            Ok(approver) => approver,
             Err(e) => match e {
                 sqlx::Error::RowNotFound => {
                     return Err(UpdateRequestApprovalError::UserNotFound(
@@ -274,56 +466,40 @@ impl RequestServiceInterface for RequestService {
             },
         };
 
-        let approval_whitelist: Vec<&str> = vec!["lsc", "bm", "admin"];
-        if !approval_whitelist.contains(&approver.role.value) {
-            return Err(UpdateRequestApprovalError::UserForbiddenToApprove(
-                "User is not allowed to approve a request".into(),
-            ));
-        }
-
-        let mut approved_by_lsc = request.approved_by_lsc;
-        let mut lsc_approver = request.lsc_approver;
-        let mut approved_by_bm = request.approved_by_bm;
-        let mut bm_approver = request.bm_approver;
-
-        if approver.role.value == "lsc" {
-            if request.approved_by_lsc.is_some() {
-                return Err(UpdateRequestApprovalError::RequestAlreadyApproved(
-                    "Request already approved".into(),
-                ));
-            }
-
-            approved_by_lsc = Some(params.approval);
-            lsc_approver = Some(approver.id);
-        } else if approver.role.value == "bm" {
-            if request.approved_by_bm.is_some() {
-                return Err(UpdateRequestApprovalError::RequestAlreadyApproved(
-                    "Request already approved".into(),
-                ));
-            }
-
-            approved_by_bm = Some(params.approval);
-            bm_approver = Some(approver.id);
-        } else if approver.role.value == "admin" {
-            if request.approved_by_lsc.is_some() && request.approved_by_bm.is_some() {
-                return Err(UpdateRequestApprovalError::RequestAlreadyApproved(
-                    "Request already approved".into(),
-                ));
-            }
-
-            if request.approved_by_lsc.is_none() {
-                approved_by_lsc = Some(params.approval);
-                lsc_approver = Some(approver.id);
-            }
-            if request.approved_by_bm.is_none() {
-                approved_by_bm = Some(params.approval);
-                bm_approver = Some(approver.id);
-            }
-        }
+        let approval = match decide_request_approval(
+            &request.approval_steps,
+            &approver,
+            params.approval,
+            params.reason,
+        ) {
+            Ok(approval) => approval,
+            Err(e) => match e {
+                RequestApprovalDecisionError::AlreadyDecided => {
+                    return Err(UpdateRequestApprovalError::RequestAlreadyApproved(
+                        "Request already approved".into(),
+                    ))
+                }
+                RequestApprovalDecisionError::AlreadyApprovedByApprover => {
+                    return Err(UpdateRequestApprovalError::RequestAlreadyApproved(
+                        "Request already approved by this user or on their behalf".into(),
+                    ))
+                }
+                RequestApprovalDecisionError::RoleNotAllowed => {
+                    return Err(UpdateRequestApprovalError::UserForbiddenToApprove(
+                        "User is not allowed to approve the current stage of the request".into(),
+                    ))
+                }
+                RequestApprovalDecisionError::RejectionReasonRequired => {
+                    return Err(UpdateRequestApprovalError::RejectionReasonRequired(
+                        "A reason is required to reject a request".into(),
+                    ))
+                }
+            },
+        };
 
         let announcement = match self
             ._announcement_repository
-            .find_one(request.announcement_id)
+            .find_one_in_unit_of_work(&mut unit_of_work, request.announcement_id)
             .await
         {
             Ok(data) => data,
@@ -337,35 +513,105 @@ impl RequestServiceInterface for RequestService {
             },
         };
 
-        let approval = RequestApproval {
-            approved_by_bm,
-            approved_by_lsc,
-            bm_approver,
-            lsc_approver,
-        };
-
-        match request.action {
+        let result = match request.action {
             RequestActionType::Create => {
-                self.handle_update_request_approval_create(announcement, request, approval)
-                    .await
+                self.handle_update_request_approval_create(
+                    &mut unit_of_work,
+                    announcement,
+                    request,
+                    approval,
+                )
+                .await
             }
             RequestActionType::Delete => {
-                self.handle_update_request_approval_delete(announcement, request, approval)
-                    .await
+                self.handle_update_request_approval_delete(
+                    &mut unit_of_work,
+                    announcement,
+                    request,
+                    approval,
+                )
+                .await
             }
             RequestActionType::ExtendDate => {
-                self.handle_update_request_approval_extend_date(announcement, request, approval)
-                    .await
+                self.handle_update_request_approval_extend_date(
+                    &mut unit_of_work,
+                    announcement,
+                    request,
+                    approval,
+                )
+                .await
             }
             RequestActionType::ChangeDevices => {
-                self.handle_update_request_approval_change_devices(announcement, request, approval)
-                    .await
+                self.handle_update_request_approval_change_devices(
+                    &mut unit_of_work,
+                    announcement,
+                    request,
+                    approval,
+                )
+                .await
             }
+            RequestActionType::ChangeContent => {
+                self.handle_update_request_approval_change_content(
+                    &mut unit_of_work,
+                    announcement,
+                    request,
+                    approval,
+                )
+                .await
+            }
+        };
+        result?;
+
+        match unit_of_work.commit().await {
+            Ok(_) => Ok(()),
+            Err(_) => Err(UpdateRequestApprovalError::InternalServerError),
+        }
+    }
+
+    async fn bulk_update_request_approval(
+        &self,
+        params: BulkUpdateRequestApprovalParams,
+    ) -> Result<Vec<BulkRequestApprovalResult>, BulkUpdateRequestApprovalError> {
+        let reason = params
+            .reason
+            .map(|reason| reason.trim().to_string())
+            .filter(|reason| !reason.is_empty());
+        if !params.approval && reason.is_none() {
+            return Err(BulkUpdateRequestApprovalError::RejectionReasonRequired(
+                "A reason is required to reject a request",
+            ));
+        }
+
+        let mut request_ids: Vec<i32> = Vec::new();
+        for request_id in params.request_ids {
+            if !request_ids.contains(&request_id) {
+                request_ids.push(request_id);
+            }
+        }
+
+        // Every request is decided in its own unit of work, so one failing request does not roll
+        // back the others. Only one request is locked at a time, approvers working through the same
+        // batch in a different order wait on each other instead of deadlocking.
+        let mut results: Vec<BulkRequestApprovalResult> = Vec::new();
+        for request_id in request_ids {
+            let result = self
+                .update_request_approval(UpdateRequestApprovalParams {
+                    request_id,
+                    approver_id: params.approver_id,
+                    approval: params.approval,
+                    reason: reason.clone(),
+                })
+                .await;
+
+            results.push(BulkRequestApprovalResult { request_id, result });
         }
+
+        Ok(results)
     }
 
     async fn handle_update_request_approval_create(
         &self,
+        unit_of_work: &mut UnitOfWork,
         announcement: AnnouncementDetail,
         request: Request,
         approval: RequestApproval,
@@ -376,9 +622,9 @@ impl RequestServiceInterface for RequestService {
             ));
         }
 
-        if approval.approved_by_bm == Some(true) && approval.approved_by_lsc == Some(true) {
-            let today = chrono::Utc::today().and_hms(0, 0, 0);
-            if today == announcement.start_date {
+        if approval.status == RequestStatus::Approved {
+            if announcement.start_date <= chrono::Utc::now() {
+                let is_displayable = announcement.is_displayable();
                 let device_ids: Vec<i32> = announcement
                     .devices
                     .into_iter()
@@ -387,38 +633,53 @@ impl RequestServiceInterface for RequestService {
 
                 if let Err(_) = self
                     ._announcement_repository
-                    .update_status(announcement.id, AnnouncementStatus::Active)
-                    .await
-                {
-                    return Err(UpdateRequestApprovalError::InternalServerError);
-                }
-
-                if let Err(_) = self
-                    ._announcement_queue
-                    .create(
-                        device_ids,
+                    .update_status(
+                        unit_of_work,
                         announcement.id,
-                        announcement.media_type.to_string(),
-                        announcement.media_duration,
+                        AnnouncementStatus::Active,
                     )
                     .await
                 {
                     return Err(UpdateRequestApprovalError::InternalServerError);
                 }
+
+                if is_displayable {
+                    if let Err(_) = self
+                        ._announcement_queue
+                        .create(
+                            unit_of_work,
+                            device_ids,
+                            announcement.id,
+                            announcement.media_type.to_string(),
+                            announcement.media_duration,
+                            announcement.recurrence,
+                        )
+                        .await
+                    {
+                        return Err(UpdateRequestApprovalError::InternalServerError);
+                    }
+                }
             } else {
                 if let Err(_) = self
                     ._announcement_repository
-                    .update_status(announcement.id, AnnouncementStatus::WaitingForSync)
+                    .update_status(
+                        unit_of_work,
+                        announcement.id,
+                        AnnouncementStatus::WaitingForSync,
+                    )
                     .await
                 {
                     return Err(UpdateRequestApprovalError::InternalServerError);
                 }
             }
-        } else if approval.approved_by_bm == Some(false) || approval.approved_by_lsc == Some(false)
-        {
+        } else if approval.status == RequestStatus::Rejected {
             if let Err(_) = self
                 ._announcement_repository
-                .update_status(announcement.id, AnnouncementStatus::Rejected)
+                .update_status(
+                    unit_of_work,
+                    announcement.id,
+                    AnnouncementStatus::Rejected,
+                )
                 .await
             {
                 return Err(UpdateRequestApprovalError::InternalServerError);
@@ -427,13 +688,17 @@ impl RequestServiceInterface for RequestService {
 
         if let Err(_) = self
             ._request_repository
-            .update_approval(UpdateApprovalParams {
-                request_id: request.id,
-                approved_by_lsc: approval.approved_by_lsc,
-                approved_by_bm: approval.approved_by_bm,
-                lsc_approver: approval.lsc_approver,
-                bm_approver: approval.bm_approver,
-            })
+            .update_approval(
+                unit_of_work,
+                UpdateApprovalParams {
+                    request_id: request.id,
+                    step_ids: approval.step_ids,
+                    approved: approval.approved,
+                    approver_id: approval.approver_id,
+                    on_behalf_of: approval.on_behalf_of,
+                    reason: approval.reason,
+                },
+            )
             .await
         {
             return Err(UpdateRequestApprovalError::InternalServerError);
@@ -444,6 +709,7 @@ impl RequestServiceInterface for RequestService {
 
     async fn handle_update_request_approval_delete(
         &self,
+        unit_of_work: &mut UnitOfWork,
         announcement: AnnouncementDetail,
         request: Request,
         approval: RequestApproval,
@@ -454,7 +720,7 @@ impl RequestServiceInterface for RequestService {
             ));
         }
 
-        if approval.approved_by_bm == Some(true) && approval.approved_by_lsc == Some(true) {
+        if approval.status == RequestStatus::Approved {
             let device_ids: Vec<i32> = announcement
                 .devices
                 .into_iter()
@@ -463,7 +729,7 @@ impl RequestServiceInterface for RequestService {
 
             if let Err(_) = self
                 ._announcement_queue
-                .delete(device_ids, announcement.id)
+                .delete(unit_of_work, device_ids, announcement.id)
                 .await
             {
                 return Err(UpdateRequestApprovalError::InternalServerError);
@@ -471,14 +737,22 @@ impl RequestServiceInterface for RequestService {
 
             if let Err(_) = self
                 ._announcement_repository
-                .update_status(announcement.id, AnnouncementStatus::Canceled)
+                .update_status(
+                    unit_of_work,
+                    announcement.id,
+                    AnnouncementStatus::Canceled,
+                )
                 .await
             {
                 return Err(UpdateRequestApprovalError::InternalServerError);
             }
 
             if let Err(_) = self
-                .batch_reject_requests_from_announcement_ids(vec![announcement.id])
+                ._request_repository
+                .batch_reject_requests_from_announcement_ids(
+                    unit_of_work,
+                    vec![announcement.id],
+                )
                 .await
             {
                 return Err(UpdateRequestApprovalError::InternalServerError);
@@ -487,13 +761,17 @@ impl RequestServiceInterface for RequestService {
 
         if let Err(_) = self
             ._request_repository
-            .update_approval(UpdateApprovalParams {
-                request_id: request.id,
-                approved_by_lsc: approval.approved_by_lsc,
-                approved_by_bm: approval.approved_by_bm,
-                lsc_approver: approval.lsc_approver,
-                bm_approver: approval.bm_approver,
-            })
+            .update_approval(
+                unit_of_work,
+                UpdateApprovalParams {
+                    request_id: request.id,
+                    step_ids: approval.step_ids,
+                    approved: approval.approved,
+                    approver_id: approval.approver_id,
+                    on_behalf_of: approval.on_behalf_of,
+                    reason: approval.reason,
+                },
+            )
             .await
         {
             return Err(UpdateRequestApprovalError::InternalServerError);
@@ -504,6 +782,7 @@ impl RequestServiceInterface for RequestService {
 
     async fn handle_update_request_approval_extend_date(
         &self,
+        unit_of_work: &mut UnitOfWork,
         announcement: AnnouncementDetail,
         request: Request,
         approval: RequestApproval,
@@ -514,10 +793,14 @@ impl RequestServiceInterface for RequestService {
             ));
         }
 
-        if approval.approved_by_bm == Some(true) && approval.approved_by_lsc == Some(true) {
+        if approval.status == RequestStatus::Approved {
             if let Err(_) = self
                 ._announcement_repository
-                .extend_end_date(announcement.id, request.metadata.extended_end_date.unwrap())
+                .extend_end_date(
+                    unit_of_work,
+                    announcement.id,
+                    request.metadata.extended_end_date.unwrap(),
+                )
                 .await
             {
                 return Err(UpdateRequestApprovalError::InternalServerError);
@@ -526,13 +809,17 @@ impl RequestServiceInterface for RequestService {
 
         if let Err(_) = self
             ._request_repository
-            .update_approval(UpdateApprovalParams {
-                request_id: request.id,
-                approved_by_lsc: approval.approved_by_lsc,
-                approved_by_bm: approval.approved_by_bm,
-                lsc_approver: approval.lsc_approver,
-                bm_approver: approval.bm_approver,
-            })
+            .update_approval(
+                unit_of_work,
+                UpdateApprovalParams {
+                    request_id: request.id,
+                    step_ids: approval.step_ids,
+                    approved: approval.approved,
+                    approver_id: approval.approver_id,
+                    on_behalf_of: approval.on_behalf_of,
+                    reason: approval.reason,
+                },
+            )
             .await
         {
             return Err(UpdateRequestApprovalError::InternalServerError);
@@ -543,6 +830,7 @@ impl RequestServiceInterface for RequestService {
 
     async fn handle_update_request_approval_change_devices(
         &self,
+        unit_of_work: &mut UnitOfWork,
         announcement: AnnouncementDetail,
         request: Request,
         approval: RequestApproval,
@@ -553,7 +841,8 @@ impl RequestServiceInterface for RequestService {
             ));
         }
 
-        if approval.approved_by_bm == Some(true) && approval.approved_by_lsc == Some(true) {
+        if approval.status == RequestStatus::Approved {
+            let is_displayable = announcement.is_displayable();
             let old_device_ids: Vec<i32> = announcement
                 .devices
                 .into_iter()
@@ -578,6 +867,7 @@ impl RequestServiceInterface for RequestService {
             if let Err(_) = self
                 ._announcement_repository
                 .update_announcement_target_devices(
+                    unit_of_work,
                     announcement.id,
                     need_to_unsync_ids.clone(),
                     need_to_sync_ids.clone(),
@@ -589,35 +879,146 @@ impl RequestServiceInterface for RequestService {
 
             if let Err(_) = self
                 ._announcement_queue
-                .delete(need_to_unsync_ids.clone(), announcement.id)
+                .delete(
+                    unit_of_work,
+                    need_to_unsync_ids.clone(),
+                    announcement.id,
+                )
                 .await
             {
                 return Err(UpdateRequestApprovalError::InternalServerError);
             }
 
+            if is_displayable {
+                if let Err(_) = self
+                    ._announcement_queue
+                    .create(
+                        unit_of_work,
+                        need_to_sync_ids.clone(),
+                        announcement.id,
+                        announcement.media_type.to_string(),
+                        announcement.media_duration,
+                        announcement.recurrence,
+                    )
+                    .await
+                {
+                    return Err(UpdateRequestApprovalError::InternalServerError);
+                }
+            }
+        }
+
+        if let Err(_) = self
+            ._request_repository
+            .update_approval(
+                unit_of_work,
+                UpdateApprovalParams {
+                    request_id: request.id,
+                    step_ids: approval.step_ids,
+                    approved: approval.approved,
+                    approver_id: approval.approver_id,
+                    on_behalf_of: approval.on_behalf_of,
+                    reason: approval.reason,
+                },
+            )
+            .await
+        {
+            return Err(UpdateRequestApprovalError::InternalServerError);
+        }
+
+        Ok(())
+    }
+
+    async fn handle_update_request_approval_change_content(
+        &self,
+        unit_of_work: &mut UnitOfWork,
+        announcement: AnnouncementDetail,
+        request: Request,
+        approval: RequestApproval,
+    ) -> Result<(), UpdateRequestApprovalError> {
+        if announcement.status != AnnouncementStatus::Active {
+            return Err(UpdateRequestApprovalError::InvalidAnnouncementStatus(
+                "Announcement status should be Active".into(),
+            ));
+        }
+
+        if approval.status == RequestStatus::Approved {
             if let Err(_) = self
-                ._announcement_queue
-                .create(
-                    need_to_sync_ids.clone(),
+                ._announcement_repository
+                .update_content(
+                    unit_of_work,
                     announcement.id,
-                    announcement.media_type.to_string(),
-                    announcement.media_duration,
+                    UpdateAnnouncementContentParams {
+                        title: request.metadata.new_title.clone(),
+                        notes: request.metadata.new_notes.clone(),
+                        media_id: request.metadata.new_media_id,
+                    },
                 )
                 .await
             {
                 return Err(UpdateRequestApprovalError::InternalServerError);
             }
+
+            let updated_announcement = match self
+                ._announcement_repository
+                .find_one_in_unit_of_work(unit_of_work, announcement.id)
+                .await
+            {
+                Ok(data) => data,
+                Err(_) => return Err(UpdateRequestApprovalError::InternalServerError),
+            };
+
+            let is_displayable = updated_announcement.is_displayable();
+            let device_ids: Vec<i32> = updated_announcement
+                .devices
+                .into_iter()
+                .map(|device| device.id)
+                .collect();
+
+            // Devices cache the media by announcement id, so the announcement is removed and
+            // re-added to make them fetch the new content
+            if let Err(_) = self
+                ._announcement_queue
+                .delete(
+                    unit_of_work,
+                    device_ids.clone(),
+                    updated_announcement.id,
+                )
+                .await
+            {
+                return Err(UpdateRequestApprovalError::InternalServerError);
+            }
+
+            if is_displayable {
+                if let Err(_) = self
+                    ._announcement_queue
+                    .create(
+                        unit_of_work,
+                        device_ids,
+                        updated_announcement.id,
+                        updated_announcement.media_type.to_string(),
+                        updated_announcement.media_duration,
+                        updated_announcement.recurrence,
+                    )
+                    .await
+                {
+                    return Err(UpdateRequestApprovalError::InternalServerError);
+                }
+            }
         }
 
         if let Err(_) = self
             ._request_repository
-            .update_approval(UpdateApprovalParams {
-                request_id: request.id,
-                approved_by_lsc: approval.approved_by_lsc,
-                approved_by_bm: approval.approved_by_bm,
-                lsc_approver: approval.lsc_approver,
-                bm_approver: approval.bm_approver,
-            })
+            .update_approval(
+                unit_of_work,
+                UpdateApprovalParams {
+                    request_id: request.id,
+                    step_ids: approval.step_ids,
+                    approved: approval.approved,
+                    approver_id: approval.approver_id,
+                    on_behalf_of: approval.on_behalf_of,
+                    reason: approval.reason,
+                },
+            )
             .await
         {
             return Err(UpdateRequestApprovalError::InternalServerError);
@@ -630,13 +1031,472 @@ impl RequestServiceInterface for RequestService {
         &self,
         announcement_ids: Vec<i32>,
     ) -> Result<(), BatchRejectRequestsFromAnnouncementIdsError> {
-        match self
+        let mut unit_of_work = match self._unit_of_work.begin().await {
+            Ok(unit_of_work) => unit_of_work,
+            Err(_) => return Err(BatchRejectRequestsFromAnnouncementIdsError::InternalServerError),
+        };
+
+        if let Err(_) = self
             ._request_repository
-            .batch_reject_requests_from_announcement_ids(announcement_ids)
+            .batch_reject_requests_from_announcement_ids(&mut unit_of_work, announcement_ids)
             .await
         {
+            return Err(BatchRejectRequestsFromAnnouncementIdsError::InternalServerError);
+        }
+
+        match unit_of_work.commit().await {
             Ok(_) => Ok(()),
             Err(_) => Err(BatchRejectRequestsFromAnnouncementIdsError::InternalServerError),
         }
     }
+    async fn withdraw_request(
+        &self,
+        request_id: i32,
+        user_id: i32,
+    ) -> Result<(), WithdrawRequestError> {
+        let mut unit_of_work = match self._unit_of_work.begin().await {
+            Ok(unit_of_work) => unit_of_work,
+            Err(_) => return Err(WithdrawRequestError::InternalServerError),
+        };
+
+        // The request stays locked until the withdrawal is committed, so a decision in flight either
+        // lands before the request is checked or waits for it
+        let request = match self
+            ._request_repository
+            .find_one_for_update(&mut unit_of_work, request_id)
+            .await
+        {
+            Ok(data) => data,
+            Err(e) => match e {
+                sqlx::Error::RowNotFound => {
+                    return Err(WithdrawRequestError::RequestNotFound("Request not found"))
+                }
+                _ => return Err(WithdrawRequestError::InternalServerError),
+            },
+        };
+
+        if request.user_id != user_id {
+            return Err(WithdrawRequestError::NotRequestOwner(
+                "Only the author of a request can withdraw it",
+            ));
+        }
+
+        if !request.is_pending() {
+            return Err(WithdrawRequestError::RequestNotPending(
+                "Only pending requests can be withdrawn",
+            ));
+        }
+
+        if let Err(e) = self
+            ._request_repository
+            .withdraw(&mut unit_of_work, request.id)
+            .await
+        {
+            match e {
+                sqlx::Error::RowNotFound => {
+                    return Err(WithdrawRequestError::RequestNotPending(
+                        "Only pending requests can be withdrawn",
+                    ))
+                }
+                _ => return Err(WithdrawRequestError::InternalServerError),
+            }
+        }
+
+        // A withdrawn create request hands the announcement back to its author as a draft, so
+        // it can be edited and submitted again.
+        if request.action == RequestActionType::Create {
+            let announcement = match self
+                ._announcement_repository
+                .find_one_in_unit_of_work(&mut unit_of_work, request.announcement_id)
+                .await
+            {
+                Ok(data) => data,
+                Err(_) => return Err(WithdrawRequestError::InternalServerError),
+            };
+
+            if announcement.status == AnnouncementStatus::WaitingForApproval {
+                if let Err(_) = self
+                    ._announcement_repository
+                    .update_status(&mut unit_of_work, announcement.id, AnnouncementStatus::Draft)
+                    .await
+                {
+                    return Err(WithdrawRequestError::InternalServerError);
+                }
+            }
+        }
+
+        match unit_of_work.commit().await {
+            Ok(_) => Ok(()),
+            Err(_) => Err(WithdrawRequestError::InternalServerError),
+        }
+    }
+    async fn list_request_comments(
+        &self,
+        request_id: i32,
+    ) -> Result<Vec<RequestComment>, ListRequestCommentError> {
+        if let Err(e) = self._request_repository.find_one(request_id).await {
+            match e {
+                sqlx::Error::RowNotFound => {
+                    return Err(ListRequestCommentError::RequestNotFound("Request not found"))
+                }
+                _ => return Err(ListRequestCommentError::InternalServerError),
+            }
+        }
+
+        match self._request_repository.find_comments(request_id).await {
+            Ok(comments) => Ok(comments),
+            Err(_) => Err(ListRequestCommentError::InternalServerError),
+        }
+    }
+
+    async fn create_request_comment(
+        &self,
+        params: CreateRequestCommentParams,
+    ) -> Result<i32, CreateRequestCommentError> {
+        let request = match self._request_repository.find_one(params.request_id).await {
+            Ok(data) => data,
+            Err(e) => match e {
+                sqlx::Error::RowNotFound => {
+                    return Err(CreateRequestCommentError::RequestNotFound("Request not found"))
+                }
+                _ => return Err(CreateRequestCommentError::InternalServerError),
+            },
+        };
+
+        let user = match self.find_request_approver(params.user_id).await {
+            Ok(user) => user,
+            Err(_) => return Err(CreateRequestCommentError::InternalServerError),
+        };
+
+        if !can_comment_on_request(request.user_id, &request.approval_steps, &user) {
+            return Err(CreateRequestCommentError::NotAllowedToComment(
+                "Only the author and the approvers of the current stage can comment on the request",
+            ));
+        }
+
+        let mut unit_of_work = match self._unit_of_work.begin().await {
+            Ok(unit_of_work) => unit_of_work,
+            Err(_) => return Err(CreateRequestCommentError::InternalServerError),
+        };
+
+        let comment_id = match self
+            ._request_repository
+            .insert_comment(
+                &mut unit_of_work,
+                InsertRequestCommentParams {
+                    request_id: params.request_id,
+                    user_id: params.user_id,
+                    approval_step_id: None,
+                    body: params.body,
+                },
+            )
+            .await
+        {
+            Ok(id) => id,
+            Err(e) => match e {
+                sqlx::Error::Database(db_error) => {
+                    if let Some(code) = db_error.code() {
+                        let code = code.to_string();
+                        if code == DatabaseError::ForeignKeyError.to_string() {
+                            return Err(CreateRequestCommentError::RequestNotFound(
+                                "Request not found",
+                            ));
+                        }
+                    }
+                    return Err(CreateRequestCommentError::InternalServerError);
+                }
+                _ => return Err(CreateRequestCommentError::InternalServerError),
+            },
+        };
+
+        match unit_of_work.commit().await {
+            Ok(_) => Ok(comment_id),
+            Err(_) => Err(CreateRequestCommentError::InternalServerError),
+        }
+    }
+
+    async fn list_approval_delegations(
+        &self,
+        user_id: i32,
+    ) -> Result<Vec<ApprovalDelegation>, ListApprovalDelegationError> {
+        match self._request_repository.find_delegations(user_id).await {
+            Ok(delegations) => Ok(delegations),
+            Err(_) => Err(ListApprovalDelegationError::InternalServerError),
+        }
+    }
+
+    async fn create_approval_delegation(
+        &self,
+        params: CreateApprovalDelegationParams,
+    ) -> Result<i32, CreateApprovalDelegationError> {
+        if params.delegate_id == params.delegator_id {
+            return Err(CreateApprovalDelegationError::InvalidDelegate(
+                "Approval rights can not be delegated to yourself",
+            ));
+        }
+
+        if params.start_date > params.end_date {
+            return Err(CreateApprovalDelegationError::InvalidDateRange(
+                "Start date must not be after the end date",
+            ));
+        }
+
+        if params.end_date + chrono::Duration::days(1) <= chrono::Utc::now() {
+            return Err(CreateApprovalDelegationError::InvalidDateRange(
+                "End date must not be in the past",
+            ));
+        }
+
+        let delegator = match self
+            ._auth_repository
+            .find_one_auth_entity_by_id(params.delegator_id)
+            .await
+        {
+            Ok(entity) => entity,
+            Err(_) => return Err(CreateApprovalDelegationError::InternalServerError),
+        };
+
+        let workflows = match self._request_repository.find_approval_workflows().await {
+            Ok(workflows) => workflows,
+            Err(_) => return Err(CreateApprovalDelegationError::InternalServerError),
+        };
+
+        if !can_delegate_approval(&workflows, delegator.role.value) {
+            return Err(CreateApprovalDelegationError::DelegatorNotApprover(
+                "Only users who can approve a workflow stage can delegate their approval rights",
+            ));
+        }
+
+        let delegate = match self
+            ._auth_repository
+            .find_one_auth_entity_by_id(params.delegate_id)
+            .await
+        {
+            Ok(entity) => entity,
+            Err(e) => match e {
+                sqlx::Error::RowNotFound => {
+                    return Err(CreateApprovalDelegationError::DelegateNotFound(
+                        "Delegate not found",
+                    ))
+                }
+                _ => return Err(CreateApprovalDelegationError::InternalServerError),
+            },
+        };
+
+        if delegate.user_status != UserStatus::Approved {
+            return Err(CreateApprovalDelegationError::InvalidDelegate(
+                "Approval rights can only be delegated to an approved user",
+            ));
+        }
+
+        let mut unit_of_work = match self._unit_of_work.begin().await {
+            Ok(unit_of_work) => unit_of_work,
+            Err(_) => return Err(CreateApprovalDelegationError::InternalServerError),
+        };
+
+        let delegation_id = match self
+            ._request_repository
+            .insert_delegation(
+                &mut unit_of_work,
+                InsertApprovalDelegationParams {
+                    delegator_id: params.delegator_id,
+                    delegate_id: params.delegate_id,
+                    start_date: params.start_date,
+                    end_date: params.end_date,
+                },
+            )
+            .await
+        {
+            Ok(id) => id,
+            Err(_) => return Err(CreateApprovalDelegationError::InternalServerError),
+        };
+
+        match unit_of_work.commit().await {
+            Ok(_) => Ok(delegation_id),
+            Err(_) => Err(CreateApprovalDelegationError::InternalServerError),
+        }
+    }
+
+    async fn delete_approval_delegation(
+        &self,
+        delegation_id: i32,
+        user_id: i32,
+    ) -> Result<(), DeleteApprovalDelegationError> {
+        let delegation = match self
+            ._request_repository
+            .find_one_delegation(delegation_id)
+            .await
+        {
+            Ok(delegation) => delegation,
+            Err(e) => match e {
+                sqlx::Error::RowNotFound => {
+                    return Err(DeleteApprovalDelegationError::ApprovalDelegationNotFound(
+                        "Approval delegation not found",
+                    ))
+                }
+                _ => return Err(DeleteApprovalDelegationError::InternalServerError),
+            },
+        };
+
+        if delegation.delegator_id != user_id {
+            return Err(DeleteApprovalDelegationError::NotDelegationOwner(
+                "Only the user who delegated their approval rights can revoke them",
+            ));
+        }
+
+        let mut unit_of_work = match self._unit_of_work.begin().await {
+            Ok(unit_of_work) => unit_of_work,
+            Err(_) => return Err(DeleteApprovalDelegationError::InternalServerError),
+        };
+
+        if let Err(e) = self
+            ._request_repository
+            .delete_delegation(&mut unit_of_work, delegation.id)
+            .await
+        {
+            match e {
+                sqlx::Error::RowNotFound => {
+                    return Err(DeleteApprovalDelegationError::ApprovalDelegationNotFound(
+                        "Approval delegation not found",
+                    ))
+                }
+                _ => return Err(DeleteApprovalDelegationError::InternalServerError),
+            }
+        }
+
+        match unit_of_work.commit().await {
+            Ok(_) => Ok(()),
+            Err(_) => Err(DeleteApprovalDelegationError::InternalServerError),
+        }
+    }
+
+    async fn list_approval_workflows(
+        &self,
+    ) -> Result<Vec<ApprovalWorkflow>, ListApprovalWorkflowError> {
+        match self._request_repository.find_approval_workflows().await {
+            Ok(workflows) => Ok(workflows),
+            Err(_) => Err(ListApprovalWorkflowError::InternalServerError),
+        }
+    }
+
+    async fn save_approval_workflow(
+        &self,
+        params: SaveApprovalWorkflowParams,
+    ) -> Result<i32, SaveApprovalWorkflowError> {
+        if params.stages.is_empty() {
+            return Err(SaveApprovalWorkflowError::InvalidApprovalWorkflow(
+                "An approval workflow needs at least one stage".into(),
+            ));
+        }
+
+        for (index, stage) in params.stages.iter().enumerate() {
+            if stage.position < 1 {
+                return Err(SaveApprovalWorkflowError::InvalidApprovalWorkflow(format!(
+                    "Stage {} needs a position of at least 1",
+                    index + 1
+                )));
+            }
+
+            if stage.roles.is_empty() {
+                return Err(SaveApprovalWorkflowError::InvalidApprovalWorkflow(format!(
+                    "Stage {} needs at least one role that can approve it",
+                    index + 1
+                )));
+            }
+
+            for role in &stage.roles {
+                if !DEFAULT_ROLES.iter().any(|r| r.value == role) {
+                    return Err(SaveApprovalWorkflowError::InvalidApprovalWorkflow(format!(
+                        "Unknown role {} on stage {}",
+                        role,
+                        index + 1
+                    )));
+                }
+            }
+        }
+
+        let mut unit_of_work = match self._unit_of_work.begin().await {
+            Ok(unit_of_work) => unit_of_work,
+            Err(_) => return Err(SaveApprovalWorkflowError::InternalServerError),
+        };
+
+        let workflow_id = match self
+            ._request_repository
+            .save_approval_workflow(&mut unit_of_work, params)
+            .await
+        {
+            Ok(id) => id,
+            Err(e) => match e {
+                sqlx::Error::Database(db_error) => {
+                    if let Some(code) = db_error.code() {
+                        let code = code.to_string();
+                        if code == DatabaseError::ForeignKeyError.to_string() {
+                            return Err(SaveApprovalWorkflowError::BuildingNotFound(
+                                "Building not found",
+                            ));
+                        }
+                    }
+                    return Err(SaveApprovalWorkflowError::InternalServerError);
+                }
+                _ => return Err(SaveApprovalWorkflowError::InternalServerError),
+            },
+        };
+
+        match unit_of_work.commit().await {
+            Ok(_) => Ok(workflow_id),
+            Err(_) => Err(SaveApprovalWorkflowError::InternalServerError),
+        }
+    }
+
+    async fn delete_approval_workflow(
+        &self,
+        workflow_id: i32,
+    ) -> Result<(), DeleteApprovalWorkflowError> {
+        let workflow = match self
+            ._request_repository
+            .find_one_approval_workflow(workflow_id)
+            .await
+        {
+            Ok(workflow) => workflow,
+            Err(e) => match e {
+                sqlx::Error::RowNotFound => {
+                    return Err(DeleteApprovalWorkflowError::ApprovalWorkflowNotFound(
+                        "Approval workflow not found",
+                    ))
+                }
+                _ => return Err(DeleteApprovalWorkflowError::InternalServerError),
+            },
+        };
+
+        if workflow.is_default() {
+            return Err(DeleteApprovalWorkflowError::DefaultApprovalWorkflowRequired(
+                "The default approval workflow can only be replaced, not deleted",
+            ));
+        }
+
+        let mut unit_of_work = match self._unit_of_work.begin().await {
+            Ok(unit_of_work) => unit_of_work,
+            Err(_) => return Err(DeleteApprovalWorkflowError::InternalServerError),
+        };
+
+        if let Err(e) = self
+            ._request_repository
+            .delete_approval_workflow(&mut unit_of_work, workflow.id)
+            .await
+        {
+            match e {
+                sqlx::Error::RowNotFound => {
+                    return Err(DeleteApprovalWorkflowError::ApprovalWorkflowNotFound(
+                        "Approval workflow not found",
+                    ))
+                }
+                _ => return Err(DeleteApprovalWorkflowError::InternalServerError),
+            }
+        }
+
+        match unit_of_work.commit().await {
+            Ok(_) => Ok(()),
+            Err(_) => Err(DeleteApprovalWorkflowError::InternalServerError),
+        }
+    }
 }
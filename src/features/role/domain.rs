@@ -35,15 +35,23 @@ pub static DEFAULT_ROLES: &'static [ApplicationRole] = &[
             ApplicationPermission::ViewAnnouncementDetail,
             ApplicationPermission::ViewAnnouncementMedia,
             ApplicationPermission::CreateAnnouncement,
+            ApplicationPermission::BroadcastEmergencyAnnouncement,
             // Request
             ApplicationPermission::ViewListRequest,
             ApplicationPermission::CreateRequest,
             ApplicationPermission::UpdateRequestApproval,
+            ApplicationPermission::ManageApprovalWorkflow,
             // User
             ApplicationPermission::ViewListUser,
             ApplicationPermission::UpdateUserApproval,
             // Media
             ApplicationPermission::CreateMedia,
+            // Job
+            ApplicationPermission::ViewListJob,
+            ApplicationPermission::RunJob,
+            // Queue
+            ApplicationPermission::ViewListQueue,
+            ApplicationPermission::ManageQueue,
         ],
     },
     ApplicationRole {
@@ -146,15 +154,23 @@ pub enum ApplicationPermission {
     ViewAnnouncementDetail,
     ViewAnnouncementMedia,
     CreateAnnouncement,
+    BroadcastEmergencyAnnouncement,
     // Request
     ViewListRequest,
     CreateRequest,
     UpdateRequestApproval,
+    ManageApprovalWorkflow,
     // User
     ViewListUser,
     UpdateUserApproval,
     // Media
     CreateMedia,
+    // Job
+    ViewListJob,
+    RunJob,
+    // Queue
+    ViewListQueue,
+    ManageQueue,
 }
 
 impl ApplicationPermission {
@@ -178,12 +194,20 @@ impl ApplicationPermission {
             ApplicationPermission::ViewAnnouncementDetail => "View Announcement Detail",
             ApplicationPermission::ViewAnnouncementMedia => "View Announcement Media",
             ApplicationPermission::CreateAnnouncement => "Create Announcement",
+            ApplicationPermission::BroadcastEmergencyAnnouncement => {
+                "Broadcast Emergency Announcement"
+            }
             ApplicationPermission::ViewListRequest => "View List Request",
             ApplicationPermission::CreateRequest => "Create Request",
             ApplicationPermission::UpdateRequestApproval => "Update Request Approval",
+            ApplicationPermission::ManageApprovalWorkflow => "Manage Approval Workflow",
             ApplicationPermission::ViewListUser => "View List User",
             ApplicationPermission::UpdateUserApproval => "Update User Approval",
             ApplicationPermission::CreateMedia => "Create Media",
+            ApplicationPermission::ViewListJob => "View List Job",
+            ApplicationPermission::RunJob => "Run Job",
+            ApplicationPermission::ViewListQueue => "View List Queue",
+            ApplicationPermission::ManageQueue => "Manage Queue",
         }
     }
 
@@ -207,12 +231,20 @@ impl ApplicationPermission {
             ApplicationPermission::ViewAnnouncementDetail => "view_announcement_detail",
             ApplicationPermission::ViewAnnouncementMedia => "view_announcement_media",
             ApplicationPermission::CreateAnnouncement => "create_announcement",
+            ApplicationPermission::BroadcastEmergencyAnnouncement => {
+                "broadcast_emergency_announcement"
+            }
             ApplicationPermission::ViewListRequest => "view_list_request",
             ApplicationPermission::CreateRequest => "create_request",
             ApplicationPermission::UpdateRequestApproval => "update_request_approval",
+            ApplicationPermission::ManageApprovalWorkflow => "manage_approval_workflow",
             ApplicationPermission::ViewListUser => "view_list_user",
             ApplicationPermission::UpdateUserApproval => "update_user_approval",
             ApplicationPermission::CreateMedia => "create_media",
+            ApplicationPermission::ViewListJob => "view_list_job",
+            ApplicationPermission::RunJob => "run_job",
+            ApplicationPermission::ViewListQueue => "view_list_queue",
+            ApplicationPermission::ManageQueue => "manage_queue",
         }
     }
 }
@@ -9,8 +9,10 @@ use crate::features::{
     device::{device_http as device_http_device, http as device_http_dashboard},
     device_status,
     floor::http as floor_http,
+    job::http as job_http,
     livestream,
     media::http as media_http,
+    queue::http as queue_http,
     request::http as request_http,
     role::{http as role_http, ApplicationPermission},
     user::{http as user_http, UserStatus},
@@ -64,6 +66,35 @@ pub fn device_routes(
                 ))
                 .to(announcement_http::get_announcement_media_presigned_url_device),
         )
+        .service(
+            web::resource("/v1/playlist")
+                .guard(guard::Get())
+                .wrap(DeviceAuthenticationMiddlewareFactory::new(
+                    device_service.clone(),
+                ))
+                .to(announcement_http::get_device_playlist_device),
+        )
+        .service(
+            web::resource("/v1/manifest")
+                .guard(guard::Get())
+                .wrap(DeviceAuthenticationMiddlewareFactory::new(
+                    device_service.clone(),
+                ))
+                .to(announcement_http::get_device_manifest_device),
+        )
+        .service(
+            web::resource("/v1/schemas/sync-envelope")
+                .guard(guard::Get())
+                .to(announcement_http::get_sync_envelope_schema_device),
+        )
+        .service(
+            web::resource("/v1/announcements/{announcement_id}/delivery")
+                .guard(guard::Put())
+                .wrap(DeviceAuthenticationMiddlewareFactory::new(
+                    device_service.clone(),
+                ))
+                .to(announcement_http::report_announcement_delivery_status_device),
+        )
 }
 
 pub fn dashboard_routes(
@@ -204,6 +235,28 @@ pub fn dashboard_routes(
         )
         .service(
             web::scope("/v1/devices")
+                .service(
+                    web::resource("/{device_id}/queue")
+                        .guard(guard::Get())
+                        .wrap(
+                            AuthenticationMiddlewareFactory::new(auth_service.clone())
+                                .with_permission(ApplicationPermission::ViewListQueue)
+                                .with_status(UserStatus::Approved)
+                                .with_require_email_confirmed(true),
+                        )
+                        .to(queue_http::inspect_device_queue),
+                )
+                .service(
+                    web::resource("/{device_id}/queue")
+                        .guard(guard::Delete())
+                        .wrap(
+                            AuthenticationMiddlewareFactory::new(auth_service.clone())
+                                .with_permission(ApplicationPermission::ManageQueue)
+                                .with_status(UserStatus::Approved)
+                                .with_require_email_confirmed(true),
+                        )
+                        .to(queue_http::purge_device_queue),
+                )
                 .service(
                     web::resource("/{device_id}/livestream")
                         .guard(guard::Get())
@@ -284,6 +337,54 @@ pub fn dashboard_routes(
         )
         .service(
             web::scope("/v1/announcements")
+                .service(
+                    web::resource("/emergency")
+                        .guard(guard::Post())
+                        .wrap(
+                            AuthenticationMiddlewareFactory::new(auth_service.clone())
+                                .with_permission(
+                                    ApplicationPermission::BroadcastEmergencyAnnouncement,
+                                )
+                                .with_status(UserStatus::Approved)
+                                .with_require_email_confirmed(true),
+                        )
+                        .to(announcement_http::broadcast_emergency_announcement),
+                )
+                .service(
+                    web::resource("/{announcement_id}/emergency/clear")
+                        .guard(guard::Post())
+                        .wrap(
+                            AuthenticationMiddlewareFactory::new(auth_service.clone())
+                                .with_permission(
+                                    ApplicationPermission::BroadcastEmergencyAnnouncement,
+                                )
+                                .with_status(UserStatus::Approved)
+                                .with_require_email_confirmed(true),
+                        )
+                        .to(announcement_http::clear_emergency_announcement),
+                )
+                .service(
+                    web::resource("/{announcement_id}/submit")
+                        .guard(guard::Post())
+                        .wrap(
+                            AuthenticationMiddlewareFactory::new(auth_service.clone())
+                                .with_permission(ApplicationPermission::CreateAnnouncement)
+                                .with_status(UserStatus::Approved)
+                                .with_require_email_confirmed(true),
+                        )
+                        .to(announcement_http::submit_draft_announcement),
+                )
+                .service(
+                    web::resource("/{announcement_id}/cancel")
+                        .guard(guard::Post())
+                        .wrap(
+                            AuthenticationMiddlewareFactory::new(auth_service.clone())
+                                .with_permission(ApplicationPermission::CreateAnnouncement)
+                                .with_status(UserStatus::Approved)
+                                .with_require_email_confirmed(true),
+                        )
+                        .to(announcement_http::cancel_announcement),
+                )
                 .service(
                     web::resource("/{announcement_id}/media")
                         .guard(guard::Get())
@@ -306,6 +407,28 @@ pub fn dashboard_routes(
                         )
                         .to(announcement_http::get_announcement_detail),
                 )
+                .service(
+                    web::resource("/{announcement_id}")
+                        .guard(guard::Put())
+                        .wrap(
+                            AuthenticationMiddlewareFactory::new(auth_service.clone())
+                                .with_permission(ApplicationPermission::CreateAnnouncement)
+                                .with_status(UserStatus::Approved)
+                                .with_require_email_confirmed(true),
+                        )
+                        .to(announcement_http::update_draft_announcement),
+                )
+                .service(
+                    web::resource("/{announcement_id}")
+                        .guard(guard::Delete())
+                        .wrap(
+                            AuthenticationMiddlewareFactory::new(auth_service.clone())
+                                .with_permission(ApplicationPermission::CreateAnnouncement)
+                                .with_status(UserStatus::Approved)
+                                .with_require_email_confirmed(true),
+                        )
+                        .to(announcement_http::delete_draft_announcement),
+                )
                 .service(
                     web::resource("")
                         .guard(guard::Get())
@@ -336,12 +459,58 @@ pub fn dashboard_routes(
                         .guard(guard::Put())
                         .wrap(
                             AuthenticationMiddlewareFactory::new(auth_service.clone())
-                                .with_permission(ApplicationPermission::UpdateRequestApproval)
+                                // The stages of the request and active delegations decide who can approve it
+                                .with_permission(ApplicationPermission::ViewListRequest)
                                 .with_status(UserStatus::Approved)
                                 .with_require_email_confirmed(true),
                         )
                         .to(request_http::update_request_approval),
                 )
+                .service(
+                    web::resource("/approval")
+                        .guard(guard::Put())
+                        .wrap(
+                            AuthenticationMiddlewareFactory::new(auth_service.clone())
+                                .with_permission(ApplicationPermission::ViewListRequest)
+                                .with_status(UserStatus::Approved)
+                                .with_require_email_confirmed(true),
+                        )
+                        .to(request_http::bulk_update_request_approval),
+                )
+                .service(
+                    web::resource("/{request_id}/withdraw")
+                        .guard(guard::Post())
+                        .wrap(
+                            AuthenticationMiddlewareFactory::new(auth_service.clone())
+                                .with_permission(ApplicationPermission::CreateRequest)
+                                .with_status(UserStatus::Approved)
+                                .with_require_email_confirmed(true),
+                        )
+                        .to(request_http::withdraw_request),
+                )
+                .service(
+                    web::resource("/{request_id}/comments")
+                        .guard(guard::Get())
+                        .wrap(
+                            AuthenticationMiddlewareFactory::new(auth_service.clone())
+                                .with_permission(ApplicationPermission::ViewListRequest)
+                                .with_status(UserStatus::Approved)
+                                .with_require_email_confirmed(true),
+                        )
+                        .to(request_http::list_request_comments),
+                )
+                .service(
+                    // Posting is further limited to the author and the approvers of the current stage
+                    web::resource("/{request_id}/comments")
+                        .guard(guard::Post())
+                        .wrap(
+                            AuthenticationMiddlewareFactory::new(auth_service.clone())
+                                .with_permission(ApplicationPermission::ViewListRequest)
+                                .with_status(UserStatus::Approved)
+                                .with_require_email_confirmed(true),
+                        )
+                        .to(request_http::create_request_comment),
+                )
                 .service(
                     web::resource("")
                         .guard(guard::Post())
@@ -365,6 +534,78 @@ pub fn dashboard_routes(
                         .to(request_http::list_request),
                 ),
         )
+        .service(
+            web::scope("/v1/approval-workflows")
+                .service(
+                    web::resource("/{workflow_id}")
+                        .guard(guard::Delete())
+                        .wrap(
+                            AuthenticationMiddlewareFactory::new(auth_service.clone())
+                                .with_permission(ApplicationPermission::ManageApprovalWorkflow)
+                                .with_status(UserStatus::Approved)
+                                .with_require_email_confirmed(true),
+                        )
+                        .to(request_http::delete_approval_workflow),
+                )
+                .service(
+                    web::resource("")
+                        .guard(guard::Put())
+                        .wrap(
+                            AuthenticationMiddlewareFactory::new(auth_service.clone())
+                                .with_permission(ApplicationPermission::ManageApprovalWorkflow)
+                                .with_status(UserStatus::Approved)
+                                .with_require_email_confirmed(true),
+                        )
+                        .to(request_http::save_approval_workflow),
+                )
+                .service(
+                    web::resource("")
+                        .guard(guard::Get())
+                        .wrap(
+                            AuthenticationMiddlewareFactory::new(auth_service.clone())
+                                .with_permission(ApplicationPermission::ManageApprovalWorkflow)
+                                .with_status(UserStatus::Approved)
+                                .with_require_email_confirmed(true),
+                        )
+                        .to(request_http::list_approval_workflows),
+                ),
+        )
+        .service(
+            web::scope("/v1/approval-delegations")
+                .service(
+                    web::resource("/{delegation_id}")
+                        .guard(guard::Delete())
+                        .wrap(
+                            AuthenticationMiddlewareFactory::new(auth_service.clone())
+                                .with_permission(ApplicationPermission::UpdateRequestApproval)
+                                .with_status(UserStatus::Approved)
+                                .with_require_email_confirmed(true),
+                        )
+                        .to(request_http::delete_approval_delegation),
+                )
+                .service(
+                    web::resource("")
+                        .guard(guard::Post())
+                        .wrap(
+                            AuthenticationMiddlewareFactory::new(auth_service.clone())
+                                .with_permission(ApplicationPermission::UpdateRequestApproval)
+                                .with_status(UserStatus::Approved)
+                                .with_require_email_confirmed(true),
+                        )
+                        .to(request_http::create_approval_delegation),
+                )
+                .service(
+                    web::resource("")
+                        .guard(guard::Get())
+                        .wrap(
+                            AuthenticationMiddlewareFactory::new(auth_service.clone())
+                                .with_permission(ApplicationPermission::ViewListRequest)
+                                .with_status(UserStatus::Approved)
+                                .with_require_email_confirmed(true),
+                        )
+                        .to(request_http::list_approval_delegations),
+                ),
+        )
         .service(
             web::scope("/v1/users")
                 .service(
@@ -403,6 +644,78 @@ pub fn dashboard_routes(
                     .to(media_http::upload),
             ),
         )
+        .service(
+            web::scope("/v1/jobs")
+                .service(
+                    web::resource("/{job_name}/runs")
+                        .guard(guard::Get())
+                        .wrap(
+                            AuthenticationMiddlewareFactory::new(auth_service.clone())
+                                .with_permission(ApplicationPermission::ViewListJob)
+                                .with_status(UserStatus::Approved)
+                                .with_require_email_confirmed(true),
+                        )
+                        .to(job_http::list_job_runs),
+                )
+                .service(
+                    web::resource("/{job_name}/run")
+                        .guard(guard::Post())
+                        .wrap(
+                            AuthenticationMiddlewareFactory::new(auth_service.clone())
+                                .with_permission(ApplicationPermission::RunJob)
+                                .with_status(UserStatus::Approved)
+                                .with_require_email_confirmed(true),
+                        )
+                        .to(job_http::run_job),
+                )
+                .service(
+                    web::resource("")
+                        .guard(guard::Get())
+                        .wrap(
+                            AuthenticationMiddlewareFactory::new(auth_service.clone())
+                                .with_permission(ApplicationPermission::ViewListJob)
+                                .with_status(UserStatus::Approved)
+                                .with_require_email_confirmed(true),
+                        )
+                        .to(job_http::list_jobs),
+                ),
+        )
+        .service(
+            web::scope("/v1/queues")
+                .service(
+                    web::resource("/{queue_name}/dead-letters/{dead_letter_id}/replay")
+                        .guard(guard::Post())
+                        .wrap(
+                            AuthenticationMiddlewareFactory::new(auth_service.clone())
+                                .with_permission(ApplicationPermission::ManageQueue)
+                                .with_status(UserStatus::Approved)
+                                .with_require_email_confirmed(true),
+                        )
+                        .to(queue_http::replay_dead_letter),
+                )
+                .service(
+                    web::resource("/{queue_name}/dead-letters")
+                        .guard(guard::Get())
+                        .wrap(
+                            AuthenticationMiddlewareFactory::new(auth_service.clone())
+                                .with_permission(ApplicationPermission::ViewListQueue)
+                                .with_status(UserStatus::Approved)
+                                .with_require_email_confirmed(true),
+                        )
+                        .to(queue_http::list_dead_letters),
+                )
+                .service(
+                    web::resource("/{queue_name}/dead-letters")
+                        .guard(guard::Delete())
+                        .wrap(
+                            AuthenticationMiddlewareFactory::new(auth_service.clone())
+                                .with_permission(ApplicationPermission::ManageQueue)
+                                .with_status(UserStatus::Approved)
+                                .with_require_email_confirmed(true),
+                        )
+                        .to(queue_http::purge_dead_letters),
+                ),
+        )
 }
 
 pub fn socket_routes() -> Scope {
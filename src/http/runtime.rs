@@ -16,8 +16,10 @@ use crate::{
         device::DeviceServiceInterface,
         device_status::socket::StatusSocketServer,
         floor::FloorServiceInterface,
+        job::service::JobServiceInterface,
         livestream::{service::LivestreamServiceInterface, socket::LivestreamSocketServer},
         media::service::MediaServiceInterface,
+        queue::service::QueueServiceInterface,
         request::RequestServiceInterface,
         role::RoleServiceInterface,
         user::UserServiceInterface,
@@ -47,6 +49,8 @@ impl WebServer {
         announcement_service: Arc<dyn AnnouncementServiceInterface + Send + Sync + 'static>,
         livestream_service: Arc<dyn LivestreamServiceInterface>,
         media_service: Arc<dyn MediaServiceInterface>,
+        job_service: Arc<dyn JobServiceInterface>,
+        queue_service: Arc<dyn QueueServiceInterface>,
         status_socket_server_addr: Addr<StatusSocketServer>,
         livestream_socket_server_addr: Addr<LivestreamSocketServer>,
     ) -> Result<Self, std::io::Error> {
@@ -60,6 +64,8 @@ impl WebServer {
         let announcement_svc = web::Data::new(announcement_service.clone());
         let livestream_svc = web::Data::new(livestream_service.clone());
         let media_svc = web::Data::new(media_service.clone());
+        let job_svc = web::Data::new(job_service.clone());
+        let queue_svc = web::Data::new(queue_service.clone());
         let status_socket_srv = web::Data::new(status_socket_server_addr);
         let livestream_socket_srv = web::Data::new(livestream_socket_server_addr);
 
@@ -78,6 +84,8 @@ impl WebServer {
                 .app_data(announcement_svc.clone())
                 .app_data(livestream_svc.clone())
                 .app_data(media_svc.clone())
+                .app_data(job_svc.clone())
+                .app_data(queue_svc.clone())
                 .app_data(status_socket_srv.clone())
                 .app_data(livestream_socket_srv.clone())
                 // .wrap(Logger::default())
@@ -8,3 +8,5 @@ pub mod cloud_storage;
 pub mod features;
 pub mod shutdown;
 pub mod scheduler;
+pub mod lock;
+pub mod outbox;
@@ -0,0 +1,117 @@
+use deadpool_redis::redis::{cmd, Script};
+
+#[derive(Debug)]
+pub enum LockError {
+    RedisError(String),
+}
+
+impl std::fmt::Display for LockError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            LockError::RedisError(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+const RELEASE_SCRIPT: &str = r#"
+if redis.call("get", KEYS[1]) == ARGV[1] then
+    return redis.call("del", KEYS[1])
+else
+    return 0
+end
+"#;
+
+const EXTEND_SCRIPT: &str = r#"
+if redis.call("get", KEYS[1]) == ARGV[1] then
+    return redis.call("pexpire", KEYS[1], ARGV[2])
+else
+    return 0
+end
+"#;
+
+/// Lease based lock shared by every instance of the application through redis,
+/// a lock can only be extended or released by the instance that owns it.
+#[derive(Clone)]
+pub struct DistributedLock {
+    client: deadpool_redis::Pool,
+    owner: String,
+}
+
+impl DistributedLock {
+    pub fn new(client: deadpool_redis::Pool, owner: String) -> Self {
+        DistributedLock { client, owner }
+    }
+
+    pub fn owner(&self) -> &str {
+        self.owner.as_str()
+    }
+
+    async fn redis(&self) -> Result<deadpool_redis::Connection, LockError> {
+        match self.client.get().await {
+            Ok(conn) => Ok(conn),
+            Err(e) => Err(LockError::RedisError(e.to_string())),
+        }
+    }
+
+    /// Returns `false` when the lock is currently held by someone else.
+    pub async fn acquire(&self, key: &str, ttl: std::time::Duration) -> Result<bool, LockError> {
+        let mut conn = self.redis().await?;
+
+        let result: Option<String> = match cmd("SET")
+            .arg(key)
+            .arg(self.owner.as_str())
+            .arg("NX")
+            .arg("PX")
+            .arg(ttl.as_millis() as u64)
+            .query_async(&mut conn)
+            .await
+        {
+            Ok(result) => result,
+            Err(e) => return Err(LockError::RedisError(e.to_string())),
+        };
+
+        Ok(result.is_some())
+    }
+
+    /// Returns `false` when the lock has expired or has been taken over by someone else.
+    pub async fn extend(&self, key: &str, ttl: std::time::Duration) -> Result<bool, LockError> {
+        let mut conn = self.redis().await?;
+
+        let result: i32 = match Script::new(EXTEND_SCRIPT)
+            .key(key)
+            .arg(self.owner.as_str())
+            .arg(ttl.as_millis() as u64)
+            .invoke_async(&mut conn)
+            .await
+        {
+            Ok(result) => result,
+            Err(e) => return Err(LockError::RedisError(e.to_string())),
+        };
+
+        Ok(result == 1)
+    }
+
+    pub async fn release(&self, key: &str) -> Result<(), LockError> {
+        let mut conn = self.redis().await?;
+
+        if let Err(e) = Script::new(RELEASE_SCRIPT)
+            .key(key)
+            .arg(self.owner.as_str())
+            .invoke_async::<_, i32>(&mut conn)
+            .await
+        {
+            return Err(LockError::RedisError(e.to_string()));
+        }
+
+        Ok(())
+    }
+
+    pub async fn is_locked(&self, key: &str) -> Result<bool, LockError> {
+        let mut conn = self.redis().await?;
+
+        match cmd("EXISTS").arg(key).query_async::<_, i32>(&mut conn).await {
+            Ok(result) => Ok(result == 1),
+            Err(e) => Err(LockError::RedisError(e.to_string())),
+        }
+    }
+}
@@ -0,0 +1,3 @@
+pub mod distributed_lock;
+
+pub use distributed_lock::*;
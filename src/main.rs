@@ -1,12 +1,22 @@
 use std::net::TcpListener;
 use std::sync::Arc;
+use std::time::Duration;
 use std::{env, process};
 
+use chrono_tz::Asia::Jakarta;
 use enchiridion_api::cloud_storage::LocalAdapter;
+use enchiridion_api::features::job::repository::JobRepository;
+use enchiridion_api::features::job::service::JobService;
+use enchiridion_api::features::livestream::definition::DEVICE_LIVESTREAM_QUEUE_NAME;
 use enchiridion_api::features::livestream::repository::LivestreamRepository;
 use enchiridion_api::features::livestream::service::LivestreamService;
 use enchiridion_api::features::media::repository::MediaRepository;
 use enchiridion_api::features::media::service::MediaService;
+use enchiridion_api::features::queue::service::QueueService;
+use enchiridion_api::database::UnitOfWorkFactory;
+use enchiridion_api::lock::DistributedLock;
+use enchiridion_api::outbox::OutboxRepository;
+use enchiridion_api::queue::{Consumer, RedisQueueBackend, RetryPolicy};
 use secrecy::ExposeSecret;
 use sqlx::PgPool;
 
@@ -25,6 +35,7 @@ use enchiridion_api::{
         user::{UserRepository, UserService},
         AuthServiceInterface, DeviceServiceInterface,
     },
+    scheduler::{AnnouncementSchedulerJob, JobRegistry},
     startup::run,
 };
 
@@ -74,8 +85,18 @@ async fn main() -> std::io::Result<()> {
     let request_repository = Arc::new(RequestRepository::new(pool.clone()));
     let livestream_repository = Arc::new(LivestreamRepository::new(pool.clone()));
     let media_repository = Arc::new(MediaRepository::new(pool.clone()));
+    let job_repository = Arc::new(JobRepository::new(pool.clone()));
 
-    let announcement_queue = Arc::new(AnnouncementQueue::new(redis_pool.clone()));
+    let unit_of_work = Arc::new(UnitOfWorkFactory::new(pool.clone()));
+
+    let outbox_repository = Arc::new(OutboxRepository::new(pool.clone()));
+
+    let queue_backend = Arc::new(RedisQueueBackend::new(redis_pool.clone()));
+
+    let announcement_queue = Arc::new(AnnouncementQueue::new(
+        outbox_repository.clone(),
+        queue_backend.clone(),
+    ));
 
     let role_service = Arc::new(RoleService::new());
     let building_service = Arc::new(BuildingService::new(building_repository.clone()));
@@ -91,6 +112,7 @@ async fn main() -> std::io::Result<()> {
     let device_service = Arc::new(DeviceService::new(
         device_repository.clone(),
         announcement_queue.clone(),
+        unit_of_work.clone(),
     ));
     let request_service = Arc::new(RequestService::new(
         announcement_queue.clone(),
@@ -98,11 +120,14 @@ async fn main() -> std::io::Result<()> {
         announcement_repository.clone(),
         auth_repository.clone(),
         device_repository.clone(),
+        media_repository.clone(),
+        unit_of_work.clone(),
     ));
     let announcement_service = Arc::new(AnnouncementService::new(
         announcement_repository.clone(),
         announcement_queue.clone(),
-        request_service.clone(),
+        request_repository.clone(),
+        unit_of_work.clone(),
         cloud_storage,
     ));
     let livestream_service = Arc::new(LivestreamService::new(livestream_repository));
@@ -113,6 +138,42 @@ async fn main() -> std::io::Result<()> {
 
     let media_service = Arc::new(MediaService::new(media_repository, cloud_storage));
 
+    let lock = DistributedLock::new(redis_pool.clone(), config.instance_id.clone());
+
+    let job_registry = JobRegistry::new(job_repository.clone(), lock.clone())
+        .register(
+            "announcement_scheduler",
+            "Moves announcements through their statuses and syncs recurring occurrences",
+            "0 * * * * *",
+            Jakarta,
+            Arc::new(AnnouncementSchedulerJob::new(announcement_service.clone())),
+        )
+        .unwrap_or_else(|e| {
+            println!("Something when wrong when registering the scheduler jobs: {}", e);
+            process::exit(1);
+        });
+    let job_registry = Arc::new(job_registry);
+
+    let job_service = Arc::new(JobService::new(job_repository, job_registry.clone()));
+
+    let queue_service = Arc::new(QueueService::new(
+        redis_pool.clone(),
+        device_repository.clone(),
+    ));
+
+    let livestream_consumer = Consumer::new(
+        redis_pool.clone(),
+        DEVICE_LIVESTREAM_QUEUE_NAME.to_string(),
+        format!("{}-consumer", config.instance_id),
+    )
+    .with_claim_min_idle(Duration::from_millis(config.queue_claim_min_idle_ms))
+    .with_trim_policy(config.queue_livestream_trim_policy.clone());
+
+    let retry_policy = RetryPolicy::new(
+        config.queue_max_deliveries,
+        Duration::from_millis(config.queue_retry_backoff_ms),
+    );
+
     auth_service.seed_default_user().await.unwrap_or_else(|e| {
         println!("Something when wrong when seeding the default user: {}", e);
         process::exit(1);
@@ -142,6 +203,15 @@ async fn main() -> std::io::Result<()> {
         announcement_service.clone(),
         livestream_service.clone(),
         media_service.clone(),
+        job_service.clone(),
+        queue_service.clone(),
+        job_registry.clone(),
+        outbox_repository.clone(),
+        lock,
+        queue_backend,
+        Box::new(livestream_consumer),
+        retry_policy,
+        config.queue_device_trim_policy.clone(),
     )
     .await
 }
@@ -0,0 +1,4 @@
+pub mod relay;
+pub mod repository;
+
+pub use repository::*;
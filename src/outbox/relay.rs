@@ -0,0 +1,190 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
+
+use tokio::{
+    sync::{mpsc, oneshot},
+    time::{sleep, Instant},
+};
+
+use crate::{
+    lock::DistributedLock,
+    queue::{QueueBackendInterface, TrimPolicy},
+    shutdown::Shutdown,
+};
+
+use super::{OutboxMessage, OutboxRepositoryInterface};
+
+const SLEEP_DURATION: Duration = Duration::from_millis(500);
+const BATCH_SIZE: i32 = 100;
+
+/// Only a single instance relays the outbox at a time so the messages of a queue keep their order.
+/// The lock is extended for as long as a batch is still being relayed.
+const RELAY_LOCK_KEY: &str = "outbox:relay";
+const RELAY_LOCK_TTL: Duration = Duration::from_secs(30);
+
+/// Delivered messages are kept for a while to help tracing a device sync, then purged
+const DELIVERED_RETENTION_DAYS: i64 = 7;
+const PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+pub async fn run(
+    mut shutdown: Shutdown,
+    _sender: mpsc::Sender<()>,
+    queue_backend: Arc<dyn QueueBackendInterface>,
+    outbox_repository: Arc<dyn OutboxRepositoryInterface>,
+    lock: DistributedLock,
+    trim_policy: TrimPolicy,
+) {
+    let (tx, mut rx) = mpsc::channel::<oneshot::Sender<bool>>(32);
+    let tx_2 = tx.clone();
+
+    let relay = actix_web::rt::spawn(async move {
+        println!("Outbox relay has started");
+
+        let mut last_purge: Option<Instant> = None;
+
+        loop {
+            if let Ok(resp) = rx.try_recv() {
+                let _ = resp.send(true);
+                break;
+            }
+
+            sleep(SLEEP_DURATION).await;
+
+            match lock.acquire(RELAY_LOCK_KEY, RELAY_LOCK_TTL).await {
+                Ok(true) => (),
+                Ok(false) => continue,
+                Err(e) => {
+                    eprintln!("Something went wrong when acquiring the outbox relay lock: {}", e);
+                    continue;
+                }
+            }
+
+            let messages = match outbox_repository.find_undelivered(BATCH_SIZE).await {
+                Ok(messages) => messages,
+                Err(e) => {
+                    eprintln!("Something went wrong when reading the outbox: {}", e);
+                    Vec::new()
+                }
+            };
+
+            let batch = relay_messages(queue_backend.as_ref(), outbox_repository.clone(), messages, &trim_policy);
+            tokio::pin!(batch);
+
+            let mut heartbeat = tokio::time::interval(RELAY_LOCK_TTL / 3);
+            heartbeat.tick().await;
+
+            let is_lock_lost = loop {
+                tokio::select! {
+                    _ = &mut batch => break false,
+                    _ = heartbeat.tick() => {
+                        match lock.extend(RELAY_LOCK_KEY, RELAY_LOCK_TTL).await {
+                            Ok(true) => (),
+                            // Another instance may be relaying the same messages already, the rest
+                            // of the batch is left to it
+                            Ok(false) => {
+                                eprintln!("The outbox relay lock has been lost, stopping the current batch");
+                                break true;
+                            }
+                            Err(e) => eprintln!("Something went wrong when extending the outbox relay lock: {}", e),
+                        }
+                    }
+                }
+            };
+
+            if is_lock_lost {
+                continue;
+            }
+
+            let is_purge_due = match last_purge {
+                Some(purged_at) => purged_at.elapsed() >= PURGE_INTERVAL,
+                None => true,
+            };
+
+            if is_purge_due {
+                let delivered_before =
+                    chrono::Utc::now() - chrono::Duration::days(DELIVERED_RETENTION_DAYS);
+                match outbox_repository.delete_delivered(delivered_before).await {
+                    Ok(count) if count > 0 => {
+                        println!("Purged {} delivered outbox messages", count)
+                    }
+                    Ok(_) => (),
+                    Err(e) => eprintln!("Something went wrong when purging the outbox: {}", e),
+                }
+                last_purge = Some(Instant::now());
+            }
+
+            if let Err(e) = lock.release(RELAY_LOCK_KEY).await {
+                eprintln!("Something went wrong when releasing the outbox relay lock: {}", e);
+            }
+        }
+    });
+
+    let shutdown_listener = actix_web::rt::spawn(async move {
+        let _ = shutdown.recv().await;
+
+        let (resp_tx, resp_rx) = oneshot::channel::<bool>();
+        if let Err(e) = tx_2.send(resp_tx).await {
+            eprintln!("Something went wrong when sending shutdown signal: {}", e);
+            return;
+        }
+
+        let _ = resp_rx.await;
+        println!("Outbox relay finished shutting down");
+    });
+
+    tokio::try_join!(relay, shutdown_listener).unwrap();
+}
+
+/// Pushes a batch of outbox messages to their queues and marks the relayed ones as delivered
+pub async fn relay_messages(
+    queue_backend: &dyn QueueBackendInterface,
+    outbox_repository: Arc<dyn OutboxRepositoryInterface>,
+    messages: Vec<OutboxMessage>,
+    trim_policy: &TrimPolicy,
+) {
+    // Once a message fails the rest of its queue waits for the next round, so a device
+    // never receives its messages out of order
+    let mut blocked_queue_names: HashSet<String> = HashSet::new();
+
+    for message in messages {
+        if blocked_queue_names.contains(&message.queue_name) {
+            continue;
+        }
+
+        let mut payload: BTreeMap<String, String> = BTreeMap::new();
+        payload.insert("data".into(), message.payload);
+
+        let producer = queue_backend.producer(message.queue_name.clone(), trim_policy.clone());
+        if let Err(e) = producer.push(payload).await {
+            eprintln!(
+                "Something went wrong when relaying the outbox message {} to {} (attempt {}): {}",
+                message.id,
+                message.queue_name,
+                message.attempts + 1,
+                e
+            );
+
+            if let Err(e) = outbox_repository
+                .record_failure(message.id, e.to_string())
+                .await
+            {
+                eprintln!("Something went wrong when recording the outbox failure: {}", e);
+            }
+
+            blocked_queue_names.insert(message.queue_name);
+            continue;
+        }
+
+        if let Err(e) = outbox_repository.mark_delivered(message.id).await {
+            // The message is relayed again on the next round, the delivery is at least once
+            eprintln!(
+                "Something went wrong when marking the outbox message {} as delivered: {}",
+                message.id, e
+            );
+            blocked_queue_names.insert(message.queue_name);
+        }
+    }
+}
@@ -0,0 +1,174 @@
+use async_trait::async_trait;
+use sqlx::{postgres::PgRow, Pool, Postgres, Row};
+
+use crate::database::UnitOfWork;
+
+pub struct OutboxMessage {
+    pub id: i32,
+    pub queue_name: String,
+    pub payload: String,
+    pub attempts: i32,
+}
+
+pub struct InsertOutboxMessageParams {
+    pub queue_name: String,
+    pub payload: String,
+}
+
+#[async_trait]
+pub trait OutboxRepositoryInterface: Send + Sync + 'static {
+    async fn insert(
+        &self,
+        unit_of_work: &mut UnitOfWork,
+        messages: Vec<InsertOutboxMessageParams>,
+    ) -> Result<(), sqlx::Error>;
+    async fn find_undelivered(&self, limit: i32) -> Result<Vec<OutboxMessage>, sqlx::Error>;
+    async fn mark_delivered(&self, message_id: i32) -> Result<(), sqlx::Error>;
+    async fn record_failure(&self, message_id: i32, error: String) -> Result<(), sqlx::Error>;
+    async fn delete_undelivered(&self, queue_name: String) -> Result<(), sqlx::Error>;
+    async fn delete_delivered(
+        &self,
+        delivered_before: chrono::DateTime<chrono::Utc>,
+    ) -> Result<u64, sqlx::Error>;
+}
+
+pub struct OutboxRepository {
+    _db: Pool<Postgres>,
+}
+
+impl OutboxRepository {
+    pub fn new(_db: Pool<Postgres>) -> Self {
+        OutboxRepository { _db }
+    }
+}
+
+#[async_trait]
+impl OutboxRepositoryInterface for OutboxRepository {
+    async fn insert(
+        &self,
+        unit_of_work: &mut UnitOfWork,
+        messages: Vec<InsertOutboxMessageParams>,
+    ) -> Result<(), sqlx::Error> {
+        if messages.is_empty() {
+            return Ok(());
+        }
+
+        let (queue_names, payloads): (Vec<String>, Vec<String>) = messages
+            .into_iter()
+            .map(|message| (message.queue_name, message.payload))
+            .unzip();
+
+        sqlx::query(
+            r#"
+            insert into "outbox" ("queue_name", "payload")
+            select * from unnest($1::text[], $2::text[])
+            "#,
+        )
+        .bind(queue_names)
+        .bind(payloads)
+        .execute(unit_of_work.connection())
+        .await?;
+
+        Ok(())
+    }
+
+    async fn find_undelivered(&self, limit: i32) -> Result<Vec<OutboxMessage>, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            select "id", "queue_name", "payload", "attempts"
+            from "outbox"
+            where "delivered_at" is null
+            order by "id"
+            limit $1
+            "#,
+        )
+        .bind(limit)
+        .map(|row: PgRow| OutboxMessage {
+            id: row.get("id"),
+            queue_name: row.get("queue_name"),
+            payload: row.get("payload"),
+            attempts: row.get("attempts"),
+        })
+        .fetch_all(&self._db)
+        .await?;
+
+        Ok(result)
+    }
+
+    async fn mark_delivered(&self, message_id: i32) -> Result<(), sqlx::Error> {
+        let rows_affected = sqlx::query(
+            r#"
+            update "outbox"
+            set
+                "attempts" = "attempts" + 1,
+                "delivered_at" = now()
+            where "id" = $1
+            "#,
+        )
+        .bind(message_id)
+        .execute(&self._db)
+        .await?
+        .rows_affected();
+
+        if rows_affected == 0 {
+            return Err(sqlx::Error::RowNotFound);
+        }
+
+        Ok(())
+    }
+
+    async fn record_failure(&self, message_id: i32, error: String) -> Result<(), sqlx::Error> {
+        let rows_affected = sqlx::query(
+            r#"
+            update "outbox"
+            set
+                "attempts" = "attempts" + 1,
+                "last_error" = $2
+            where "id" = $1
+            "#,
+        )
+        .bind(message_id)
+        .bind(error)
+        .execute(&self._db)
+        .await?
+        .rows_affected();
+
+        if rows_affected == 0 {
+            return Err(sqlx::Error::RowNotFound);
+        }
+
+        Ok(())
+    }
+
+    async fn delete_undelivered(&self, queue_name: String) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            delete from "outbox"
+            where "queue_name" = $1 and "delivered_at" is null
+            "#,
+        )
+        .bind(queue_name)
+        .execute(&self._db)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn delete_delivered(
+        &self,
+        delivered_before: chrono::DateTime<chrono::Utc>,
+    ) -> Result<u64, sqlx::Error> {
+        let rows_affected = sqlx::query(
+            r#"
+            delete from "outbox"
+            where "delivered_at" < $1
+            "#,
+        )
+        .bind(delivered_before)
+        .execute(&self._db)
+        .await?
+        .rows_affected();
+
+        Ok(rows_affected)
+    }
+}
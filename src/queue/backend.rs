@@ -0,0 +1,27 @@
+use super::{
+    Consumer, ConsumerInterface, Producer, ProducerInterface, QueueBackendInterface, TrimPolicy,
+};
+
+pub struct RedisQueueBackend {
+    client: deadpool_redis::Pool,
+}
+
+impl RedisQueueBackend {
+    pub fn new(client: deadpool_redis::Pool) -> Self {
+        RedisQueueBackend { client }
+    }
+}
+
+impl QueueBackendInterface for RedisQueueBackend {
+    fn producer(&self, queue_name: String, trim_policy: TrimPolicy) -> Box<dyn ProducerInterface> {
+        Box::new(Producer::new(self.client.clone(), queue_name).with_trim_policy(trim_policy))
+    }
+
+    fn consumer(&self, queue_name: String, consumer_name: String) -> Box<dyn ConsumerInterface> {
+        Box::new(Consumer::new(
+            self.client.clone(),
+            queue_name,
+            consumer_name,
+        ))
+    }
+}
@@ -1,9 +1,20 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
 use redis::{
-    streams::{StreamKey, StreamPendingReply, StreamReadOptions, StreamReadReply},
+    streams::{
+        StreamClaimReply, StreamInfoGroupsReply, StreamKey, StreamPendingCountReply,
+        StreamPendingReply, StreamRangeReply, StreamReadOptions, StreamReadReply,
+    },
     AsyncCommands, RedisResult, Value,
 };
 use serde::de::DeserializeOwned;
 
+use super::{
+    dead_letter_queue_name, ConsumerInterface, DeadLetterQueue, Envelope, PendingMessage,
+    QueueInfo, TrimPolicy, DEFAULT_CLAIM_MIN_IDLE, ENVELOPE_SCHEMA_VERSION,
+};
+
 pub enum RedisErrorCode {
     StreamGroupAlreadyExists,
 }
@@ -39,23 +50,43 @@ pub struct Consumer {
     client: deadpool_redis::Pool,
     queue_name: String,
     group_name: &'static str,
-    consumer_name: &'static str,
+    consumer_name: String,
+    claim_min_idle: Duration,
+    trim_policy: TrimPolicy,
 
     is_group_exist: bool,
 }
 
+const CLAIM_BATCH_SIZE: usize = 100;
+
 impl Consumer {
-    pub fn new(client: deadpool_redis::Pool, queue_name: String) -> Consumer {
+    /// Every instance sharing the consumer group must use its own `consumer_name`,
+    /// otherwise the instances would read each other's pending messages.
+    pub fn new(client: deadpool_redis::Pool, queue_name: String, consumer_name: String) -> Consumer {
         Consumer {
             client,
             queue_name,
             group_name: "main-group",
-            consumer_name: "main-consumer",
+            consumer_name,
+            claim_min_idle: DEFAULT_CLAIM_MIN_IDLE,
+            trim_policy: TrimPolicy::None,
 
             is_group_exist: false,
         }
     }
 
+    pub fn with_claim_min_idle(mut self, claim_min_idle: Duration) -> Self {
+        self.claim_min_idle = claim_min_idle;
+        self
+    }
+
+    /// Used for the streams that are appended to outside of this application,
+    /// so they can only be trimmed from the consuming side
+    pub fn with_trim_policy(mut self, trim_policy: TrimPolicy) -> Self {
+        self.trim_policy = trim_policy;
+        self
+    }
+
     pub async fn redis(&mut self) -> Result<deadpool_redis::Connection, ConsumerError> {
         let mut redis = self
             .client
@@ -88,10 +119,12 @@ impl Consumer {
         Ok(redis)
     }
 
+    /// Decodes the `data` of every entry as an envelope around `T`, envelopes written
+    /// with a newer schema version than this application knows are rejected
     pub fn parse<T: DeserializeOwned>(
         &self,
         data: Vec<StreamKey>,
-    ) -> Result<Vec<(String, T)>, ConsumerError> {
+    ) -> Result<Vec<(String, Envelope<T>)>, ConsumerError> {
         let mut raw: Vec<(String, String)> = vec![];
 
         for res in data {
@@ -106,11 +139,17 @@ impl Consumer {
             }
         }
 
-        let mut result: Vec<(String, T)> = vec![];
+        let mut result: Vec<(String, Envelope<T>)> = vec![];
 
         for (message_id, data) in raw {
-            match serde_json::from_str::<T>(data.as_str()) {
-                Ok(payload) => result.push((message_id, payload)),
+            match serde_json::from_str::<Envelope<T>>(data.as_str()) {
+                Ok(envelope) if envelope.schema_version > ENVELOPE_SCHEMA_VERSION => {
+                    return Err(ConsumerError::ApplicationError(format!(
+                        "Unsupported envelope schema version {}",
+                        envelope.schema_version
+                    )))
+                }
+                Ok(envelope) => result.push((message_id, envelope)),
                 Err(e) => return Err(ConsumerError::ApplicationError(e.to_string())),
             };
         }
@@ -144,12 +183,12 @@ impl Consumer {
 
     pub async fn consume<T: DeserializeOwned>(
         &mut self,
-    ) -> Result<Vec<(String, T)>, ConsumerError> {
+    ) -> Result<Vec<(String, Envelope<T>)>, ConsumerError> {
         let mut redis = self.redis().await?;
 
         let opts = StreamReadOptions::default()
             .group(self.group_name.to_string(), self.consumer_name.to_string())
-            .block(0)
+            .block(self.claim_min_idle.as_millis() as usize)
             .count(1);
         let result: RedisResult<StreamReadReply> = redis
             .xread_options(&[self.queue_name.clone()], &[">"], &opts)
@@ -166,30 +205,55 @@ impl Consumer {
     pub async fn read_by_message_id<T: DeserializeOwned>(
         &mut self,
         message_id: String,
-    ) -> Result<Vec<(String, T)>, ConsumerError> {
+    ) -> Result<Vec<(String, Envelope<T>)>, ConsumerError> {
+        let keys = self.range_by_message_id(message_id).await?;
+
+        Ok(self.parse::<T>(keys)?)
+    }
+
+    // Reading the group with an explicit id only returns the entries after that id,
+    // so a single pending message is fetched through its range instead
+    async fn range_by_message_id(
+        &mut self,
+        message_id: String,
+    ) -> Result<Vec<StreamKey>, ConsumerError> {
         let mut redis = self.redis().await?;
 
-        let opts = StreamReadOptions::default()
-            .group(self.group_name.to_string(), self.consumer_name.to_string())
-            .count(1);
-        let result: RedisResult<StreamReadReply> = redis
-            .xread_options(&[self.queue_name.clone()], &[message_id], &opts)
+        let result: RedisResult<StreamRangeReply> = redis
+            .xrange(self.queue_name.clone(), message_id.clone(), message_id)
             .await;
 
-        let keys = match result {
-            Ok(r) => r.keys,
-            Err(e) => return Err(ConsumerError::RedisError(e)),
-        };
+        match result {
+            Ok(r) => Ok(vec![StreamKey {
+                key: self.queue_name.clone(),
+                ids: r.ids,
+            }]),
+            Err(e) => Err(ConsumerError::RedisError(e)),
+        }
+    }
 
-        Ok(self.parse::<T>(keys)?)
+    pub async fn read_raw_by_message_id(
+        &mut self,
+        message_id: String,
+    ) -> Result<Vec<(String, String)>, ConsumerError> {
+        let keys = self.range_by_message_id(message_id).await?;
+
+        Ok(self.parse_raw(keys)?)
+    }
+}
+
+#[async_trait]
+impl ConsumerInterface for Consumer {
+    fn claim_min_idle(&self) -> Duration {
+        self.claim_min_idle
     }
 
-    pub async fn consume_raw(&mut self) -> Result<Vec<(String, String)>, ConsumerError> {
+    async fn consume_raw(&mut self) -> Result<Vec<(String, String)>, ConsumerError> {
         let mut redis = self.redis().await?;
 
         let opts = StreamReadOptions::default()
             .group(self.group_name.to_string(), self.consumer_name.to_string())
-            .block(0)
+            .block(self.claim_min_idle.as_millis() as usize)
             .count(1);
         let result: RedisResult<StreamReadReply> = redis
             .xread_options(&[self.queue_name.clone()], &[">"], &opts)
@@ -203,48 +267,252 @@ impl Consumer {
         Ok(self.parse_raw(keys)?)
     }
 
-    pub async fn read_raw_by_message_id(
+    async fn get_pending_message(&mut self) -> Result<Option<PendingMessage>, ConsumerError> {
+        let mut redis = self.redis().await?;
+
+        let result: RedisResult<StreamPendingCountReply> = redis
+            .xpending_consumer_count(
+                self.queue_name.clone(),
+                self.group_name,
+                "-",
+                "+",
+                1,
+                self.consumer_name.clone(),
+            )
+            .await;
+
+        let reply = match result {
+            Ok(r) => r,
+            Err(e) => return Err(ConsumerError::RedisError(e)),
+        };
+
+        Ok(reply.ids.into_iter().next().map(|pending| PendingMessage {
+            id: pending.id,
+            times_delivered: pending.times_delivered,
+            idle: Duration::from_millis(pending.last_delivered_ms as u64),
+        }))
+    }
+
+    async fn claim_raw(
         &mut self,
         message_id: String,
+        min_idle: Duration,
     ) -> Result<Vec<(String, String)>, ConsumerError> {
         let mut redis = self.redis().await?;
 
-        let opts = StreamReadOptions::default()
-            .group(self.group_name.to_string(), self.consumer_name.to_string())
-            .count(1);
-        let result: RedisResult<StreamReadReply> = redis
-            .xread_options(&[self.queue_name.clone()], &[message_id], &opts)
+        let result: RedisResult<StreamClaimReply> = redis
+            .xclaim(
+                self.queue_name.clone(),
+                self.group_name,
+                self.consumer_name.clone(),
+                min_idle.as_millis() as usize,
+                &[message_id],
+            )
             .await;
 
-        let keys = match result {
-            Ok(r) => r.keys,
+        let ids = match result {
+            Ok(r) => r.ids,
             Err(e) => return Err(ConsumerError::RedisError(e)),
         };
 
-        Ok(self.parse_raw(keys)?)
+        self.parse_raw(vec![StreamKey {
+            key: self.queue_name.clone(),
+            ids,
+        }])
+    }
+
+    async fn claim_stale_messages(&mut self) -> Result<Vec<String>, ConsumerError> {
+        let mut redis = self.redis().await?;
+
+        let mut claimed_ids: Vec<String> = vec![];
+        let mut cursor = "0-0".to_string();
+
+        loop {
+            // The reply holds the next cursor, the claimed ids and, since redis 7, the deleted ids
+            let result: RedisResult<Vec<Value>> = redis::cmd("XAUTOCLAIM")
+                .arg(self.queue_name.clone())
+                .arg(self.group_name)
+                .arg(self.consumer_name.clone())
+                .arg(self.claim_min_idle.as_millis() as usize)
+                .arg(cursor.clone())
+                .arg("COUNT")
+                .arg(CLAIM_BATCH_SIZE)
+                .arg("JUSTID")
+                .query_async(&mut redis)
+                .await;
+
+            let reply = match result {
+                Ok(r) => r,
+                Err(e) => return Err(ConsumerError::RedisError(e)),
+            };
+
+            let (next_cursor, ids) = match reply.as_slice() {
+                [next_cursor, ids, ..] => (
+                    redis::from_redis_value::<String>(next_cursor),
+                    redis::from_redis_value::<Vec<String>>(ids),
+                ),
+                _ => {
+                    return Err(ConsumerError::ApplicationError(
+                        "Unexpected reply from XAUTOCLAIM".into(),
+                    ))
+                }
+            };
+
+            claimed_ids.extend(ids.map_err(ConsumerError::RedisError)?);
+            cursor = next_cursor.map_err(ConsumerError::RedisError)?;
+
+            if cursor == "0-0" {
+                break;
+            }
+        }
+
+        Ok(claimed_ids)
     }
 
+    async fn trim(&mut self) -> Result<usize, ConsumerError> {
+        if self.trim_policy == TrimPolicy::None {
+            return Ok(0);
+        }
+
+        let mut redis = self.redis().await?;
 
+        let mut command = redis::cmd("XTRIM");
+        command.arg(self.queue_name.clone());
+        self.trim_policy.write_args(&mut command);
 
-    pub async fn get_pending_message_id(&mut self) -> Result<Option<String>, ConsumerError> {
+        match command.query_async::<_, usize>(&mut redis).await {
+            Ok(count) => Ok(count),
+            Err(e) => Err(ConsumerError::RedisError(e)),
+        }
+    }
+
+    async fn info(&mut self) -> Result<QueueInfo, ConsumerError> {
         let mut redis = self.redis().await?;
 
-        let result: RedisResult<StreamPendingReply> = redis
-            .xpending(self.queue_name.clone(), self.group_name.clone())
-            .await;
+        let length: usize = match redis.xlen(self.queue_name.clone()).await {
+            Ok(length) => length,
+            Err(e) => return Err(ConsumerError::RedisError(e)),
+        };
 
-        let reply = match result {
-            Ok(r) => match r {
-                StreamPendingReply::Data(reply) => reply,
-                StreamPendingReply::Empty => return Ok(None),
+        let groups: StreamInfoGroupsReply = match redis.xinfo_groups(self.queue_name.clone()).await
+        {
+            Ok(groups) => groups,
+            Err(e) => return Err(ConsumerError::RedisError(e)),
+        };
+        let group = groups
+            .groups
+            .into_iter()
+            .find(|group| group.name == self.group_name);
+
+        let pending: StreamPendingReply = match redis
+            .xpending(self.queue_name.clone(), self.group_name)
+            .await
+        {
+            Ok(pending) => pending,
+            Err(e) => return Err(ConsumerError::RedisError(e)),
+        };
+
+        Ok(QueueInfo {
+            length,
+            pending_count: pending.count(),
+            last_delivered_id: group
+                .map(|group| group.last_delivered_id)
+                .filter(|id| id != "0-0"),
+            oldest_pending_id: match pending {
+                StreamPendingReply::Data(data) => Some(data.start_id),
+                StreamPendingReply::Empty => None,
             },
+        })
+    }
+
+    async fn pending_message_ids(&mut self, count: usize) -> Result<Vec<String>, ConsumerError> {
+        let mut redis = self.redis().await?;
+
+        let result: RedisResult<StreamPendingCountReply> = redis
+            .xpending_count(self.queue_name.clone(), self.group_name, "-", "+", count)
+            .await;
+
+        match result {
+            Ok(r) => Ok(r.ids.into_iter().map(|pending| pending.id).collect()),
+            Err(e) => Err(ConsumerError::RedisError(e)),
+        }
+    }
+
+    async fn read_latest_raw(
+        &mut self,
+        count: usize,
+    ) -> Result<Vec<(String, String)>, ConsumerError> {
+        let mut redis = self.redis().await?;
+
+        let result: RedisResult<StreamRangeReply> = redis
+            .xrevrange_count(self.queue_name.clone(), "+", "-", count)
+            .await;
+
+        let ids = match result {
+            Ok(r) => r.ids,
             Err(e) => return Err(ConsumerError::RedisError(e)),
         };
 
-        Ok(Some(reply.start_id))
+        self.parse_raw(vec![StreamKey {
+            key: self.queue_name.clone(),
+            ids,
+        }])
     }
 
-    pub async fn ack(&mut self, message_id: String) -> Result<(), ConsumerError> {
+    async fn purge(&mut self) -> Result<usize, ConsumerError> {
+        loop {
+            let pending_ids = self.pending_message_ids(CLAIM_BATCH_SIZE).await?;
+            if pending_ids.is_empty() {
+                break;
+            }
+
+            let mut redis = self.redis().await?;
+            if let Err(e) = redis
+                .xack::<String, &str, String, ()>(
+                    self.queue_name.clone(),
+                    self.group_name,
+                    &pending_ids,
+                )
+                .await
+            {
+                return Err(ConsumerError::RedisError(e));
+            }
+        }
+
+        let mut redis = self.redis().await?;
+
+        match redis::cmd("XTRIM")
+            .arg(self.queue_name.clone())
+            .arg("MAXLEN")
+            .arg(0)
+            .query_async::<_, usize>(&mut redis)
+            .await
+        {
+            Ok(count) => Ok(count),
+            Err(e) => Err(ConsumerError::RedisError(e)),
+        }
+    }
+
+    async fn dead_letter(
+        &mut self,
+        message_id: String,
+        payload: String,
+        reason: String,
+        times_delivered: usize,
+    ) -> Result<(), ConsumerError> {
+        let dead_letter_queue = DeadLetterQueue::new(self.client.clone(), self.queue_name.clone());
+
+        if let Err(e) = dead_letter_queue
+            .push(message_id.clone(), payload, reason, times_delivered)
+            .await
+        {
+            return Err(ConsumerError::ApplicationError(e.to_string()));
+        }
+
+        self.ack(message_id).await
+    }
+
+    async fn ack(&mut self, message_id: String) -> Result<(), ConsumerError> {
         let mut redis = self.redis().await?;
 
         if let Err(e) = redis
@@ -260,4 +528,23 @@ impl Consumer {
 
         Ok(())
     }
+
+    async fn delete(&mut self) -> Result<(), ConsumerError> {
+        let mut redis = self.redis().await?;
+
+        if let Err(e) = redis::cmd("DEL")
+            .arg(&[
+                self.queue_name.clone(),
+                dead_letter_queue_name(&self.queue_name),
+            ])
+            .query_async::<_, ()>(&mut redis)
+            .await
+        {
+            return Err(ConsumerError::RedisError(e));
+        }
+
+        self.is_group_exist = false;
+
+        Ok(())
+    }
 }
@@ -0,0 +1,175 @@
+use std::collections::BTreeMap;
+
+use redis::{
+    streams::{StreamId, StreamRangeReply},
+    AsyncCommands,
+};
+
+use super::TrimPolicy;
+
+pub enum DeadLetterError {
+    RedisError(String),
+}
+
+impl std::fmt::Display for DeadLetterError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            DeadLetterError::RedisError(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+/// Dead letters are kept for inspection, but not forever
+pub(super) const DEAD_LETTER_TRIM_POLICY: TrimPolicy = TrimPolicy::MaxLen(10000);
+
+pub fn dead_letter_queue_name(queue_name: &str) -> String {
+    format!("{}-dlq", queue_name)
+}
+
+pub struct DeadLetter {
+    pub id: String,
+    pub original_id: String,
+    pub data: String,
+    pub reason: String,
+    pub delivery_count: usize,
+    pub failed_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl DeadLetter {
+    fn from_stream_id(stream_id: StreamId) -> Self {
+        let failed_at = stream_id
+            .get::<String>("failed_at")
+            .and_then(|date| chrono::DateTime::parse_from_rfc3339(&date).ok())
+            .map(|date| date.with_timezone(&chrono::Utc))
+            .unwrap_or_else(chrono::Utc::now);
+
+        DeadLetter {
+            original_id: stream_id.get("original_id").unwrap_or_default(),
+            data: stream_id.get("data").unwrap_or_default(),
+            reason: stream_id.get("reason").unwrap_or_default(),
+            delivery_count: stream_id.get("delivery_count").unwrap_or_default(),
+            failed_at,
+            id: stream_id.id,
+        }
+    }
+}
+
+/// Holds the messages of a queue that could not be processed, together with
+/// the reason of the last failure, until they are replayed or purged.
+pub struct DeadLetterQueue {
+    client: deadpool_redis::Pool,
+    queue_name: String,
+}
+
+impl DeadLetterQueue {
+    pub fn new(client: deadpool_redis::Pool, queue_name: String) -> Self {
+        DeadLetterQueue { client, queue_name }
+    }
+
+    pub fn name(&self) -> String {
+        dead_letter_queue_name(&self.queue_name)
+    }
+
+    async fn redis(&self) -> Result<deadpool_redis::Connection, DeadLetterError> {
+        match self.client.get().await {
+            Ok(conn) => Ok(conn),
+            Err(e) => Err(DeadLetterError::RedisError(e.to_string())),
+        }
+    }
+
+    pub async fn push(
+        &self,
+        original_id: String,
+        data: String,
+        reason: String,
+        delivery_count: usize,
+    ) -> Result<(), DeadLetterError> {
+        let mut redis = self.redis().await?;
+
+        let mut payload: BTreeMap<String, String> = BTreeMap::new();
+        payload.insert("original_id".into(), original_id);
+        payload.insert("data".into(), data);
+        payload.insert("reason".into(), reason);
+        payload.insert("delivery_count".into(), delivery_count.to_string());
+        payload.insert("failed_at".into(), chrono::Utc::now().to_rfc3339());
+
+        let mut command = redis::cmd("XADD");
+        command.arg(self.name());
+        DEAD_LETTER_TRIM_POLICY.write_args(&mut command);
+        command.arg("*");
+        for (field, value) in payload {
+            command.arg(field).arg(value);
+        }
+
+        if let Err(e) = command.query_async::<_, ()>(&mut redis).await {
+            return Err(DeadLetterError::RedisError(e.to_string()));
+        }
+
+        Ok(())
+    }
+
+    pub async fn count(&self) -> Result<usize, DeadLetterError> {
+        let mut redis = self.redis().await?;
+
+        match redis.xlen::<String, usize>(self.name()).await {
+            Ok(count) => Ok(count),
+            Err(e) => Err(DeadLetterError::RedisError(e.to_string())),
+        }
+    }
+
+    /// Returns the most recently dead-lettered messages first
+    pub async fn list(&self, limit: usize) -> Result<Vec<DeadLetter>, DeadLetterError> {
+        let mut redis = self.redis().await?;
+
+        let result: redis::RedisResult<StreamRangeReply> = redis
+            .xrevrange_count(self.name(), "+", "-", limit)
+            .await;
+
+        match result {
+            Ok(reply) => Ok(reply
+                .ids
+                .into_iter()
+                .map(DeadLetter::from_stream_id)
+                .collect()),
+            Err(e) => Err(DeadLetterError::RedisError(e.to_string())),
+        }
+    }
+
+    pub async fn find(&self, id: String) -> Result<Option<DeadLetter>, DeadLetterError> {
+        let mut redis = self.redis().await?;
+
+        let result: redis::RedisResult<StreamRangeReply> =
+            redis.xrange(self.name(), id.clone(), id).await;
+
+        match result {
+            Ok(reply) => Ok(reply
+                .ids
+                .into_iter()
+                .next()
+                .map(DeadLetter::from_stream_id)),
+            Err(e) => Err(DeadLetterError::RedisError(e.to_string())),
+        }
+    }
+
+    pub async fn delete(&self, id: String) -> Result<(), DeadLetterError> {
+        let mut redis = self.redis().await?;
+
+        if let Err(e) = redis.xdel::<String, String, ()>(self.name(), &[id]).await {
+            return Err(DeadLetterError::RedisError(e.to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Drops every dead-lettered message of the queue, returning how many were removed
+    pub async fn purge(&self) -> Result<usize, DeadLetterError> {
+        let count = self.count().await?;
+
+        let mut redis = self.redis().await?;
+        if let Err(e) = redis.del::<String, ()>(self.name()).await {
+            return Err(DeadLetterError::RedisError(e.to_string()));
+        }
+
+        Ok(count)
+    }
+}
@@ -0,0 +1,31 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Bumped whenever a field of the envelope changes in a way older readers cannot handle,
+/// see `schemas/device_sync_envelope.v1.json`
+pub const ENVELOPE_SCHEMA_VERSION: u32 = 1;
+
+/// Wraps every message published to a queue. The `message_id` stays the same when a message
+/// is delivered more than once, so readers can use it to deduplicate.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct Envelope<T> {
+    pub schema_version: u32,
+    pub message_id: Uuid,
+    pub emitted_at: chrono::DateTime<chrono::Utc>,
+    #[serde(rename = "type")]
+    pub message_type: String,
+    pub payload: T,
+}
+
+impl<T> Envelope<T> {
+    pub fn new(message_type: &str, payload: T) -> Self {
+        Envelope {
+            schema_version: ENVELOPE_SCHEMA_VERSION,
+            message_id: Uuid::new_v4(),
+            emitted_at: chrono::Utc::now(),
+            message_type: message_type.to_string(),
+            payload,
+        }
+    }
+}
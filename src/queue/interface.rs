@@ -0,0 +1,103 @@
+use std::{collections::BTreeMap, time::Duration};
+
+use async_trait::async_trait;
+
+use super::{ConsumerError, ProducerError, TrimPolicy};
+
+/// Messages left pending by another consumer for longer than this are assumed abandoned
+pub const DEFAULT_CLAIM_MIN_IDLE: Duration = Duration::from_secs(60);
+
+pub struct PendingMessage {
+    pub id: String,
+    pub times_delivered: usize,
+    /// Time elapsed since the message was last delivered
+    pub idle: Duration,
+}
+
+pub struct QueueInfo {
+    pub length: usize,
+    pub pending_count: usize,
+    pub last_delivered_id: Option<String>,
+    pub oldest_pending_id: Option<String>,
+}
+
+/// Stream ids start with the unix time in milliseconds the entry was added at
+pub fn message_timestamp(message_id: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    let millis: i64 = message_id.split('-').next()?.parse().ok()?;
+
+    chrono::TimeZone::timestamp_millis_opt(&chrono::Utc, millis).single()
+}
+
+#[async_trait]
+pub trait ProducerInterface: Send + Sync {
+    async fn push(&self, payload: BTreeMap<String, String>) -> Result<(), ProducerError>;
+}
+
+/// A member of the consumer group of a queue. Every message read is pending on the consumer
+/// that read it until it is acknowledged, dead-lettered or claimed by another consumer.
+#[async_trait]
+pub trait ConsumerInterface: Send {
+    fn claim_min_idle(&self) -> Duration;
+
+    /// Waits for the next message that was never delivered to the group,
+    /// returns nothing when none arrived within the claim min idle
+    async fn consume_raw(&mut self) -> Result<Vec<(String, String)>, ConsumerError>;
+
+    /// Only the messages delivered to this consumer are considered pending,
+    /// the ones owned by the other instances are left to them.
+    async fn get_pending_message(&mut self) -> Result<Option<PendingMessage>, ConsumerError>;
+
+    /// Delivers a pending message again to this consumer, which increments its delivery count.
+    /// Nothing is returned when the message was delivered more recently than `min_idle`.
+    async fn claim_raw(
+        &mut self,
+        message_id: String,
+        min_idle: Duration,
+    ) -> Result<Vec<(String, String)>, ConsumerError>;
+
+    /// Takes over the messages that stayed pending on any consumer of the group for longer than
+    /// the claim min idle, e.g. the ones left behind by a crashed or renamed instance.
+    /// The claimed messages become pending on this consumer and are returned by `get_pending_message`,
+    /// their delivery count is left untouched until they are read again.
+    async fn claim_stale_messages(&mut self) -> Result<Vec<String>, ConsumerError>;
+
+    /// Trims the queue according to the trim policy and returns the number of evicted entries.
+    /// Evicted entries that were still pending can no longer be read nor retried.
+    async fn trim(&mut self) -> Result<usize, ConsumerError>;
+
+    /// Describes the queue as seen by the consumer group, regardless of which consumer owns the messages
+    async fn info(&mut self) -> Result<QueueInfo, ConsumerError>;
+
+    /// Returns the ids of the messages delivered to any consumer of the group but not acknowledged yet
+    async fn pending_message_ids(&mut self, count: usize) -> Result<Vec<String>, ConsumerError>;
+
+    /// Reads the latest messages of the queue without delivering them to the group
+    async fn read_latest_raw(
+        &mut self,
+        count: usize,
+    ) -> Result<Vec<(String, String)>, ConsumerError>;
+
+    /// Acknowledges every pending message and empties the queue, returning the number of removed messages.
+    /// The queue and its consumer group are kept so the readers blocked on it are left undisturbed.
+    async fn purge(&mut self) -> Result<usize, ConsumerError>;
+
+    /// Moves the message to the dead letter queue and acknowledges it, so it is no longer retried
+    async fn dead_letter(
+        &mut self,
+        message_id: String,
+        payload: String,
+        reason: String,
+        times_delivered: usize,
+    ) -> Result<(), ConsumerError>;
+
+    async fn ack(&mut self, message_id: String) -> Result<(), ConsumerError>;
+
+    /// Removes the queue altogether, with its consumer group and dead letters
+    async fn delete(&mut self) -> Result<(), ConsumerError>;
+}
+
+/// Where the queues live, either redis or the process memory
+pub trait QueueBackendInterface: Send + Sync + 'static {
+    fn producer(&self, queue_name: String, trim_policy: TrimPolicy) -> Box<dyn ProducerInterface>;
+    fn consumer(&self, queue_name: String, consumer_name: String) -> Box<dyn ConsumerInterface>;
+}
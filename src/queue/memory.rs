@@ -0,0 +1,462 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    ops::Bound,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use tokio::sync::watch;
+
+use super::{
+    dead_letter_queue_name, ConsumerError, ConsumerInterface, PendingMessage, ProducerError,
+    ProducerInterface, QueueBackendInterface, QueueInfo, TrimPolicy, DEAD_LETTER_TRIM_POLICY,
+    DEFAULT_CLAIM_MIN_IDLE,
+};
+
+const GROUP_NAME: &str = "main-group";
+
+type MessageId = (u64, u64);
+
+fn format_message_id(id: MessageId) -> String {
+    format!("{}-{}", id.0, id.1)
+}
+
+fn parse_message_id(id: &str) -> Option<MessageId> {
+    let (millis, sequence) = id.split_once('-')?;
+
+    Some((millis.parse().ok()?, sequence.parse().ok()?))
+}
+
+fn now_millis() -> u64 {
+    chrono::Utc::now().timestamp_millis() as u64
+}
+
+struct PendingEntry {
+    consumer: String,
+    delivered_at: Instant,
+    times_delivered: usize,
+}
+
+struct Group {
+    last_delivered_id: MessageId,
+    pending: BTreeMap<MessageId, PendingEntry>,
+}
+
+#[derive(Default)]
+struct Stream {
+    entries: BTreeMap<MessageId, BTreeMap<String, String>>,
+    last_id: MessageId,
+    groups: HashMap<String, Group>,
+}
+
+impl Stream {
+    /// Like redis, a group created on an existing stream only receives the messages added afterwards
+    fn group(&mut self, name: &str) -> &mut Group {
+        let last_id = self.last_id;
+
+        self.groups
+            .entry(name.to_string())
+            .or_insert_with(|| Group {
+                last_delivered_id: last_id,
+                pending: BTreeMap::new(),
+            })
+    }
+
+    fn append(&mut self, fields: BTreeMap<String, String>) -> MessageId {
+        let millis = now_millis();
+        let id = if millis > self.last_id.0 {
+            (millis, 0)
+        } else {
+            (self.last_id.0, self.last_id.1 + 1)
+        };
+
+        self.entries.insert(id, fields);
+        self.last_id = id;
+
+        id
+    }
+
+    fn data(&self, id: &MessageId) -> Option<String> {
+        self.entries
+            .get(id)
+            .and_then(|fields| fields.get("data").cloned())
+    }
+
+    fn trim(&mut self, trim_policy: &TrimPolicy) -> usize {
+        let length = self.entries.len();
+
+        match trim_policy {
+            TrimPolicy::None => (),
+            TrimPolicy::MaxLen(max_len) => {
+                while self.entries.len() > *max_len {
+                    let oldest_id = match self.entries.keys().next() {
+                        Some(id) => *id,
+                        None => break,
+                    };
+                    self.entries.remove(&oldest_id);
+                }
+            }
+            TrimPolicy::MinAge(age) => {
+                let min_millis = now_millis().saturating_sub(age.as_millis() as u64);
+                self.entries.retain(|id, _| id.0 >= min_millis);
+            }
+        }
+
+        length - self.entries.len()
+    }
+}
+
+/// Keeps the queues in the memory of the process with the same semantics as the redis streams
+/// (consumer groups, pending messages, delivery counts and acknowledgements).
+/// Suited for the tests and for the setups where every producer and consumer run in one process.
+#[derive(Clone)]
+pub struct InMemoryQueue {
+    streams: Arc<Mutex<HashMap<String, Stream>>>,
+    changes: Arc<watch::Sender<u64>>,
+}
+
+impl InMemoryQueue {
+    pub fn new() -> Self {
+        let (changes, _) = watch::channel(0);
+
+        InMemoryQueue {
+            streams: Arc::new(Mutex::new(HashMap::new())),
+            changes: Arc::new(changes),
+        }
+    }
+
+    fn with_stream<R>(&self, queue_name: &str, f: impl FnOnce(&mut Stream) -> R) -> R {
+        let mut streams = self.streams.lock().unwrap();
+        let stream = streams.entry(queue_name.to_string()).or_default();
+
+        f(stream)
+    }
+
+    fn notify(&self) {
+        self.changes.send_modify(|version| *version += 1);
+    }
+}
+
+impl Default for InMemoryQueue {
+    fn default() -> Self {
+        InMemoryQueue::new()
+    }
+}
+
+impl QueueBackendInterface for InMemoryQueue {
+    fn producer(&self, queue_name: String, trim_policy: TrimPolicy) -> Box<dyn ProducerInterface> {
+        Box::new(InMemoryProducer::new(self.clone(), queue_name).with_trim_policy(trim_policy))
+    }
+
+    fn consumer(&self, queue_name: String, consumer_name: String) -> Box<dyn ConsumerInterface> {
+        Box::new(InMemoryConsumer::new(
+            self.clone(),
+            queue_name,
+            consumer_name,
+        ))
+    }
+}
+
+pub struct InMemoryProducer {
+    queue: InMemoryQueue,
+    queue_name: String,
+    trim_policy: TrimPolicy,
+}
+
+impl InMemoryProducer {
+    pub fn new(queue: InMemoryQueue, queue_name: String) -> Self {
+        InMemoryProducer {
+            queue,
+            queue_name,
+            trim_policy: TrimPolicy::None,
+        }
+    }
+
+    pub fn with_trim_policy(mut self, trim_policy: TrimPolicy) -> Self {
+        self.trim_policy = trim_policy;
+        self
+    }
+}
+
+#[async_trait]
+impl ProducerInterface for InMemoryProducer {
+    async fn push(&self, payload: BTreeMap<String, String>) -> Result<(), ProducerError> {
+        self.queue.with_stream(&self.queue_name, |stream| {
+            stream.group(GROUP_NAME);
+            stream.append(payload);
+            stream.trim(&self.trim_policy);
+        });
+        self.queue.notify();
+
+        Ok(())
+    }
+}
+
+pub struct InMemoryConsumer {
+    queue: InMemoryQueue,
+    queue_name: String,
+    consumer_name: String,
+    claim_min_idle: Duration,
+    trim_policy: TrimPolicy,
+}
+
+impl InMemoryConsumer {
+    pub fn new(queue: InMemoryQueue, queue_name: String, consumer_name: String) -> Self {
+        InMemoryConsumer {
+            queue,
+            queue_name,
+            consumer_name,
+            claim_min_idle: DEFAULT_CLAIM_MIN_IDLE,
+            trim_policy: TrimPolicy::None,
+        }
+    }
+
+    pub fn with_claim_min_idle(mut self, claim_min_idle: Duration) -> Self {
+        self.claim_min_idle = claim_min_idle;
+        self
+    }
+
+    pub fn with_trim_policy(mut self, trim_policy: TrimPolicy) -> Self {
+        self.trim_policy = trim_policy;
+        self
+    }
+
+    /// Hands the next undelivered message to this consumer, the message id is returned
+    /// even when the entry has no data so it still ends up pending like on redis
+    fn deliver_next(&self) -> Option<(MessageId, Option<String>)> {
+        self.queue.with_stream(&self.queue_name, |stream| {
+            let last_delivered_id = stream.group(GROUP_NAME).last_delivered_id;
+
+            let id = *stream
+                .entries
+                .range((Bound::Excluded(last_delivered_id), Bound::Unbounded))
+                .next()?
+                .0;
+            let data = stream.data(&id);
+
+            let group = stream.group(GROUP_NAME);
+            group.last_delivered_id = id;
+            group.pending.insert(
+                id,
+                PendingEntry {
+                    consumer: self.consumer_name.clone(),
+                    delivered_at: Instant::now(),
+                    times_delivered: 1,
+                },
+            );
+
+            Some((id, data))
+        })
+    }
+}
+
+#[async_trait]
+impl ConsumerInterface for InMemoryConsumer {
+    fn claim_min_idle(&self) -> Duration {
+        self.claim_min_idle
+    }
+
+    async fn consume_raw(&mut self) -> Result<Vec<(String, String)>, ConsumerError> {
+        // Subscribing before looking at the stream so a push in between is not missed
+        let mut changes = self.queue.changes.subscribe();
+
+        loop {
+            if let Some((id, data)) = self.deliver_next() {
+                return Ok(data
+                    .map(|data| vec![(format_message_id(id), data)])
+                    .unwrap_or_default());
+            }
+
+            match tokio::time::timeout(self.claim_min_idle, changes.changed()).await {
+                Ok(Ok(_)) => continue,
+                _ => return Ok(vec![]),
+            }
+        }
+    }
+
+    async fn get_pending_message(&mut self) -> Result<Option<PendingMessage>, ConsumerError> {
+        Ok(self.queue.with_stream(&self.queue_name, |stream| {
+            stream
+                .group(GROUP_NAME)
+                .pending
+                .iter()
+                .find(|(_, pending)| pending.consumer == self.consumer_name)
+                .map(|(id, pending)| PendingMessage {
+                    id: format_message_id(*id),
+                    times_delivered: pending.times_delivered,
+                    idle: pending.delivered_at.elapsed(),
+                })
+        }))
+    }
+
+    async fn claim_raw(
+        &mut self,
+        message_id: String,
+        min_idle: Duration,
+    ) -> Result<Vec<(String, String)>, ConsumerError> {
+        let id = match parse_message_id(&message_id) {
+            Some(id) => id,
+            None => return Ok(vec![]),
+        };
+
+        Ok(self.queue.with_stream(&self.queue_name, |stream| {
+            let data = stream.data(&id);
+            let is_deleted = !stream.entries.contains_key(&id);
+
+            let group = stream.group(GROUP_NAME);
+            let pending = match group.pending.get_mut(&id) {
+                Some(pending) if pending.delivered_at.elapsed() >= min_idle => pending,
+                _ => return vec![],
+            };
+
+            // Same as redis 7, a pending message whose entry was trimmed is dropped
+            if is_deleted {
+                group.pending.remove(&id);
+                return vec![];
+            }
+
+            pending.consumer = self.consumer_name.clone();
+            pending.delivered_at = Instant::now();
+            pending.times_delivered += 1;
+
+            data.map(|data| vec![(message_id, data)])
+                .unwrap_or_default()
+        }))
+    }
+
+    async fn claim_stale_messages(&mut self) -> Result<Vec<String>, ConsumerError> {
+        Ok(self.queue.with_stream(&self.queue_name, |stream| {
+            let entries = &stream.entries;
+            let last_id = stream.last_id;
+            let group = stream
+                .groups
+                .entry(GROUP_NAME.to_string())
+                .or_insert_with(|| Group {
+                    last_delivered_id: last_id,
+                    pending: BTreeMap::new(),
+                });
+
+            group.pending.retain(|id, pending| {
+                entries.contains_key(id) || pending.delivered_at.elapsed() < self.claim_min_idle
+            });
+
+            let mut claimed_ids: Vec<String> = vec![];
+            for (id, pending) in group.pending.iter_mut() {
+                if pending.delivered_at.elapsed() >= self.claim_min_idle {
+                    pending.consumer = self.consumer_name.clone();
+                    pending.delivered_at = Instant::now();
+                    claimed_ids.push(format_message_id(*id));
+                }
+            }
+
+            claimed_ids
+        }))
+    }
+
+    async fn trim(&mut self) -> Result<usize, ConsumerError> {
+        Ok(self
+            .queue
+            .with_stream(&self.queue_name, |stream| stream.trim(&self.trim_policy)))
+    }
+
+    async fn info(&mut self) -> Result<QueueInfo, ConsumerError> {
+        Ok(self.queue.with_stream(&self.queue_name, |stream| {
+            let length = stream.entries.len();
+            let group = stream.group(GROUP_NAME);
+
+            QueueInfo {
+                length,
+                pending_count: group.pending.len(),
+                last_delivered_id: Some(group.last_delivered_id)
+                    .filter(|id| *id != (0, 0))
+                    .map(format_message_id),
+                oldest_pending_id: group.pending.keys().next().copied().map(format_message_id),
+            }
+        }))
+    }
+
+    async fn pending_message_ids(&mut self, count: usize) -> Result<Vec<String>, ConsumerError> {
+        Ok(self.queue.with_stream(&self.queue_name, |stream| {
+            stream
+                .group(GROUP_NAME)
+                .pending
+                .keys()
+                .take(count)
+                .copied()
+                .map(format_message_id)
+                .collect()
+        }))
+    }
+
+    async fn read_latest_raw(
+        &mut self,
+        count: usize,
+    ) -> Result<Vec<(String, String)>, ConsumerError> {
+        Ok(self.queue.with_stream(&self.queue_name, |stream| {
+            stream
+                .entries
+                .iter()
+                .rev()
+                .take(count)
+                .filter_map(|(id, fields)| {
+                    fields
+                        .get("data")
+                        .map(|data| (format_message_id(*id), data.clone()))
+                })
+                .collect()
+        }))
+    }
+
+    async fn purge(&mut self) -> Result<usize, ConsumerError> {
+        Ok(self.queue.with_stream(&self.queue_name, |stream| {
+            stream.group(GROUP_NAME).pending.clear();
+
+            let count = stream.entries.len();
+            stream.entries.clear();
+
+            count
+        }))
+    }
+
+    async fn dead_letter(
+        &mut self,
+        message_id: String,
+        payload: String,
+        reason: String,
+        times_delivered: usize,
+    ) -> Result<(), ConsumerError> {
+        let mut fields: BTreeMap<String, String> = BTreeMap::new();
+        fields.insert("original_id".into(), message_id.clone());
+        fields.insert("data".into(), payload);
+        fields.insert("reason".into(), reason);
+        fields.insert("delivery_count".into(), times_delivered.to_string());
+        fields.insert("failed_at".into(), chrono::Utc::now().to_rfc3339());
+
+        self.queue
+            .with_stream(&dead_letter_queue_name(&self.queue_name), |stream| {
+                stream.append(fields);
+                stream.trim(&DEAD_LETTER_TRIM_POLICY);
+            });
+
+        self.ack(message_id).await
+    }
+
+    async fn ack(&mut self, message_id: String) -> Result<(), ConsumerError> {
+        if let Some(id) = parse_message_id(&message_id) {
+            self.queue.with_stream(&self.queue_name, |stream| {
+                stream.group(GROUP_NAME).pending.remove(&id);
+            });
+        }
+
+        Ok(())
+    }
+
+    async fn delete(&mut self) -> Result<(), ConsumerError> {
+        let mut streams = self.queue.streams.lock().unwrap();
+        streams.remove(&self.queue_name);
+        streams.remove(&dead_letter_queue_name(&self.queue_name));
+
+        Ok(())
+    }
+}
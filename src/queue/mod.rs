@@ -1,7 +1,21 @@
 pub mod producer;
 pub mod consumer;
+pub mod backend;
+pub mod dead_letter;
+pub mod envelope;
 pub mod error;
+pub mod interface;
+pub mod memory;
+pub mod retry;
+pub mod trim;
 
 pub use producer::*;
 pub use consumer::*;
+pub use backend::*;
+pub use dead_letter::*;
+pub use envelope::*;
 pub use error::*;
+pub use interface::*;
+pub use memory::*;
+pub use retry::*;
+pub use trim::*;
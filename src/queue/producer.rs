@@ -1,15 +1,28 @@
 use std::collections::BTreeMap;
 
+use async_trait::async_trait;
 use redis::AsyncCommands;
 
+use super::{ProducerInterface, TrimPolicy};
+
+#[derive(Debug)]
 pub enum ProducerError {
     RedisError(String),
 }
 
+impl std::fmt::Display for ProducerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ProducerError::RedisError(message) => write!(f, "{}", message),
+        }
+    }
+}
+
 pub struct Producer {
     pub client: deadpool_redis::Pool,
     pub queue_name: String,
     pub group_name: &'static str,
+    pub trim_policy: TrimPolicy,
 }
 
 impl Producer {
@@ -18,15 +31,20 @@ impl Producer {
             client,
             queue_name,
             group_name: "main-group",
+            trim_policy: TrimPolicy::None,
         }
     }
 
+    pub fn with_trim_policy(mut self, trim_policy: TrimPolicy) -> Self {
+        self.trim_policy = trim_policy;
+        self
+    }
+
     async fn initialize_consumer_group(&self) -> Result<(), ProducerError> {
-        let mut redis = self
-            .client
-            .get()
-            .await
-            .expect("Cannot get redis connection");
+        let mut redis = match self.client.get().await {
+            Ok(conn) => conn,
+            Err(e) => return Err(ProducerError::RedisError(e.to_string())),
+        };
 
         if let Err(e) = redis
             .xgroup_create_mkstream::<String, String, String, ()>(
@@ -45,24 +63,27 @@ impl Producer {
 
         Ok(())
     }
+}
 
-    pub async fn push(&self, payload: BTreeMap<String, String>) -> Result<(), ProducerError> {
+#[async_trait]
+impl ProducerInterface for Producer {
+    async fn push(&self, payload: BTreeMap<String, String>) -> Result<(), ProducerError> {
         self.initialize_consumer_group().await?;
 
-        let mut conn = self
-            .client
-            .get()
-            .await
-            .expect("Cannot get redis connection");
+        let mut conn = match self.client.get().await {
+            Ok(conn) => conn,
+            Err(e) => return Err(ProducerError::RedisError(e.to_string())),
+        };
 
-        if let Err(e) = conn
-            .xadd_map::<String, String, BTreeMap<String, String>, ()>(
-                self.queue_name.clone(),
-                "*".into(),
-                payload,
-            )
-            .await
-        {
+        let mut command = redis::cmd("XADD");
+        command.arg(self.queue_name.clone());
+        self.trim_policy.write_args(&mut command);
+        command.arg("*");
+        for (field, value) in payload {
+            command.arg(field).arg(value);
+        }
+
+        if let Err(e) = command.query_async::<_, ()>(&mut conn).await {
             return Err(ProducerError::RedisError(e.to_string()));
         }
 
@@ -0,0 +1,33 @@
+use std::time::Duration;
+
+/// The upper bound of the delay between two deliveries of the same message
+const MAX_BACKOFF: Duration = Duration::from_secs(300);
+
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_deliveries: usize,
+    pub backoff: Duration,
+}
+
+impl RetryPolicy {
+    pub fn new(max_deliveries: usize, backoff: Duration) -> Self {
+        RetryPolicy {
+            max_deliveries,
+            backoff,
+        }
+    }
+
+    /// How long a failed message stays pending before it is delivered again,
+    /// doubling on every delivery
+    pub fn backoff(&self, times_delivered: usize) -> Duration {
+        let exponent = times_delivered.saturating_sub(1).min(16) as u32;
+
+        self.backoff
+            .saturating_mul(2u32.saturating_pow(exponent))
+            .min(MAX_BACKOFF)
+    }
+
+    pub fn is_exhausted(&self, times_delivered: usize) -> bool {
+        times_delivered >= self.max_deliveries
+    }
+}
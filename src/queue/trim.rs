@@ -0,0 +1,61 @@
+use std::{str::FromStr, time::Duration};
+
+/// Bounds how much history a stream keeps. The trimming is approximate (`~`),
+/// redis only drops whole macro nodes which keeps it cheap on every append.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TrimPolicy {
+    None,
+    /// Keeps about the given number of the latest entries
+    MaxLen(usize),
+    /// Keeps the entries added within the given duration, through MINID
+    MinAge(Duration),
+}
+
+#[derive(Debug)]
+pub struct TrimPolicyParseError(String);
+
+impl std::fmt::Display for TrimPolicyParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl TrimPolicy {
+    pub fn write_args(&self, command: &mut redis::Cmd) {
+        match self {
+            TrimPolicy::None => (),
+            TrimPolicy::MaxLen(max_len) => {
+                command.arg("MAXLEN").arg("~").arg(*max_len);
+            }
+            TrimPolicy::MinAge(age) => {
+                let min_timestamp = chrono::Utc::now().timestamp_millis() - age.as_millis() as i64;
+                command
+                    .arg("MINID")
+                    .arg("~")
+                    .arg(format!("{}-0", min_timestamp.max(0)));
+            }
+        }
+    }
+}
+
+/// Parses `none`, `maxlen:<entries>` or `minage:<seconds>`
+impl FromStr for TrimPolicy {
+    type Err = TrimPolicyParseError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || TrimPolicyParseError(format!("Invalid trim policy: {}", value));
+
+        if value == "none" {
+            return Ok(TrimPolicy::None);
+        }
+
+        let (kind, amount) = value.split_once(':').ok_or_else(invalid)?;
+        let amount: u64 = amount.parse().map_err(|_| invalid())?;
+
+        match kind {
+            "maxlen" => Ok(TrimPolicy::MaxLen(amount as usize)),
+            "minage" => Ok(TrimPolicy::MinAge(Duration::from_secs(amount))),
+            _ => Err(invalid()),
+        }
+    }
+}
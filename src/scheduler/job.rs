@@ -0,0 +1,26 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono_tz::Tz;
+use cron::Schedule;
+
+#[async_trait]
+pub trait JobHandler: Send + Sync + 'static {
+    /// `tick` is the schedule time the run belongs to, replayed runs receive the time of the missed tick
+    async fn execute(&self, tick: chrono::DateTime<chrono::Utc>) -> Result<(), String>;
+}
+
+pub struct Job {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub cron: &'static str,
+    pub timezone: Tz,
+    pub schedule: Schedule,
+    pub handler: Arc<dyn JobHandler>,
+}
+
+impl Job {
+    pub fn next_run_after(&self, datetime: chrono::DateTime<Tz>) -> Option<chrono::DateTime<Tz>> {
+        self.schedule.after(&datetime).take(1).next()
+    }
+}
@@ -1,3 +1,7 @@
+pub mod job;
+pub mod registry;
 pub mod scheduler;
 
+pub use job::*;
+pub use registry::*;
 pub use scheduler::*;
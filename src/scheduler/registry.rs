@@ -0,0 +1,234 @@
+use std::{str::FromStr, sync::Arc, time::Duration};
+
+use chrono_tz::Tz;
+use cron::Schedule;
+
+use crate::{
+    features::job::{
+        domain::{JobRunOutcome, JobRunTrigger},
+        error::ExecuteJobError,
+        repository::JobRepositoryInterface,
+    },
+    lock::DistributedLock,
+};
+
+use super::{Job, JobHandler};
+
+/// Lifetime of the running lock, it is extended for as long as the job is still executing
+const RUNNING_LOCK_TTL: Duration = Duration::from_secs(60);
+
+/// Lifetime of the claim on a single tick, long enough for every instance to have moved past it
+const TICK_LOCK_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+pub struct JobRegistry {
+    jobs: Vec<Arc<Job>>,
+    _job_repository: Arc<dyn JobRepositoryInterface>,
+    _lock: DistributedLock,
+}
+
+impl JobRegistry {
+    pub fn new(_job_repository: Arc<dyn JobRepositoryInterface>, _lock: DistributedLock) -> Self {
+        JobRegistry {
+            jobs: vec![],
+            _job_repository,
+            _lock,
+        }
+    }
+
+    fn running_lock_key(name: &str) -> String {
+        format!("scheduler:job:{}:running", name)
+    }
+
+    fn tick_lock_key(name: &str, tick: chrono::DateTime<chrono::Utc>) -> String {
+        format!("scheduler:job:{}:tick:{}", name, tick.timestamp())
+    }
+
+    pub fn register(
+        mut self,
+        name: &'static str,
+        description: &'static str,
+        cron: &'static str,
+        timezone: Tz,
+        handler: Arc<dyn JobHandler>,
+    ) -> Result<Self, cron::error::Error> {
+        let schedule = Schedule::from_str(cron)?;
+
+        self.jobs.push(Arc::new(Job {
+            name,
+            description,
+            cron,
+            timezone,
+            schedule,
+            handler,
+        }));
+
+        Ok(self)
+    }
+
+    pub fn jobs(&self) -> Vec<Arc<Job>> {
+        self.jobs.clone()
+    }
+
+    pub fn find(&self, name: &str) -> Option<Arc<Job>> {
+        self.jobs.iter().find(|job| job.name == name).cloned()
+    }
+
+    /// Runs the job to completion and records the run in the job history.
+    /// A job is never executed concurrently with itself across every instance of the application,
+    /// and a scheduled tick is executed by whichever instance claims it first.
+    pub async fn execute(
+        &self,
+        name: &str,
+        trigger: JobRunTrigger,
+        tick: chrono::DateTime<chrono::Utc>,
+    ) -> Result<(), ExecuteJobError> {
+        let job = match self.find(name) {
+            Some(job) => job,
+            None => return Err(ExecuteJobError::JobNotFound),
+        };
+
+        let running_key = JobRegistry::running_lock_key(job.name);
+        let acquired = self
+            ._lock
+            .acquire(running_key.as_str(), RUNNING_LOCK_TTL)
+            .await
+            .map_err(|e| ExecuteJobError::Lock(e.to_string()))?;
+        if !acquired {
+            return Err(ExecuteJobError::JobAlreadyRunning);
+        }
+
+        if trigger != JobRunTrigger::Manual {
+            let tick_key = JobRegistry::tick_lock_key(job.name, tick);
+            let claimed = match self._lock.acquire(tick_key.as_str(), TICK_LOCK_TTL).await {
+                Ok(claimed) => claimed,
+                Err(e) => {
+                    self.release_running_lock(running_key.as_str()).await;
+                    return Err(ExecuteJobError::Lock(e.to_string()));
+                }
+            };
+
+            // The tick has already been handled by another instance
+            if !claimed {
+                self.release_running_lock(running_key.as_str()).await;
+                return Ok(());
+            }
+        }
+
+        let execution = self.execute_and_record(job.clone(), trigger, tick);
+        tokio::pin!(execution);
+
+        let mut heartbeat = tokio::time::interval(RUNNING_LOCK_TTL / 3);
+        heartbeat.tick().await;
+
+        let result = loop {
+            tokio::select! {
+                result = &mut execution => break result,
+                _ = heartbeat.tick() => {
+                    match self._lock.extend(running_key.as_str(), RUNNING_LOCK_TTL).await {
+                        Ok(true) => (),
+                        Ok(false) => eprintln!("The running lock of the {} job has been lost", job.name),
+                        Err(e) => eprintln!("Something went wrong when extending the running lock of the {} job: {}", job.name, e),
+                    }
+                }
+            }
+        };
+
+        self.release_running_lock(running_key.as_str()).await;
+
+        result
+    }
+
+    async fn release_running_lock(&self, key: &str) {
+        if let Err(e) = self._lock.release(key).await {
+            eprintln!("Something went wrong when releasing the {} lock: {}", key, e);
+        }
+    }
+
+    async fn execute_and_record(
+        &self,
+        job: Arc<Job>,
+        trigger: JobRunTrigger,
+        tick: chrono::DateTime<chrono::Utc>,
+    ) -> Result<(), ExecuteJobError> {
+        let job_run_id = self
+            ._job_repository
+            .insert_run(job.name.to_string(), trigger.clone())
+            .await?;
+
+        let (outcome, error) = match job.handler.execute(tick).await {
+            Ok(_) => (JobRunOutcome::Succeeded, None),
+            Err(e) => {
+                eprintln!("Something went wrong when executing the {} job: {}", job.name, e);
+                (JobRunOutcome::Failed, Some(e))
+            }
+        };
+        let succeeded = outcome == JobRunOutcome::Succeeded;
+
+        self._job_repository
+            .finish_run(job_run_id, outcome, error.clone())
+            .await?;
+
+        // Manual runs are outside of the schedule so they never move the last tick
+        if succeeded && trigger != JobRunTrigger::Manual {
+            self._job_repository
+                .upsert_last_tick(job.name.to_string(), tick)
+                .await?;
+        }
+
+        match error {
+            Some(e) => Err(ExecuteJobError::Failed(e)),
+            None => Ok(()),
+        }
+    }
+
+    /// Runs the job once for the latest tick missed between the last successful tick and `until`.
+    /// The handlers derive their work from the tick they receive, so a single run at the latest
+    /// tick has the same effect as replaying every missed one.
+    pub async fn catch_up(
+        &self,
+        name: &str,
+        until: chrono::DateTime<Tz>,
+    ) -> Result<(), ExecuteJobError> {
+        let job = match self.find(name) {
+            Some(job) => job,
+            None => return Err(ExecuteJobError::JobNotFound),
+        };
+
+        let last_tick = match self._job_repository.find_last_tick(job.name.to_string()).await? {
+            Some(tick) => tick.with_timezone(&job.timezone),
+            None => {
+                // Nothing to replay on the first run, the current time becomes the starting point
+                self._job_repository
+                    .upsert_last_tick(job.name.to_string(), until.with_timezone(&chrono::Utc))
+                    .await?;
+                return Ok(());
+            }
+        };
+
+        let latest_tick = match job
+            .schedule
+            .after(&last_tick)
+            .take_while(|tick| *tick <= until)
+            .last()
+        {
+            Some(tick) => tick,
+            None => return Ok(()),
+        };
+
+        println!("Catching up the {} job with its missed run at {}", job.name, latest_tick);
+
+        self.execute(
+            job.name,
+            JobRunTrigger::CatchUp,
+            latest_tick.with_timezone(&chrono::Utc),
+        )
+        .await
+    }
+
+    pub async fn is_running(&self, name: &str) -> Result<bool, ExecuteJobError> {
+        self._lock
+            .is_locked(JobRegistry::running_lock_key(name).as_str())
+            .await
+            .map_err(|e| ExecuteJobError::Lock(e.to_string()))
+    }
+}
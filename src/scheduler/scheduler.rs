@@ -1,26 +1,27 @@
-use std::{str::FromStr, sync::Arc};
+use std::{collections::HashMap, sync::Arc};
 
-use chrono::{NaiveTime, TimeZone, Utc};
-use chrono_tz::{Asia::Jakarta, Tz};
-use cron::Schedule;
+use async_trait::async_trait;
+use chrono::Utc;
+use chrono_tz::Tz;
 use tokio::{
     sync::{mpsc, oneshot},
     time::{sleep, Duration},
 };
 
 use crate::{
-    features::{AnnouncementServiceInterface, HandleScheduledAnnouncementsError},
+    features::{
+        job::{domain::JobRunTrigger, error::ExecuteJobError},
+        AnnouncementServiceInterface, HandleScheduledAnnouncementsError,
+    },
     shutdown::Shutdown,
 };
 
+use super::{JobHandler, JobRegistry};
+
 pub async fn execute_announcement_scheduler(
     announcement_service: Arc<dyn AnnouncementServiceInterface + Send + Sync + 'static>,
+    now: chrono::DateTime<chrono::Utc>,
 ) -> Result<(), HandleScheduledAnnouncementsError> {
-    let now = chrono::DateTime::from_utc(
-        chrono::Utc::today().naive_utc().and_hms(0, 0, 0) + chrono::Duration::days(1),
-        chrono::Utc,
-    );
-
     let announcement_service_1 = announcement_service.clone();
     let announcement_service_2 = announcement_service.clone();
     let announcement_service_3 = announcement_service.clone();
@@ -41,28 +42,81 @@ pub async fn execute_announcement_scheduler(
             .await
     });
 
-    match tokio::try_join!(
+    let (waiting_for_approval_result, waiting_for_sync_result, active_result) = match tokio::try_join!(
         waiting_for_approval_handler,
         waiting_for_sync_handler,
         active_handler
     ) {
-        Ok(_result) => Ok(()),
-        Err(_) => Err(HandleScheduledAnnouncementsError::InternalServerError),
+        Ok(result) => result,
+        Err(_) => return Err(HandleScheduledAnnouncementsError::BrokenThread),
+    };
+
+    waiting_for_approval_result?;
+    waiting_for_sync_result?;
+    active_result?;
+
+    // Occurrences are evaluated after the status transitions so announcements that just became active are picked up
+    announcement_service
+        .handle_recurring_announcements(now)
+        .await
+}
+
+pub struct AnnouncementSchedulerJob {
+    _announcement_service: Arc<dyn AnnouncementServiceInterface + Send + Sync + 'static>,
+}
+
+impl AnnouncementSchedulerJob {
+    pub fn new(
+        _announcement_service: Arc<dyn AnnouncementServiceInterface + Send + Sync + 'static>,
+    ) -> Self {
+        AnnouncementSchedulerJob {
+            _announcement_service,
+        }
     }
 }
 
-pub async fn run(
-    mut shutdown: Shutdown,
-    _sender: mpsc::Sender<()>,
-    announcement_service: Arc<dyn AnnouncementServiceInterface + Send + Sync + 'static>,
-) {
-    // TODO: refactor scheduler in the future so can have more than one cron
+#[async_trait]
+impl JobHandler for AnnouncementSchedulerJob {
+    async fn execute(&self, tick: chrono::DateTime<chrono::Utc>) -> Result<(), String> {
+        match execute_announcement_scheduler(self._announcement_service.clone(), tick).await {
+            Ok(_) => Ok(()),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+}
+
+pub async fn run(mut shutdown: Shutdown, _sender: mpsc::Sender<()>, registry: Arc<JobRegistry>) {
     let (tx, mut rx) = mpsc::channel::<oneshot::Sender<bool>>(32);
     let tx_2 = tx.clone();
 
     let cron = actix_web::rt::spawn(async move {
-        let schedule = Schedule::from_str("0 0 0 * * *").unwrap();
-        let mut last_tick: Option<chrono::DateTime<Tz>> = None;
+        let mut last_ticks: HashMap<&'static str, chrono::DateTime<Tz>> = HashMap::new();
+
+        for job in registry.jobs() {
+            let now = Utc::now().with_timezone(&job.timezone);
+
+            // Catching up runs alongside the loop so a slow or failing job never delays the schedule
+            let catch_up_registry = registry.clone();
+            let name = job.name;
+            actix_web::rt::spawn(async move {
+                match catch_up_registry.catch_up(name, now).await {
+                    // Failed runs are already reported and recorded in the run history
+                    Ok(_) | Err(ExecuteJobError::Failed(_)) => (),
+                    Err(ExecuteJobError::JobAlreadyRunning) => {
+                        println!("Skipping the catch up of the {} job since it is already running", name);
+                    }
+                    Err(e) => {
+                        eprintln!("Something went wrong when catching up the {} job: {}", name, e);
+                    }
+                }
+            });
+
+            if let Some(event) = job.next_run_after(now) {
+                println!("Scheduler registered the {} job, next schedule time: {}", job.name, event);
+            }
+
+            last_ticks.insert(job.name, now);
+        }
 
         loop {
             if let Ok(resp) = rx.try_recv() {
@@ -72,44 +126,35 @@ pub async fn run(
 
             sleep(Duration::from_millis(250)).await;
 
-            if last_tick == None {
-                let today_utc = (Utc::today() - chrono::Duration::days(1))
-                    .and_time(NaiveTime::from_hms(17, 0, 0))
-                    .unwrap();
-                let today_jakarta = Jakarta.from_utc_datetime(&today_utc.naive_utc());
-
-                if let Some(event) = schedule.after(&today_jakarta).take(1).next() {
-                    println!(
-                        "Announcement Scheduler is starting, next schedule time: {}",
-                        event
-                    );
+            for job in registry.jobs() {
+                let now = Utc::now().with_timezone(&job.timezone);
+                let last_tick = match last_ticks.get(job.name) {
+                    Some(tick) => *tick,
+                    None => now,
+                };
+
+                if let Some(event) = job.next_run_after(last_tick) {
+                    if event <= now {
+                        let registry = registry.clone();
+                        let name = job.name;
+                        let tick = event.with_timezone(&Utc);
+                        actix_web::rt::spawn(async move {
+                            match registry.execute(name, JobRunTrigger::Scheduled, tick).await {
+                                // Failed runs are already reported and recorded in the run history
+                                Ok(_) | Err(ExecuteJobError::Failed(_)) => (),
+                                Err(ExecuteJobError::JobAlreadyRunning) => {
+                                    println!("Skipping the {} job since the previous run has not finished", name);
+                                }
+                                Err(e) => {
+                                    eprintln!("Something went wrong when running the {} job: {}", name, e);
+                                }
+                            }
+                        });
+                    }
                 }
 
-                last_tick = Some(today_jakarta);
-                continue;
+                last_ticks.insert(job.name, now);
             }
-
-            let utc_now = Utc::now().naive_utc();
-            let now = Jakarta.from_utc_datetime(&utc_now);
-
-            if let Some(event) = schedule.after(&last_tick.unwrap()).take(1).next() {
-                if event > now {
-                    continue;
-                }
-
-                println!(
-                    "Announcement scheduler started processing at {}",
-                    now
-                );
-
-                if let Err(e) = execute_announcement_scheduler(announcement_service.clone()).await {
-                    eprintln!("Something went wrong when executing the announcement scheduler: {}", e);
-                }
-
-                println!("Announcement scheduler finished processing");
-            }
-
-            last_tick = Some(now);
         }
     });
 
@@ -9,10 +9,18 @@ use tokio::sync::{broadcast, mpsc};
 use crate::features::livestream::definition::{LivestreamDeviceMap, LivestreamSessionMap};
 use crate::features::livestream::service::LivestreamServiceInterface;
 use crate::features::livestream::socket::LivestreamSocketServer;
+use crate::features::job::service::JobServiceInterface;
 use crate::features::media::service::MediaServiceInterface;
+use crate::features::queue::service::QueueServiceInterface;
 use crate::features::{device_status, livestream};
+use crate::lock::DistributedLock;
+use crate::outbox::{self, OutboxRepositoryInterface};
+use crate::queue::{ConsumerInterface, QueueBackendInterface, RetryPolicy, TrimPolicy};
 use crate::shutdown::Shutdown;
-use crate::{http::WebServer, scheduler};
+use crate::{
+    http::WebServer,
+    scheduler::{self, JobRegistry},
+};
 
 use crate::features::{
     announcement::AnnouncementServiceInterface, auth::AuthServiceInterface,
@@ -34,6 +42,15 @@ pub async fn run(
     announcement_service: Arc<dyn AnnouncementServiceInterface + Send + Sync + 'static>,
     livestream_service: Arc<dyn LivestreamServiceInterface>,
     media_service: Arc<dyn MediaServiceInterface>,
+    job_service: Arc<dyn JobServiceInterface>,
+    queue_service: Arc<dyn QueueServiceInterface>,
+    job_registry: Arc<JobRegistry>,
+    outbox_repository: Arc<dyn OutboxRepositoryInterface>,
+    lock: DistributedLock,
+    queue_backend: Arc<dyn QueueBackendInterface>,
+    livestream_consumer: Box<dyn ConsumerInterface>,
+    retry_policy: RetryPolicy,
+    device_queue_trim_policy: TrimPolicy,
 ) -> Result<(), std::io::Error> {
     let (notify_shutdown, _) = broadcast::channel::<()>(1);
 
@@ -41,15 +58,16 @@ pub async fn run(
     let shutdown_2 = Shutdown::new(notify_shutdown.subscribe());
     let shutdown_3 = Shutdown::new(notify_shutdown.subscribe());
     let shutdown_4 = Shutdown::new(notify_shutdown.subscribe());
+    let shutdown_5 = Shutdown::new(notify_shutdown.subscribe());
 
     let (shutdown_complete_tx, mut shutdown_complete_rx) = mpsc::channel::<()>(1);
     let shutdown_complete_tx_1 = shutdown_complete_tx.clone();
     let shutdown_complete_tx_2 = shutdown_complete_tx.clone();
     let shutdown_complete_tx_3 = shutdown_complete_tx.clone();
     let shutdown_complete_tx_4 = shutdown_complete_tx.clone();
+    let shutdown_complete_tx_5 = shutdown_complete_tx.clone();
 
     let announcement_service_1 = announcement_service.clone();
-    let announcement_service_2 = announcement_service.clone();
 
     let livestream_service_1 = livestream_service.clone();
     let livestream_service_2 = livestream_service.clone();
@@ -81,7 +99,6 @@ pub async fn run(
         LivestreamSocketServer::new(livestream_sessions_1, livestream_devices_1).start();
 
     let redis_1 = redis.clone();
-    let redis_2 = redis.clone();
 
     actix_web::rt::spawn(async move {
         let server = match WebServer::build(
@@ -96,6 +113,8 @@ pub async fn run(
             announcement_service_1,
             livestream_service_1,
             media_service,
+            job_service,
+            queue_service,
             device_status_socket_srv,
             livestream_socket_srv,
         ) {
@@ -113,7 +132,7 @@ pub async fn run(
     });
 
     actix_web::rt::spawn(async move {
-        scheduler::run(shutdown_2, shutdown_complete_tx_2, announcement_service_2).await;
+        scheduler::run(shutdown_2, shutdown_complete_tx_2, job_registry).await;
     });
 
     actix_web::rt::spawn(async move {
@@ -131,7 +150,8 @@ pub async fn run(
         livestream::listener::run(
             shutdown_4,
             shutdown_complete_tx_4,
-            redis_2,
+            livestream_consumer,
+            retry_policy,
             livestream_service_2,
             livestream_sessions_2,
             livestream_devices_2,
@@ -139,6 +159,18 @@ pub async fn run(
         .await;
     });
 
+    actix_web::rt::spawn(async move {
+        outbox::relay::run(
+            shutdown_5,
+            shutdown_complete_tx_5,
+            queue_backend,
+            outbox_repository,
+            lock,
+            device_queue_trim_policy,
+        )
+        .await;
+    });
+
     let signal_listener = actix_web::rt::spawn(async move {
         actix_web::rt::signal::ctrl_c().await.unwrap();
     });
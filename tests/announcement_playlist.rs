@@ -0,0 +1,94 @@
+use enchiridion_api::features::announcement::{
+    build_playlist, PlaylistAnnouncement, DEFAULT_IMAGE_DISPLAY_DURATION_SECS,
+};
+use enchiridion_api::features::media::domain::MediaType;
+
+fn image(id: i32, priority: i32, weight: i32) -> PlaylistAnnouncement {
+    PlaylistAnnouncement {
+        id,
+        title: format!("Announcement {}", id),
+        media_type: MediaType::Image,
+        media_duration: None,
+        display_duration: None,
+        priority,
+        weight,
+        is_emergency: false,
+    }
+}
+
+fn announcement_ids(announcements: Vec<PlaylistAnnouncement>) -> Vec<i32> {
+    build_playlist(announcements)
+        .into_iter()
+        .map(|item| item.announcement_id)
+        .collect()
+}
+
+#[test]
+fn empty_device_has_an_empty_playlist() {
+    assert!(build_playlist(vec![]).is_empty());
+}
+
+#[test]
+fn higher_priority_announcements_play_first() {
+    let ids = announcement_ids(vec![image(1, 0, 1), image(2, 5, 1), image(3, 1, 1)]);
+
+    assert_eq!(ids, vec![2, 3, 1]);
+}
+
+#[test]
+fn same_priority_announcements_play_in_creation_order() {
+    let ids = announcement_ids(vec![image(3, 0, 1), image(1, 0, 1), image(2, 0, 1)]);
+
+    assert_eq!(ids, vec![1, 2, 3]);
+}
+
+#[test]
+fn weight_repeats_announcements_without_playing_them_back_to_back() {
+    let ids = announcement_ids(vec![image(1, 0, 2), image(2, 0, 1), image(3, 0, 1)]);
+
+    assert_eq!(ids, vec![1, 2, 3, 1]);
+    assert_eq!(ids.iter().filter(|id| **id == 1).count(), 2);
+}
+
+#[test]
+fn weight_is_spread_within_a_priority_only() {
+    let ids = announcement_ids(vec![image(1, 0, 3), image(2, 1, 2)]);
+
+    assert_eq!(ids, vec![2, 2, 1, 1, 1]);
+}
+
+#[test]
+fn emergency_preempts_the_rotation() {
+    let mut emergency = image(4, 0, 1);
+    emergency.is_emergency = true;
+
+    let ids = announcement_ids(vec![image(1, 10, 3), emergency, image(2, 0, 1)]);
+
+    assert_eq!(ids, vec![4]);
+}
+
+#[test]
+fn images_fall_back_to_the_default_display_duration() {
+    let mut with_duration = image(1, 0, 1);
+    with_duration.display_duration = Some(25.0);
+
+    let playlist = build_playlist(vec![with_duration, image(2, 0, 1)]);
+
+    assert_eq!(playlist[0].duration, Some(25.0));
+    assert_eq!(
+        playlist[1].duration,
+        Some(DEFAULT_IMAGE_DISPLAY_DURATION_SECS)
+    );
+}
+
+#[test]
+fn videos_play_for_the_length_of_the_media() {
+    let mut video = image(1, 0, 1);
+    video.media_type = MediaType::Video;
+    video.media_duration = Some(42.5);
+    video.display_duration = Some(5.0);
+
+    let playlist = build_playlist(vec![video]);
+
+    assert_eq!(playlist[0].duration, Some(42.5));
+}
@@ -0,0 +1,112 @@
+use chrono::{NaiveTime, TimeZone, Utc, Weekday};
+use enchiridion_api::features::announcement::{
+    AnnouncementRecurrence, AnnouncementStatus, AnnouncementTimeWindow,
+};
+use enchiridion_api::features::device::TargetedAnnouncement;
+use enchiridion_api::features::media::domain::MediaType;
+
+fn window(start: (u32, u32), end: (u32, u32)) -> AnnouncementTimeWindow {
+    AnnouncementTimeWindow {
+        start_time: NaiveTime::from_hms(start.0, start.1, 0),
+        end_time: NaiveTime::from_hms(end.0, end.1, 0),
+    }
+}
+
+fn recurrence(
+    days_of_week: Vec<Weekday>,
+    time_windows: Vec<AnnouncementTimeWindow>,
+) -> AnnouncementRecurrence {
+    AnnouncementRecurrence {
+        days_of_week,
+        time_windows,
+    }
+}
+
+/// 2023-03-13 is a Monday, Asia/Jakarta is 7 hours ahead of UTC
+fn jakarta(day: u32, hour: u32, minute: u32) -> chrono::DateTime<Utc> {
+    chrono_tz::Asia::Jakarta
+        .ymd(2023, 3, day)
+        .and_hms(hour, minute, 0)
+        .with_timezone(&Utc)
+}
+
+fn targeted(
+    status: AnnouncementStatus,
+    recurrence: Option<AnnouncementRecurrence>,
+    occurrence_active: bool,
+) -> TargetedAnnouncement {
+    TargetedAnnouncement {
+        id: 1,
+        status,
+        is_emergency: false,
+        media_type: MediaType::Image,
+        media_duration: None,
+        recurrence,
+        occurrence_active,
+    }
+}
+
+#[test]
+fn occurs_inside_a_time_window_on_a_selected_day() {
+    let recurrence = recurrence(vec![Weekday::Mon], vec![window((8, 0), (10, 0))]);
+
+    assert!(recurrence.is_occurring_at(jakarta(13, 9, 0)));
+}
+
+#[test]
+fn window_start_is_inclusive_and_end_is_exclusive() {
+    let recurrence = recurrence(vec![Weekday::Mon], vec![window((8, 0), (10, 0))]);
+
+    assert!(!recurrence.is_occurring_at(jakarta(13, 7, 59)));
+    assert!(recurrence.is_occurring_at(jakarta(13, 8, 0)));
+    assert!(recurrence.is_occurring_at(jakarta(13, 9, 59)));
+    assert!(!recurrence.is_occurring_at(jakarta(13, 10, 0)));
+}
+
+#[test]
+fn does_not_occur_on_other_days() {
+    let recurrence = recurrence(vec![Weekday::Mon], vec![window((8, 0), (10, 0))]);
+
+    assert!(!recurrence.is_occurring_at(jakarta(14, 9, 0)));
+    assert!(!recurrence.is_occurring_at(jakarta(12, 9, 0)));
+}
+
+#[test]
+fn occurs_in_any_of_its_time_windows() {
+    let recurrence = recurrence(
+        vec![Weekday::Mon, Weekday::Wed],
+        vec![window((8, 0), (10, 0)), window((13, 0), (14, 30))],
+    );
+
+    assert!(recurrence.is_occurring_at(jakarta(15, 14, 0)));
+    assert!(!recurrence.is_occurring_at(jakarta(15, 12, 0)));
+    assert!(!recurrence.is_occurring_at(jakarta(15, 14, 30)));
+}
+
+#[test]
+fn day_and_time_are_evaluated_in_campus_time() {
+    let recurrence = recurrence(vec![Weekday::Mon], vec![window((2, 0), (4, 0))]);
+
+    // Sunday 20:00 UTC is already Monday 03:00 on campus
+    assert!(recurrence.is_occurring_at(Utc.ymd(2023, 3, 12).and_hms(20, 0, 0)));
+    assert!(!recurrence.is_occurring_at(Utc.ymd(2023, 3, 13).and_hms(3, 0, 0)));
+}
+
+#[test]
+fn active_announcement_without_recurrence_is_displayed() {
+    assert!(targeted(AnnouncementStatus::Active, None, false).is_displayed());
+}
+
+#[test]
+fn recurring_announcement_is_only_displayed_during_an_occurrence() {
+    let weekly = recurrence(vec![Weekday::Mon], vec![window((8, 0), (10, 0))]);
+
+    assert!(!targeted(AnnouncementStatus::Active, Some(weekly.clone()), false).is_displayed());
+    assert!(targeted(AnnouncementStatus::Active, Some(weekly), true).is_displayed());
+}
+
+#[test]
+fn inactive_announcement_is_never_displayed() {
+    assert!(!targeted(AnnouncementStatus::WaitingForSync, None, false).is_displayed());
+    assert!(!targeted(AnnouncementStatus::Done, None, false).is_displayed());
+}
@@ -0,0 +1,101 @@
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use enchiridion_api::features::announcement::{
+    manifest_etag, ManifestAnnouncement, DEFAULT_IMAGE_DISPLAY_DURATION_SECS,
+    MANIFEST_MEDIA_URL_WINDOW_SECS,
+};
+use enchiridion_api::features::media::domain::MediaType;
+
+fn now() -> DateTime<Utc> {
+    Utc.ymd(2023, 3, 15).and_hms(10, 0, 0)
+}
+
+fn announcement(id: i32) -> ManifestAnnouncement {
+    ManifestAnnouncement {
+        id,
+        title: format!("Announcement {}", id),
+        media: format!("announcements/{}.png", id),
+        media_type: MediaType::Image,
+        media_duration: None,
+        display_duration: None,
+        content_hash: Some(format!("hash-{}", id)),
+        start_date: Utc.ymd(2023, 3, 1).and_hms(0, 0, 0),
+        end_date: Utc.ymd(2023, 3, 31).and_hms(0, 0, 0),
+        recurrence: None,
+        priority: 0,
+        weight: 1,
+        is_emergency: false,
+    }
+}
+
+#[test]
+fn etag_is_stable_for_the_same_announcements() {
+    let first = manifest_etag(&vec![announcement(1), announcement(2)], now());
+    let second = manifest_etag(&vec![announcement(1), announcement(2)], now());
+
+    assert_eq!(first, second);
+    assert!(first.starts_with('"') && first.ends_with('"'));
+}
+
+#[test]
+fn etag_changes_when_an_announcement_is_added_or_removed() {
+    let both = manifest_etag(&vec![announcement(1), announcement(2)], now());
+    let one = manifest_etag(&vec![announcement(1)], now());
+    let none = manifest_etag(&vec![], now());
+
+    assert_ne!(both, one);
+    assert_ne!(one, none);
+}
+
+#[test]
+fn etag_changes_when_the_media_content_changes() {
+    let mut replaced = announcement(1);
+    replaced.content_hash = Some("another-hash".to_string());
+
+    assert_ne!(
+        manifest_etag(&vec![announcement(1)], now()),
+        manifest_etag(&vec![replaced], now())
+    );
+}
+
+#[test]
+fn etag_changes_when_the_validity_window_changes() {
+    let mut extended = announcement(1);
+    extended.end_date = Utc.ymd(2023, 4, 30).and_hms(0, 0, 0);
+
+    assert_ne!(
+        manifest_etag(&vec![announcement(1)], now()),
+        manifest_etag(&vec![extended], now())
+    );
+}
+
+#[test]
+fn etag_is_stable_within_a_media_url_window() {
+    let window_start = Utc.timestamp(
+        now().timestamp() - now().timestamp() % MANIFEST_MEDIA_URL_WINDOW_SECS,
+        0,
+    );
+    let window_end = window_start + Duration::seconds(MANIFEST_MEDIA_URL_WINDOW_SECS - 1);
+
+    assert_eq!(
+        manifest_etag(&vec![announcement(1)], window_start),
+        manifest_etag(&vec![announcement(1)], window_end)
+    );
+}
+
+#[test]
+fn etag_changes_before_the_media_urls_expire() {
+    let later = now() + Duration::seconds(MANIFEST_MEDIA_URL_WINDOW_SECS);
+
+    assert_ne!(
+        manifest_etag(&vec![announcement(1)], now()),
+        manifest_etag(&vec![announcement(1)], later)
+    );
+}
+
+#[test]
+fn images_without_a_display_duration_use_the_default() {
+    assert_eq!(
+        announcement(1).duration(),
+        Some(DEFAULT_IMAGE_DISPLAY_DURATION_SECS)
+    );
+}
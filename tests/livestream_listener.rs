@@ -0,0 +1,225 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use enchiridion_api::features::livestream::definition::{
+    DeviceLivestreamQueryResult, LivestreamInterval, LivestreamMessagePayload,
+    LivestreamQueryAction, LivestreamRange, DEVICE_LIVESTREAM_QUEUE_NAME,
+};
+use enchiridion_api::features::livestream::error::{InsertLivestreamError, QueryLivestreamError};
+use enchiridion_api::features::livestream::listener;
+use enchiridion_api::features::livestream::service::LivestreamServiceInterface;
+use enchiridion_api::queue::{
+    dead_letter_queue_name, ConsumerInterface, InMemoryConsumer, InMemoryProducer, InMemoryQueue,
+    ProducerInterface, RetryPolicy,
+};
+use enchiridion_api::shutdown::Shutdown;
+use tokio::sync::{broadcast, mpsc};
+
+const VALID_MESSAGE: &str = "2023-03-01T10:00:00+07:00 1 3";
+
+#[derive(Default)]
+struct FakeLivestreamService {
+    inserted: Mutex<Vec<LivestreamMessagePayload>>,
+    failures_left: Mutex<usize>,
+}
+
+impl FakeLivestreamService {
+    fn failing(times: usize) -> Self {
+        FakeLivestreamService {
+            inserted: Mutex::new(vec![]),
+            failures_left: Mutex::new(times),
+        }
+    }
+
+    fn inserted_count(&self) -> usize {
+        self.inserted.lock().unwrap().len()
+    }
+}
+
+#[async_trait]
+impl LivestreamServiceInterface for FakeLivestreamService {
+    async fn insert(&self, message: LivestreamMessagePayload) -> Result<(), InsertLivestreamError> {
+        let mut failures_left = self.failures_left.lock().unwrap();
+        if *failures_left > 0 {
+            *failures_left -= 1;
+            return Err(InsertLivestreamError::DatabaseError(
+                sqlx::Error::PoolTimedOut,
+            ));
+        }
+
+        self.inserted.lock().unwrap().push(message);
+        Ok(())
+    }
+
+    async fn query(
+        &self,
+        _device_id: i32,
+        _action: LivestreamQueryAction,
+        _interval: LivestreamInterval,
+        _range: LivestreamRange,
+    ) -> Result<DeviceLivestreamQueryResult, QueryLivestreamError> {
+        Err(QueryLivestreamError::UnsupportedQuery)
+    }
+}
+
+struct TestListener {
+    queue: InMemoryQueue,
+    _notify_shutdown: broadcast::Sender<()>,
+}
+
+impl TestListener {
+    fn spawn(service: Arc<FakeLivestreamService>, retry_policy: RetryPolicy) -> Self {
+        let queue = InMemoryQueue::new();
+        let (notify_shutdown, _) = broadcast::channel::<()>(1);
+        let (shutdown_complete_tx, _) = mpsc::channel::<()>(1);
+
+        let consumer = InMemoryConsumer::new(
+            queue.clone(),
+            DEVICE_LIVESTREAM_QUEUE_NAME.into(),
+            "listener".into(),
+        )
+        .with_claim_min_idle(Duration::from_millis(100));
+
+        let shutdown = Shutdown::new(notify_shutdown.subscribe());
+        actix_web::rt::spawn(async move {
+            listener::run(
+                shutdown,
+                shutdown_complete_tx,
+                Box::new(consumer),
+                retry_policy,
+                service,
+                Arc::new(Mutex::new(HashMap::new())),
+                Arc::new(Mutex::new(HashMap::new())),
+            )
+            .await;
+        });
+
+        TestListener {
+            queue,
+            _notify_shutdown: notify_shutdown,
+        }
+    }
+
+    async fn push(&self, data: &str) {
+        let mut payload = std::collections::BTreeMap::new();
+        payload.insert("data".to_string(), data.to_string());
+
+        InMemoryProducer::new(self.queue.clone(), DEVICE_LIVESTREAM_QUEUE_NAME.into())
+            .push(payload)
+            .await
+            .unwrap();
+    }
+
+    fn inspector(&self, queue_name: String) -> InMemoryConsumer {
+        InMemoryConsumer::new(self.queue.clone(), queue_name, "inspector".into())
+    }
+
+    async fn pending_count(&self) -> usize {
+        self.inspector(DEVICE_LIVESTREAM_QUEUE_NAME.into())
+            .info()
+            .await
+            .unwrap()
+            .pending_count
+    }
+
+    async fn dead_letters(&self) -> Vec<String> {
+        self.inspector(dead_letter_queue_name(DEVICE_LIVESTREAM_QUEUE_NAME))
+            .read_latest_raw(10)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|(_, data)| data)
+            .collect()
+    }
+}
+
+async fn wait_until(condition: impl Fn() -> bool) {
+    let started_at = Instant::now();
+    while !condition() {
+        assert!(
+            started_at.elapsed() < Duration::from_secs(5),
+            "Timed out waiting for the listener"
+        );
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+}
+
+#[actix_web::test]
+async fn listener_stores_and_acknowledges_messages() {
+    let service = Arc::new(FakeLivestreamService::default());
+    let listener = TestListener::spawn(
+        service.clone(),
+        RetryPolicy::new(5, Duration::from_millis(10)),
+    );
+
+    listener.push(VALID_MESSAGE).await;
+    wait_until(|| service.inserted_count() == 1).await;
+
+    let inserted = service.inserted.lock().unwrap()[0].clone();
+    assert_eq!(1, inserted.device_id);
+    assert_eq!(3, inserted.num_of_faces);
+
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(0, listener.pending_count().await);
+    assert!(listener.dead_letters().await.is_empty());
+}
+
+#[actix_web::test]
+async fn listener_retries_failed_messages() {
+    let service = Arc::new(FakeLivestreamService::failing(2));
+    let listener = TestListener::spawn(
+        service.clone(),
+        RetryPolicy::new(5, Duration::from_millis(10)),
+    );
+
+    listener.push(VALID_MESSAGE).await;
+    wait_until(|| service.inserted_count() == 1).await;
+
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(0, listener.pending_count().await);
+    assert!(listener.dead_letters().await.is_empty());
+}
+
+#[actix_web::test]
+async fn listener_dead_letters_exhausted_messages() {
+    let service = Arc::new(FakeLivestreamService::failing(usize::MAX));
+    let listener = TestListener::spawn(
+        service.clone(),
+        RetryPolicy::new(2, Duration::from_millis(10)),
+    );
+
+    listener.push(VALID_MESSAGE).await;
+
+    let started_at = Instant::now();
+    while listener.dead_letters().await.is_empty() {
+        assert!(started_at.elapsed() < Duration::from_secs(5));
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    assert_eq!(
+        vec![VALID_MESSAGE.to_string()],
+        listener.dead_letters().await
+    );
+    assert_eq!(0, listener.pending_count().await);
+    assert_eq!(0, service.inserted_count());
+}
+
+#[actix_web::test]
+async fn listener_dead_letters_malformed_messages_right_away() {
+    let service = Arc::new(FakeLivestreamService::default());
+    let listener = TestListener::spawn(
+        service.clone(),
+        RetryPolicy::new(5, Duration::from_secs(60)),
+    );
+
+    listener.push("not a livestream message").await;
+    listener.push(VALID_MESSAGE).await;
+    wait_until(|| service.inserted_count() == 1).await;
+
+    assert_eq!(
+        vec!["not a livestream message".to_string()],
+        listener.dead_letters().await
+    );
+}
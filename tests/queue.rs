@@ -0,0 +1,320 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use enchiridion_api::database::UnitOfWork;
+use enchiridion_api::features::announcement::{
+    device_queue_name, AnnouncementQueue, AnnouncementQueueInterface, AnnouncementSyncAction,
+    DeviceSynchronizationParams, DEVICE_SYNCHRONIZATION_MESSAGE_TYPE,
+};
+use enchiridion_api::outbox::{
+    relay::relay_messages, InsertOutboxMessageParams, OutboxMessage, OutboxRepositoryInterface,
+};
+use enchiridion_api::queue::{
+    ConsumerInterface, Envelope, InMemoryConsumer, InMemoryProducer, InMemoryQueue,
+    ProducerInterface, TrimPolicy,
+};
+
+const QUEUE_NAME: &str = "test-queue";
+
+#[derive(Default)]
+struct FakeOutboxRepository {
+    delivered_ids: Mutex<Vec<i32>>,
+    deleted_queue_names: Mutex<Vec<String>>,
+}
+
+#[async_trait]
+impl OutboxRepositoryInterface for FakeOutboxRepository {
+    async fn insert(
+        &self,
+        _unit_of_work: &mut UnitOfWork,
+        _messages: Vec<InsertOutboxMessageParams>,
+    ) -> Result<(), sqlx::Error> {
+        Ok(())
+    }
+
+    async fn find_undelivered(&self, _limit: i32) -> Result<Vec<OutboxMessage>, sqlx::Error> {
+        Ok(vec![])
+    }
+
+    async fn mark_delivered(&self, message_id: i32) -> Result<(), sqlx::Error> {
+        self.delivered_ids.lock().unwrap().push(message_id);
+        Ok(())
+    }
+
+    async fn record_failure(&self, _message_id: i32, _error: String) -> Result<(), sqlx::Error> {
+        Ok(())
+    }
+
+    async fn delete_undelivered(&self, queue_name: String) -> Result<(), sqlx::Error> {
+        self.deleted_queue_names.lock().unwrap().push(queue_name);
+        Ok(())
+    }
+
+    async fn delete_delivered(
+        &self,
+        _delivered_before: chrono::DateTime<chrono::Utc>,
+    ) -> Result<u64, sqlx::Error> {
+        Ok(0)
+    }
+}
+
+fn payload(data: &str) -> BTreeMap<String, String> {
+    let mut payload: BTreeMap<String, String> = BTreeMap::new();
+    payload.insert("data".into(), data.into());
+    payload
+}
+
+fn consumer(queue: &InMemoryQueue, queue_name: &str, consumer_name: &str) -> InMemoryConsumer {
+    InMemoryConsumer::new(queue.clone(), queue_name.into(), consumer_name.into())
+        .with_claim_min_idle(Duration::from_millis(20))
+}
+
+#[tokio::test]
+async fn consumer_group_delivers_each_message_once() {
+    let queue = InMemoryQueue::new();
+    let producer = InMemoryProducer::new(queue.clone(), QUEUE_NAME.into());
+    producer.push(payload("first")).await.unwrap();
+    producer.push(payload("second")).await.unwrap();
+
+    let mut consumer_1 = consumer(&queue, QUEUE_NAME, "consumer-1");
+    let mut consumer_2 = consumer(&queue, QUEUE_NAME, "consumer-2");
+
+    let first = consumer_1.consume_raw().await.unwrap();
+    let second = consumer_2.consume_raw().await.unwrap();
+    assert_eq!("first", first[0].1);
+    assert_eq!("second", second[0].1);
+    assert!(consumer_1.consume_raw().await.unwrap().is_empty());
+
+    let info = consumer_1.info().await.unwrap();
+    assert_eq!(2, info.length);
+    assert_eq!(2, info.pending_count);
+    assert_eq!(Some(first[0].0.clone()), info.oldest_pending_id);
+    assert_eq!(Some(second[0].0.clone()), info.last_delivered_id);
+
+    consumer_1.ack(first[0].0.clone()).await.unwrap();
+
+    let info = consumer_1.info().await.unwrap();
+    assert_eq!(1, info.pending_count);
+    assert_eq!(Some(second[0].0.clone()), info.oldest_pending_id);
+}
+
+#[tokio::test]
+async fn consume_waits_for_upcoming_messages() {
+    let queue = InMemoryQueue::new();
+    let mut consumer =
+        consumer(&queue, QUEUE_NAME, "consumer-1").with_claim_min_idle(Duration::from_secs(5));
+    assert_eq!(0, consumer.info().await.unwrap().length);
+
+    let producer = InMemoryProducer::new(queue.clone(), QUEUE_NAME.into());
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        producer.push(payload("late")).await.unwrap();
+    });
+
+    let data = consumer.consume_raw().await.unwrap();
+    assert_eq!("late", data[0].1);
+}
+
+#[tokio::test]
+async fn claiming_a_pending_message_counts_the_delivery() {
+    let queue = InMemoryQueue::new();
+    let producer = InMemoryProducer::new(queue.clone(), QUEUE_NAME.into());
+    producer.push(payload("message")).await.unwrap();
+
+    let mut consumer = consumer(&queue, QUEUE_NAME, "consumer-1");
+    let data = consumer.consume_raw().await.unwrap();
+    let message_id = data[0].0.clone();
+
+    let pending = consumer.get_pending_message().await.unwrap().unwrap();
+    assert_eq!(message_id, pending.id);
+    assert_eq!(1, pending.times_delivered);
+
+    // Not idle long enough yet
+    let claimed = consumer
+        .claim_raw(message_id.clone(), Duration::from_secs(60))
+        .await
+        .unwrap();
+    assert!(claimed.is_empty());
+
+    let claimed = consumer
+        .claim_raw(message_id.clone(), Duration::ZERO)
+        .await
+        .unwrap();
+    assert_eq!("message", claimed[0].1);
+
+    let pending = consumer.get_pending_message().await.unwrap().unwrap();
+    assert_eq!(2, pending.times_delivered);
+}
+
+#[tokio::test]
+async fn stale_messages_are_claimed_by_another_consumer() {
+    let queue = InMemoryQueue::new();
+    let producer = InMemoryProducer::new(queue.clone(), QUEUE_NAME.into());
+    producer.push(payload("message")).await.unwrap();
+
+    let mut crashed_consumer = consumer(&queue, QUEUE_NAME, "consumer-1");
+    let data = crashed_consumer.consume_raw().await.unwrap();
+
+    let mut consumer = consumer(&queue, QUEUE_NAME, "consumer-2");
+    assert!(consumer.claim_stale_messages().await.unwrap().is_empty());
+    assert!(consumer.get_pending_message().await.unwrap().is_none());
+
+    tokio::time::sleep(Duration::from_millis(30)).await;
+
+    let claimed_ids = consumer.claim_stale_messages().await.unwrap();
+    assert_eq!(vec![data[0].0.clone()], claimed_ids);
+
+    let pending = consumer.get_pending_message().await.unwrap().unwrap();
+    assert_eq!(data[0].0, pending.id);
+    assert!(crashed_consumer
+        .get_pending_message()
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn dead_lettered_messages_are_acknowledged() {
+    let queue = InMemoryQueue::new();
+    let producer = InMemoryProducer::new(queue.clone(), QUEUE_NAME.into());
+    producer.push(payload("poison")).await.unwrap();
+
+    let mut consumer = consumer(&queue, QUEUE_NAME, "consumer-1");
+    let data = consumer.consume_raw().await.unwrap();
+    consumer
+        .dead_letter(data[0].0.clone(), data[0].1.clone(), "broken".into(), 1)
+        .await
+        .unwrap();
+
+    assert_eq!(0, consumer.info().await.unwrap().pending_count);
+
+    let mut dead_letters = InMemoryConsumer::new(
+        queue.clone(),
+        format!("{}-dlq", QUEUE_NAME),
+        "inspector".into(),
+    );
+    let dead_lettered = dead_letters.read_latest_raw(10).await.unwrap();
+    assert_eq!(1, dead_lettered.len());
+    assert_eq!("poison", dead_lettered[0].1);
+}
+
+#[tokio::test]
+async fn producer_trims_the_queue() {
+    let queue = InMemoryQueue::new();
+    let producer = InMemoryProducer::new(queue.clone(), QUEUE_NAME.into())
+        .with_trim_policy(TrimPolicy::MaxLen(2));
+    for data in ["first", "second", "third"] {
+        producer.push(payload(data)).await.unwrap();
+    }
+
+    let mut consumer = consumer(&queue, QUEUE_NAME, "consumer-1");
+    let latest: Vec<String> = consumer
+        .read_latest_raw(10)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|(_, data)| data)
+        .collect();
+    assert_eq!(vec!["third".to_string(), "second".to_string()], latest);
+}
+
+#[tokio::test]
+async fn relayed_synchronization_messages_reach_the_device_queues() {
+    let queue = InMemoryQueue::new();
+    let outbox_repository = Arc::new(FakeOutboxRepository::default());
+    let announcement_queue =
+        AnnouncementQueue::new(outbox_repository.clone(), Arc::new(queue.clone()));
+
+    let params = DeviceSynchronizationParams::new(AnnouncementSyncAction::Create)
+        .announcement_id(10)
+        .media_type("image".into());
+    let messages = match announcement_queue.synchronization_messages(vec![1, 2], &params) {
+        Ok(messages) => messages,
+        Err(e) => panic!("Unable to build the synchronization messages: {}", e),
+    };
+    let messages: Vec<OutboxMessage> = messages
+        .into_iter()
+        .enumerate()
+        .map(|(i, message)| OutboxMessage {
+            id: i as i32 + 1,
+            queue_name: message.queue_name,
+            payload: message.payload,
+            attempts: 0,
+        })
+        .collect();
+
+    relay_messages(
+        &queue,
+        outbox_repository.clone(),
+        messages,
+        &TrimPolicy::None,
+    )
+    .await;
+
+    assert_eq!(vec![1, 2], *outbox_repository.delivered_ids.lock().unwrap());
+
+    let mut message_ids = vec![];
+    for device_id in [1, 2] {
+        let mut consumer = consumer(&queue, &device_queue_name(device_id), "device");
+        let data = consumer.consume_raw().await.unwrap();
+        assert_eq!(1, data.len());
+
+        let envelope: Envelope<serde_json::Value> = serde_json::from_str(&data[0].1).unwrap();
+        assert_eq!(DEVICE_SYNCHRONIZATION_MESSAGE_TYPE, envelope.message_type);
+        assert_eq!("create", envelope.payload["action"]);
+        assert_eq!(10, envelope.payload["announcement_id"]);
+        message_ids.push(envelope.message_id);
+    }
+    assert_ne!(message_ids[0], message_ids[1]);
+}
+
+#[tokio::test]
+async fn removing_a_device_queue_drops_its_messages() {
+    let queue = InMemoryQueue::new();
+    let outbox_repository = Arc::new(FakeOutboxRepository::default());
+    let announcement_queue =
+        AnnouncementQueue::new(outbox_repository.clone(), Arc::new(queue.clone()));
+
+    let queue_name = device_queue_name(1);
+    let producer = InMemoryProducer::new(queue.clone(), queue_name.clone());
+    producer.push(payload("message")).await.unwrap();
+
+    if announcement_queue.remove_device_queue(1).await.is_err() {
+        panic!("Unable to remove the device queue");
+    }
+
+    assert_eq!(
+        vec![queue_name.clone()],
+        *outbox_repository.deleted_queue_names.lock().unwrap()
+    );
+
+    let mut consumer = consumer(&queue, &queue_name, "device");
+    assert_eq!(0, consumer.info().await.unwrap().length);
+}
+
+#[tokio::test]
+async fn emergency_messages_carry_their_own_actions() {
+    let queue = InMemoryQueue::new();
+    let announcement_queue = AnnouncementQueue::new(
+        Arc::new(FakeOutboxRepository::default()),
+        Arc::new(queue.clone()),
+    );
+
+    for (action, expected) in [
+        (AnnouncementSyncAction::Emergency, "emergency"),
+        (AnnouncementSyncAction::ClearEmergency, "clearEmergency"),
+    ] {
+        let params = DeviceSynchronizationParams::new(action).announcement_id(10);
+        let messages = match announcement_queue.synchronization_messages(vec![1], &params) {
+            Ok(messages) => messages,
+            Err(e) => panic!("Unable to build the synchronization messages: {}", e),
+        };
+
+        let envelope: Envelope<serde_json::Value> =
+            serde_json::from_str(&messages[0].payload).unwrap();
+        assert_eq!(expected, envelope.payload["action"]);
+        assert_eq!(10, envelope.payload["announcement_id"]);
+    }
+}
@@ -0,0 +1,318 @@
+use enchiridion_api::features::request::{
+    can_comment_on_request, can_delegate_approval, decide_request_approval, ApprovalWorkflow,
+    ApprovalWorkflowStage, RequestApprovalDecisionError, RequestApprovalStep, RequestApprover,
+    RequestApproverDelegator, RequestStatus,
+};
+
+fn step(id: i32, roles: &[&str]) -> RequestApprovalStep {
+    RequestApprovalStep {
+        id,
+        position: id,
+        roles: roles.iter().map(|role| role.to_string()).collect(),
+        approved: None,
+        approver_id: None,
+        on_behalf_of: None,
+        approved_at: None,
+    }
+}
+
+/// The default workflow, LSC and BM in any order or an admin for both
+fn default_workflow_steps() -> Vec<RequestApprovalStep> {
+    let mut bm = step(2, &["bm", "admin"]);
+    bm.position = 1;
+
+    vec![step(1, &["lsc", "admin"]), bm]
+}
+
+fn approver(id: i32, role: &str) -> RequestApprover {
+    RequestApprover {
+        id,
+        role: role.to_string(),
+        delegators: vec![],
+    }
+}
+
+fn decided(mut step: RequestApprovalStep, approved: bool, approver_id: i32) -> RequestApprovalStep {
+    step.approved = Some(approved);
+    step.approver_id = Some(approver_id);
+    step
+}
+
+#[test]
+fn single_stage_workflow_is_approved_by_one_approver() {
+    let steps = vec![step(1, &["ssc"])];
+
+    let approval = decide_request_approval(&steps, &approver(10, "ssc"), true, None).unwrap();
+
+    assert_eq!(approval.step_ids, vec![1]);
+    assert_eq!(approval.status, RequestStatus::Approved);
+}
+
+#[test]
+fn stages_are_decided_in_order() {
+    let steps = vec![step(1, &["lsc"]), step(2, &["bm"]), step(3, &["admin"])];
+
+    assert_eq!(
+        decide_request_approval(&steps, &approver(10, "bm"), true, None).unwrap_err(),
+        RequestApprovalDecisionError::RoleNotAllowed
+    );
+
+    let approval = decide_request_approval(&steps, &approver(10, "lsc"), true, None).unwrap();
+    assert_eq!(approval.step_ids, vec![1]);
+    assert_eq!(approval.status, RequestStatus::Pending);
+}
+
+#[test]
+fn last_stage_completes_the_approval() {
+    let steps = vec![
+        decided(step(1, &["lsc"]), true, 10),
+        step(2, &["bm", "admin"]),
+    ];
+
+    let approval = decide_request_approval(&steps, &approver(11, "admin"), true, None).unwrap();
+
+    assert_eq!(approval.step_ids, vec![2]);
+    assert_eq!(approval.status, RequestStatus::Approved);
+}
+
+#[test]
+fn stages_sharing_a_position_are_decided_in_any_order() {
+    let steps = default_workflow_steps();
+
+    let approval = decide_request_approval(&steps, &approver(11, "bm"), true, None).unwrap();
+    assert_eq!(approval.step_ids, vec![2]);
+    assert_eq!(approval.status, RequestStatus::Pending);
+
+    let steps = vec![steps[0].clone(), decided(steps[1].clone(), true, 11)];
+    let approval = decide_request_approval(&steps, &approver(10, "lsc"), true, None).unwrap();
+    assert_eq!(approval.step_ids, vec![1]);
+    assert_eq!(approval.status, RequestStatus::Approved);
+}
+
+#[test]
+fn a_single_admin_completes_the_default_workflow() {
+    let steps = default_workflow_steps();
+
+    let approval = decide_request_approval(&steps, &approver(12, "admin"), true, None).unwrap();
+
+    assert_eq!(approval.step_ids, vec![1, 2]);
+    assert_eq!(approval.status, RequestStatus::Approved);
+}
+
+#[test]
+fn an_admin_completes_the_stage_left_by_lsc() {
+    let steps = default_workflow_steps();
+    let steps = vec![decided(steps[0].clone(), true, 10), steps[1].clone()];
+
+    let approval = decide_request_approval(&steps, &approver(12, "admin"), true, None).unwrap();
+
+    assert_eq!(approval.step_ids, vec![2]);
+    assert_eq!(approval.status, RequestStatus::Approved);
+}
+
+#[test]
+fn any_stage_can_reject_the_request() {
+    let steps = vec![step(1, &["lsc"]), step(2, &["bm"])];
+
+    let approval = decide_request_approval(
+        &steps,
+        &approver(10, "lsc"),
+        false,
+        Some("Wrong building".to_string()),
+    )
+    .unwrap();
+
+    assert_eq!(approval.status, RequestStatus::Rejected);
+    assert_eq!(approval.reason, Some("Wrong building".to_string()));
+}
+
+#[test]
+fn rejections_need_a_reason() {
+    let steps = vec![step(1, &["lsc"])];
+
+    assert_eq!(
+        decide_request_approval(&steps, &approver(10, "lsc"), false, None).unwrap_err(),
+        RequestApprovalDecisionError::RejectionReasonRequired
+    );
+    assert_eq!(
+        decide_request_approval(&steps, &approver(10, "lsc"), false, Some("  ".to_string()))
+            .unwrap_err(),
+        RequestApprovalDecisionError::RejectionReasonRequired
+    );
+}
+
+#[test]
+fn an_approver_can_only_decide_on_one_stage() {
+    let steps = vec![decided(step(1, &["admin"]), true, 10), step(2, &["admin"])];
+
+    assert_eq!(
+        decide_request_approval(&steps, &approver(10, "admin"), true, None).unwrap_err(),
+        RequestApprovalDecisionError::AlreadyApprovedByApprover
+    );
+}
+
+#[test]
+fn decided_requests_can_not_be_approved_again() {
+    let rejected = vec![decided(step(1, &["lsc"]), false, 10), step(2, &["bm"])];
+    let approved = vec![decided(step(1, &["lsc"]), true, 10)];
+
+    assert_eq!(
+        RequestStatus::from_steps(&rejected),
+        RequestStatus::Rejected
+    );
+    assert_eq!(
+        decide_request_approval(&rejected, &approver(11, "bm"), true, None).unwrap_err(),
+        RequestApprovalDecisionError::AlreadyDecided
+    );
+    assert_eq!(
+        decide_request_approval(&approved, &approver(11, "lsc"), true, None).unwrap_err(),
+        RequestApprovalDecisionError::AlreadyDecided
+    );
+}
+
+#[test]
+fn delegates_can_approve_with_the_role_of_their_delegator() {
+    let steps = vec![step(1, &["lsc"]), step(2, &["bm"])];
+    let mut delegate = approver(20, "ssc");
+    delegate.delegators.push(RequestApproverDelegator {
+        user_id: 10,
+        role: "lsc".to_string(),
+    });
+
+    let approval = decide_request_approval(&steps, &delegate, true, None).unwrap();
+
+    assert_eq!(approval.step_ids, vec![1]);
+    assert_eq!(approval.approver_id, 20);
+    assert_eq!(approval.on_behalf_of, Some(10));
+}
+
+#[test]
+fn approvers_use_their_own_role_before_a_delegated_one() {
+    let steps = vec![step(1, &["lsc", "admin"])];
+    let mut delegate = approver(20, "admin");
+    delegate.delegators.push(RequestApproverDelegator {
+        user_id: 10,
+        role: "lsc".to_string(),
+    });
+
+    let approval = decide_request_approval(&steps, &delegate, true, None).unwrap();
+
+    assert_eq!(approval.on_behalf_of, None);
+}
+
+#[test]
+fn delegates_can_not_decide_twice_for_the_same_delegator() {
+    let steps = vec![decided(step(1, &["admin"]), true, 10), step(2, &["admin"])];
+    let mut delegate = approver(20, "ssc");
+    delegate.delegators.push(RequestApproverDelegator {
+        user_id: 10,
+        role: "admin".to_string(),
+    });
+
+    assert_eq!(
+        decide_request_approval(&steps, &delegate, true, None).unwrap_err(),
+        RequestApprovalDecisionError::AlreadyApprovedByApprover
+    );
+}
+
+#[test]
+fn an_lsc_who_approved_can_complete_the_request_for_an_absent_bm() {
+    let steps = default_workflow_steps();
+    let steps = vec![decided(steps[0].clone(), true, 10), steps[1].clone()];
+    let mut lsc = approver(10, "lsc");
+    lsc.delegators.push(RequestApproverDelegator {
+        user_id: 11,
+        role: "bm".to_string(),
+    });
+
+    let approval = decide_request_approval(&steps, &lsc, true, None).unwrap();
+
+    assert_eq!(approval.step_ids, vec![2]);
+    assert_eq!(approval.approver_id, 10);
+    assert_eq!(approval.on_behalf_of, Some(11));
+    assert_eq!(approval.status, RequestStatus::Approved);
+}
+
+#[test]
+fn a_bm_delegate_completes_a_request_decided_by_lsc() {
+    let steps = default_workflow_steps();
+    let steps = vec![decided(steps[0].clone(), true, 10), steps[1].clone()];
+    let mut delegate = approver(20, "ssc");
+    delegate.delegators.push(RequestApproverDelegator {
+        user_id: 11,
+        role: "bm".to_string(),
+    });
+
+    let approval = decide_request_approval(&steps, &delegate, true, None).unwrap();
+
+    assert_eq!(approval.step_ids, vec![2]);
+    assert_eq!(approval.on_behalf_of, Some(11));
+    assert_eq!(approval.status, RequestStatus::Approved);
+}
+
+#[test]
+fn the_author_can_always_comment() {
+    let steps = vec![decided(step(1, &["lsc"]), false, 10)];
+
+    assert!(can_comment_on_request(30, &steps, &approver(30, "ssc")));
+}
+
+#[test]
+fn only_approvers_of_the_current_stage_can_comment() {
+    let steps = vec![step(1, &["lsc"]), step(2, &["bm"])];
+
+    assert!(can_comment_on_request(30, &steps, &approver(10, "lsc")));
+    assert!(!can_comment_on_request(30, &steps, &approver(11, "bm")));
+    assert!(!can_comment_on_request(30, &steps, &approver(12, "ssc")));
+}
+
+#[test]
+fn delegates_can_comment_for_their_delegator() {
+    let steps = default_workflow_steps();
+    let mut delegate = approver(20, "ssc");
+    delegate.delegators.push(RequestApproverDelegator {
+        user_id: 11,
+        role: "bm".to_string(),
+    });
+
+    assert!(can_comment_on_request(30, &steps, &delegate));
+}
+
+#[test]
+fn nobody_but_the_author_comments_on_a_decided_request() {
+    let steps = vec![decided(step(1, &["lsc"]), true, 10)];
+
+    assert!(!can_comment_on_request(30, &steps, &approver(10, "lsc")));
+}
+
+fn workflow(stages: &[&[&str]]) -> ApprovalWorkflow {
+    ApprovalWorkflow {
+        id: 1,
+        building_id: None,
+        action: None,
+        stages: stages
+            .iter()
+            .map(|roles| ApprovalWorkflowStage {
+                position: 1,
+                roles: roles.iter().map(|role| role.to_string()).collect(),
+            })
+            .collect(),
+    }
+}
+
+#[test]
+fn approvers_of_any_workflow_stage_can_delegate() {
+    let workflows = vec![workflow(&[&["lsc", "admin"], &["bm", "admin"]])];
+
+    assert!(can_delegate_approval(&workflows, "lsc"));
+    assert!(can_delegate_approval(&workflows, "bm"));
+    assert!(can_delegate_approval(&workflows, "admin"));
+}
+
+#[test]
+fn users_who_approve_no_stage_can_not_delegate() {
+    let workflows = vec![workflow(&[&["lsc", "admin"], &["bm", "admin"]])];
+
+    assert!(!can_delegate_approval(&workflows, "ssc"));
+    assert!(!can_delegate_approval(&[], "lsc"));
+}
@@ -0,0 +1,344 @@
+//! Runs against the database at `DATABASE_URL` with the migrations applied, the tests are skipped
+//! when it is not set.
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use deadpool_redis::redis::RedisError;
+use sqlx::{PgPool, Row};
+
+use enchiridion_api::database::{UnitOfWorkFactory, UnitOfWorkFactoryInterface};
+use enchiridion_api::features::announcement::{
+    AnnouncementQueue, AnnouncementRepository, AnnouncementRepositoryInterface, AnnouncementStatus,
+};
+use enchiridion_api::features::auth::{AuthRepositoryInterface, UserAuthEntity};
+use enchiridion_api::features::device::DeviceRepository;
+use enchiridion_api::features::media::repository::MediaRepository;
+use enchiridion_api::features::request::{
+    BulkUpdateRequestApprovalParams, InsertRequestParams, RequestActionType, RequestRepository,
+    RequestRepositoryInterface, RequestService, RequestServiceInterface, RequestStatus,
+    UpdateRequestApprovalParams,
+};
+use enchiridion_api::features::role::RoleObject;
+use enchiridion_api::features::user::UserStatus;
+use enchiridion_api::outbox::OutboxRepository;
+use enchiridion_api::queue::InMemoryQueue;
+
+static FIXTURE_COUNTER: AtomicU32 = AtomicU32::new(0);
+
+/// Approvers are looked up by id only, their role is kept next to the id instead of in the
+/// configuration the real repository needs
+struct FixedAuthRepository {
+    users: Vec<(i32, &'static str)>,
+}
+
+#[async_trait]
+impl AuthRepositoryInterface for FixedAuthRepository {
+    async fn find_one_auth_entity_by_email(
+        &self,
+        _email: String,
+    ) -> Result<UserAuthEntity, sqlx::Error> {
+        Err(sqlx::Error::RowNotFound)
+    }
+
+    async fn find_one_auth_entity_by_id(&self, id: i32) -> Result<UserAuthEntity, sqlx::Error> {
+        let role = match self.users.iter().find(|(user_id, _)| *user_id == id) {
+            Some((_, role)) => *role,
+            None => return Err(sqlx::Error::RowNotFound),
+        };
+
+        Ok(UserAuthEntity {
+            id,
+            name: format!("User {}", id),
+            email: format!("user-{}@example.com", id),
+            profile_picture: None,
+            is_email_confirmed: true,
+            user_status: UserStatus::Approved,
+            role: RoleObject {
+                name: role,
+                value: role,
+                description: role,
+                permissions: vec![],
+            },
+            building: None,
+        })
+    }
+
+    async fn get_user_refresh_token(&self, _user_id: i32) -> Result<String, RedisError> {
+        unimplemented!()
+    }
+
+    async fn set_user_refresh_token(
+        &self,
+        _user_id: i32,
+        _refresh_token: String,
+    ) -> Result<(), RedisError> {
+        unimplemented!()
+    }
+
+    async fn delete_user_refresh_token(&self, _user_id: i32) -> Result<(), RedisError> {
+        unimplemented!()
+    }
+}
+
+struct Fixture {
+    pool: PgPool,
+    author_id: i32,
+    lsc_id: i32,
+    bm_id: i32,
+    media_id: i32,
+    device_id: i32,
+    request_service: RequestService,
+    request_repository: Arc<RequestRepository>,
+    announcement_repository: Arc<AnnouncementRepository>,
+}
+
+fn unique_suffix() -> String {
+    format!(
+        "{}-{}",
+        chrono::Utc::now().timestamp_nanos(),
+        FIXTURE_COUNTER.fetch_add(1, Ordering::SeqCst)
+    )
+}
+
+async fn insert_user(pool: &PgPool, role: &str) -> i32 {
+    let suffix = unique_suffix();
+
+    sqlx::query(
+        r#"
+        insert into "user" ("name", "email", "password", "password_salt", "role", "status", "is_email_confirmed")
+        values ($1, $2, '\x00', '', $3, 'approved', true)
+        returning "id"
+        "#,
+    )
+    .bind(format!("{} {}", role, suffix))
+    .bind(format!("{}-{}@example.com", role, suffix))
+    .bind(role)
+    .fetch_one(pool)
+    .await
+    .unwrap()
+    .get("id")
+}
+
+async fn fixture() -> Option<Fixture> {
+    let database_url = match std::env::var("DATABASE_URL") {
+        Ok(url) => url,
+        Err(_) => {
+            eprintln!("DATABASE_URL is not set, skipping");
+            return None;
+        }
+    };
+    let pool = PgPool::connect(&database_url).await.unwrap();
+
+    let author_id = insert_user(&pool, "ssc").await;
+    let lsc_id = insert_user(&pool, "lsc").await;
+    let bm_id = insert_user(&pool, "bm").await;
+
+    let media_id: i32 = sqlx::query(
+        r#"
+        insert into "media" ("path", "media_type") values ('race.png', 'image') returning "id"
+        "#,
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap()
+    .get("id");
+
+    // Announcements are only found through the devices they are shown on
+    let device_id: i32 = sqlx::query(
+        r#"
+        with "building" as (
+            insert into "building" ("name", "color") values ($1, '#000000') returning "id"
+        ), "floor" as (
+            insert into "floor" ("name", "building_id") select $1, "id" from "building" returning "id"
+        )
+        insert into "device" ("name", "description", "access_key_id", "secret_access_key", "secret_access_key_salt", "floor_id")
+        select $1, '', $1, '\x00', '', "id" from "floor"
+        returning "id"
+        "#,
+    )
+    .bind(unique_suffix())
+    .fetch_one(&pool)
+    .await
+    .unwrap()
+    .get("id");
+
+    // The pool is never used, redis is only reached by the device status lookups
+    let redis_pool = deadpool_redis::Config::from_url("redis://127.0.0.1:6379")
+        .create_pool(Some(deadpool_redis::Runtime::Tokio1))
+        .unwrap();
+
+    let request_repository = Arc::new(RequestRepository::new(pool.clone()));
+    let announcement_repository = Arc::new(AnnouncementRepository::new(pool.clone()));
+    let announcement_queue = Arc::new(AnnouncementQueue::new(
+        Arc::new(OutboxRepository::new(pool.clone())),
+        Arc::new(InMemoryQueue::new()),
+    ));
+
+    let request_service = RequestService::new(
+        announcement_queue,
+        request_repository.clone(),
+        announcement_repository.clone(),
+        Arc::new(FixedAuthRepository {
+            users: vec![(author_id, "ssc"), (lsc_id, "lsc"), (bm_id, "bm")],
+        }),
+        Arc::new(DeviceRepository::new(pool.clone(), redis_pool)),
+        Arc::new(MediaRepository::new(pool.clone())),
+        Arc::new(UnitOfWorkFactory::new(pool.clone())),
+    );
+
+    Some(Fixture {
+        pool,
+        author_id,
+        lsc_id,
+        bm_id,
+        media_id,
+        device_id,
+        request_service,
+        request_repository,
+        announcement_repository,
+    })
+}
+
+/// An announcement waiting for approval that already started, with the create request of the default
+/// workflow (LSC and BM in any order)
+async fn create_request(fixture: &Fixture) -> (i32, i32) {
+    let announcement_id: i32 = sqlx::query(
+        r#"
+        insert into "announcement" ("title", "start_date", "end_date", "status", "notes", "user_id", "media_id")
+        values ('Race', now() - interval '1 hour', now() + interval '1 day', 'waiting_for_approval', '', $1, $2)
+        returning "id"
+        "#,
+    )
+    .bind(fixture.author_id)
+    .bind(fixture.media_id)
+    .fetch_one(&fixture.pool)
+    .await
+    .unwrap()
+    .get("id");
+
+    sqlx::query(
+        r#"
+        insert into "device_announcement" ("device_id", "announcement_id") values ($1, $2)
+        "#,
+    )
+    .bind(fixture.device_id)
+    .bind(announcement_id)
+    .execute(&fixture.pool)
+    .await
+    .unwrap();
+
+    let mut unit_of_work = UnitOfWorkFactory::new(fixture.pool.clone())
+        .begin()
+        .await
+        .unwrap();
+    let request_id = fixture
+        .request_repository
+        .insert(
+            &mut unit_of_work,
+            InsertRequestParams::new(
+                RequestActionType::Create,
+                "Race".into(),
+                announcement_id,
+                fixture.author_id,
+            ),
+        )
+        .await
+        .unwrap();
+    unit_of_work.commit().await.unwrap();
+
+    (request_id, announcement_id)
+}
+
+async fn assert_approved_and_active(fixture: &Fixture, request_id: i32, announcement_id: i32) {
+    let request = fixture
+        .request_repository
+        .find_one(request_id)
+        .await
+        .unwrap();
+    assert_eq!(request.status(), RequestStatus::Approved);
+
+    let announcement = fixture
+        .announcement_repository
+        .find_one(announcement_id)
+        .await
+        .unwrap();
+    assert_eq!(announcement.status, AnnouncementStatus::Active);
+}
+
+fn approve(request_id: i32, approver_id: i32) -> UpdateRequestApprovalParams {
+    UpdateRequestApprovalParams {
+        request_id,
+        approver_id,
+        approval: true,
+        reason: None,
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn unordered_stages_approved_at_the_same_time_activate_the_announcement() {
+    let fixture = match fixture().await {
+        Some(fixture) => fixture,
+        None => return,
+    };
+    let (request_id, announcement_id) = create_request(&fixture).await;
+
+    let (lsc_result, bm_result) = tokio::join!(
+        fixture
+            .request_service
+            .update_request_approval(approve(request_id, fixture.lsc_id)),
+        fixture
+            .request_service
+            .update_request_approval(approve(request_id, fixture.bm_id)),
+    );
+
+    assert!(lsc_result.is_ok(), "{:?}", lsc_result);
+    assert!(bm_result.is_ok(), "{:?}", bm_result);
+    assert_approved_and_active(&fixture, request_id, announcement_id).await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn overlapping_bulk_approvals_leave_every_request_decided() {
+    let fixture = match fixture().await {
+        Some(fixture) => fixture,
+        None => return,
+    };
+
+    let mut requests: Vec<(i32, i32)> = Vec::new();
+    for _ in 0..5 {
+        requests.push(create_request(&fixture).await);
+    }
+    let request_ids: Vec<i32> = requests.iter().map(|(request_id, _)| *request_id).collect();
+
+    let (lsc_results, bm_results) = tokio::join!(
+        fixture
+            .request_service
+            .bulk_update_request_approval(BulkUpdateRequestApprovalParams {
+                request_ids: request_ids.clone(),
+                approver_id: fixture.lsc_id,
+                approval: true,
+                reason: None,
+            }),
+        fixture
+            .request_service
+            .bulk_update_request_approval(BulkUpdateRequestApprovalParams {
+                request_ids: request_ids.iter().rev().copied().collect(),
+                approver_id: fixture.bm_id,
+                approval: true,
+                reason: None,
+            }),
+    );
+
+    for result in lsc_results.unwrap().into_iter().chain(bm_results.unwrap()) {
+        assert!(
+            result.result.is_ok(),
+            "request {}: {:?}",
+            result.request_id,
+            result.result
+        );
+    }
+    for (request_id, announcement_id) in requests {
+        assert_approved_and_active(&fixture, request_id, announcement_id).await;
+    }
+}